@@ -1,3 +1,4 @@
+use crate::oracle::PriceRejection;
 use anchor_lang::prelude::*;
 
 #[error_code]
@@ -42,6 +43,84 @@ pub enum ErrorCode {
     AuthorityNotFound,
     #[msg("Invalid PDA address")]
     InvalidPdaAddress,
+    #[msg("Invalid oracle feed provided")]
+    InvalidOracleFeed,
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfig,
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    #[msg("Oracle price is zero or negative")]
+    InvalidOraclePrice,
+    #[msg("Oracle confidence interval is too wide")]
+    OracleConfidenceTooWide,
+    #[msg("Fee rate exceeds the maximum allowed")]
+    FeeTooHigh,
+    #[msg("Maximum number of assets reached")]
+    MaxAssetsReached,
+    #[msg("Asset accounts are missing or out of order")]
+    InvalidAssetAccounts,
+    #[msg("Output is below the minimum accepted")]
+    SlippageExceeded,
+    #[msg("Account is already on the current layout version")]
+    AlreadyMigrated,
+    #[msg("Deposit is too small to mint any LP tokens")]
+    DepositTooSmall,
+    #[msg("Reward duration is zero or too long")]
+    InvalidRewardDuration,
+    #[msg("Rewards are too small to emit anything over the duration")]
+    RewardRateTooLow,
+    #[msg("Maximum number of reward streams reached")]
+    MaxRewardStreamsReached,
+    #[msg("Reward stream accounts are missing or out of order")]
+    InvalidRewardStreamAccounts,
+    #[msg("Account does not have a recognized layout")]
+    InvalidAccountLayout,
+    #[msg("Account must be migrated to the current version first")]
+    NotMigrated,
+    #[msg("User still has LP tokens staked")]
+    StakedLpRemaining,
+    #[msg("Roles must be a non-empty combination of known roles")]
+    InvalidRoles,
+    #[msg("Withdrawal exceeds the pool's outflow limit for this window")]
+    OutflowLimitExceeded,
+    #[msg("Withdrawal exceeds the caller's outflow limit for this window")]
+    CallerOutflowLimitExceeded,
+    #[msg("Invalid outflow limits")]
+    InvalidOutflowLimits,
+    #[msg("This operation is paused")]
+    OperationPaused,
+    #[msg("Pause flags must be a non-empty combination of known operations")]
+    InvalidPauseFlags,
+    #[msg("Deposit would take the pool over its AUM cap")]
+    AumCapExceeded,
+    #[msg("Deposit would take the user's LP position over the per-wallet cap")]
+    UserLpCapExceeded,
+    #[msg("Wallet is not on the allowlist")]
+    NotAllowlisted,
+    #[msg("Vault holds no surplus over the pool's books")]
+    NoSurplus,
+    #[msg("Vault holds no deficit against the pool's books")]
+    NoDeficit,
+    #[msg("Peg band exceeds the maximum allowed")]
+    InvalidPegBand,
+    #[msg("A stablecoin is trading outside its peg band")]
+    StablecoinDepegged,
+    #[msg("Oracle price update is not fully verified")]
+    UnverifiedOraclePrice,
+    #[msg("Pool was not migrated from the legacy pool")]
+    NotLegacyPool,
+    #[msg("The pool's outflow limits account is missing")]
+    MissingOutflowLimits,
+}
+
+impl From<PriceRejection> for ErrorCode {
+    fn from(rejection: PriceRejection) -> Self {
+        match rejection {
+            PriceRejection::Stale => ErrorCode::StaleOraclePrice,
+            PriceRejection::NonPositive => ErrorCode::InvalidOraclePrice,
+            PriceRejection::ConfidenceTooWide => ErrorCode::OracleConfidenceTooWide,
+        }
+    }
 }
 
 // For backward compatibility with existing code
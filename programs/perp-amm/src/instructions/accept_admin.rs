@@ -0,0 +1,38 @@
+use crate::errors::VaultError;
+use crate::state::PoolState;
+use crate::AdminTransferred;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.pending_admin == new_admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+/**
+ * @dev Complete an admin transfer started by `propose_admin`. Must be signed by
+ * the proposed key, which proves it is controlled before it takes over.
+ */
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+
+    let previous_admin = pool_state.admin;
+    pool_state.admin = ctx.accounts.new_admin.key();
+    pool_state.pending_admin = Pubkey::default();
+
+    msg!("Admin transferred to: {}", pool_state.admin);
+    emit!(AdminTransferred {
+        pool: ctx.accounts.pool_state.key(),
+        previous_admin,
+        new_admin: pool_state.admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
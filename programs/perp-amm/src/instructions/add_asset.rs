@@ -0,0 +1,190 @@
+use crate::{
+    errors::VaultError,
+    oracle::{self, OracleKind},
+    state::*,
+    AssetAdded,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+/// Configuration for a newly whitelisted asset
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AddAssetParams {
+    /// Stablecoins are valued at exactly 1 USD while their oracle stays within
+    /// the pool's peg band
+    pub is_stable: bool,
+    pub oracle_kind: OracleKind,
+    /// Feed id the oracle must publish the price under (Pyth only)
+    pub oracle_feed_id: [u8; 32],
+    pub max_price_age: u64,
+    pub max_confidence_bps: u16,
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+    pub target_weight_bps: u16,
+}
+
+#[derive(Accounts)]
+pub struct AddAsset<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Mint of the asset being whitelisted
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// Registry entry for the asset
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AssetConfig::INIT_SPACE,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub asset: Box<Account<'info, AssetConfig>>,
+
+    /// Vault holding the pool's balance of the asset
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"asset_vault".as_ref(), pool_state.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = pool_state,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by reading a price from the feed below (ignored for stable
+    /// assets while the pool has no peg band)
+    pub oracle_program: AccountInfo<'info>,
+
+    /// CHECK: Validated by reading a price from the feed below (ignored for stable
+    /// assets while the pool has no peg band)
+    pub oracle_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/**
+ * @dev Whitelist a new asset, creating its registry entry and vault.
+ * Assets are appended to the registry; their index is the order in which
+ * AUM accounts must be passed to deposit and withdraw.
+ */
+pub fn add_asset(ctx: Context<AddAsset>, params: AddAssetParams) -> Result<()> {
+    let pool_key = ctx.accounts.pool_state.key();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+
+    register_asset(
+        pool_key,
+        &mut pool_state,
+        &mut ctx.accounts.asset,
+        ctx.bumps.asset,
+        AssetAccounts {
+            mint: &ctx.accounts.mint,
+            vault: ctx.accounts.vault.key(),
+            oracle_program: &ctx.accounts.oracle_program,
+            oracle_feed: &ctx.accounts.oracle_feed,
+        },
+        &params,
+    )?;
+
+    let asset = &ctx.accounts.asset;
+    emit!(AssetAdded {
+        admin: ctx.accounts.admin.key(),
+        pool: pool_key,
+        mint: asset.mint,
+        index: asset.index,
+        vault: asset.vault,
+        is_stable: asset.is_stable,
+        oracle_kind: asset.oracle_kind,
+        oracle_feed: asset.oracle_feed,
+        target_weight_bps: asset.target_weight_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts an asset's registry entry points at
+pub(crate) struct AssetAccounts<'a, 'info> {
+    pub mint: &'a Account<'info, Mint>,
+    pub vault: Pubkey,
+    pub oracle_program: &'a AccountInfo<'info>,
+    pub oracle_feed: &'a AccountInfo<'info>,
+}
+
+/// Validate `params` and fill in a new registry entry, appending it to the pool's
+/// registry. Used by `add_asset` and `migrate_legacy_pool`.
+pub(crate) fn register_asset(
+    pool_key: Pubkey,
+    pool_state: &mut PoolState,
+    asset: &mut AssetConfig,
+    asset_bump: u8,
+    accounts: AssetAccounts<'_, '_>,
+    params: &AddAssetParams,
+) -> Result<()> {
+    require!(
+        pool_state.asset_count < MAX_ASSETS,
+        VaultError::MaxAssetsReached
+    );
+    require!(
+        params.deposit_fee_bps <= MAX_FEE_BPS && params.withdraw_fee_bps <= MAX_FEE_BPS,
+        VaultError::FeeTooHigh
+    );
+
+    // Make sure the feed returns a usable price before listing the asset. Stable
+    // assets are only priced through their feed once the pool has a peg band.
+    if !params.is_stable || pool_state.peg_band_bps > 0 {
+        require!(params.max_price_age > 0, VaultError::InvalidOracleConfig);
+
+        let price = oracle::read_price(
+            params.oracle_kind,
+            accounts.oracle_program,
+            accounts.oracle_feed,
+            &params.oracle_feed_id,
+        )?;
+        price
+            .validate(
+                Clock::get()?.unix_timestamp,
+                params.max_price_age,
+                params.max_confidence_bps,
+            )
+            .map_err(|rejection| error!(VaultError::from(rejection)))?;
+    }
+
+    asset.pool_state = pool_key;
+    asset.index = pool_state.asset_count;
+    asset.mint = accounts.mint.key();
+    asset.vault = accounts.vault;
+    asset.decimals = accounts.mint.decimals;
+    asset.is_stable = params.is_stable;
+    asset.deposited = 0;
+    asset.accumulated_fees = 0;
+    asset.oracle_kind = params.oracle_kind;
+    asset.oracle_program = accounts.oracle_program.key();
+    asset.oracle_feed = accounts.oracle_feed.key();
+    asset.oracle_feed_id = params.oracle_feed_id;
+    asset.max_price_age = params.max_price_age;
+    asset.max_confidence_bps = params.max_confidence_bps;
+    asset.deposit_fee_bps = params.deposit_fee_bps;
+    asset.withdraw_fee_bps = params.withdraw_fee_bps;
+    asset.target_weight_bps = params.target_weight_bps;
+    asset.bump = asset_bump;
+
+    pool_state.asset_count += 1;
+    pool_state.total_target_weight_bps = pool_state
+        .total_target_weight_bps
+        .checked_add(params.target_weight_bps as u32)
+        .ok_or(VaultError::MathError)?;
+
+    msg!("Added asset {} at index {}", asset.mint, asset.index);
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use crate::errors::ErrorCode;
 use crate::state::PoolState;
-use crate::state::MAX_AUTHORITIES;
+use crate::AuthorityAdded;
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -9,11 +9,11 @@ pub struct AddAuthority<'info> {
     pub admin: Signer<'info>,
 
     #[account(mut)]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 }
 
-pub fn add_authority(ctx: Context<AddAuthority>, new_authority: Pubkey) -> Result<()> {
-    let pool_state = &mut ctx.accounts.pool_state;
+pub fn add_authority(ctx: Context<AddAuthority>, new_authority: Pubkey, roles: u8) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
 
     // Ensure only the admin can add new authorities
     require_keys_eq!(
@@ -22,20 +22,20 @@ pub fn add_authority(ctx: Context<AddAuthority>, new_authority: Pubkey) -> Resul
         ErrorCode::Unauthorized
     );
 
-    // Check if the authority already exists in the list
-    if pool_state.authorities.iter().any(|a| *a == new_authority) {
-        return Err(ErrorCode::AuthorityAlreadyExists.into());
-    }
+    // Add the new authority (rejects duplicates, a full list and unknown roles)
+    pool_state.add_authority(new_authority, roles)?;
 
-    // Check if we've reached the maximum number of authorities
-    require!(
-        pool_state.authorities.len() < MAX_AUTHORITIES,
-        ErrorCode::MaxAuthoritiesReached
+    msg!(
+        "Added new authority: {} with roles {:#b}",
+        new_authority,
+        roles
     );
-
-    // Add the new authority
-    pool_state.authorities.push(new_authority);
-
-    msg!("Added new authority: {}", new_authority);
+    emit!(AuthorityAdded {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        authority: new_authority,
+        roles,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
@@ -0,0 +1,89 @@
+use crate::{errors::VaultError, state::*, RewardStreamAdded};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct AddRewardStream<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Mint of the reward token
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// Registry entry for the stream
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RewardStream::INIT_SPACE,
+        seeds = [b"reward_stream".as_ref(), pool_state.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub reward_stream: Box<Account<'info, RewardStream>>,
+
+    /// Vault holding the stream's undistributed rewards
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"reward_stream_vault".as_ref(), pool_state.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = pool_state,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/**
+ * @dev Register a new reward stream, creating its registry entry and vault.
+ * Streams are appended to the registry; their index is the order in which they
+ * must be passed to deposit and withdraw. Nothing is emitted until the stream
+ * is funded with `fund_reward_stream`.
+ */
+pub fn add_reward_stream(ctx: Context<AddRewardStream>) -> Result<()> {
+    let pool_key = ctx.accounts.pool_state.key();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+
+    require!(
+        (pool_state.reward_stream_count as usize) < MAX_REWARD_STREAMS,
+        VaultError::MaxRewardStreamsReached
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let stream = &mut ctx.accounts.reward_stream;
+    stream.pool_state = pool_key;
+    stream.index = pool_state.reward_stream_count;
+    stream.mint = ctx.accounts.mint.key();
+    stream.vault = ctx.accounts.vault.key();
+    stream.tokens_per_interval = 0;
+    stream.reward_start_time = 0;
+    stream.reward_end_time = 0;
+    stream.last_distribution_time = now as u64;
+    stream.cumulative_reward_per_token = 0;
+    stream.total_rewards_deposited = 0;
+    stream.total_rewards_claimed = 0;
+    stream.reward_remainder = 0;
+    stream.bump = ctx.bumps.reward_stream;
+
+    pool_state.reward_stream_count += 1;
+
+    emit!(RewardStreamAdded {
+        admin: ctx.accounts.admin.key(),
+        pool: pool_key,
+        mint: stream.mint,
+        index: stream.index,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -1,7 +1,4 @@
-use crate::{
-    errors::VaultError, state::*, CHAINLINK_PROGRAM_ID, DEVNET_SOL_PRICE_FEED,
-    MAINNET_SOL_PRICE_FEED, NATIVE_MINT,
-};
+use crate::{errors::VaultError, state::*, util::*, AdminWithdrawn};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
@@ -13,53 +10,94 @@ pub struct AdminWithdraw<'info> {
 
     #[account(
         mut,
-        seeds = [b"pool_state".as_ref()],
-        bump
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::SETTLEMENTS) @ VaultError::OperationPaused
     )]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
-    /// The vault token account from which WSOL is held.
-    #[account(mut)]
-    pub vault_account: Account<'info, TokenAccount>,
+    /// Registry entry of the asset being withdrawn
+    #[account(
+        mut,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
 
-    // Token account (e.g., WSOL or USDC)
+    /// CHECK: Per-window caps on what leaves the pool, see `set_outflow_limits`.
+    /// Pinned by its seeds and loaded in the handler, as it doesn't exist until the
+    /// limits are first set; until then withdrawals are unlimited. Once the pool
+    /// is flagged as having limits, it must exist.
     #[account(
         mut,
-        constraint = admin_token_account.mint == NATIVE_MINT.parse::<Pubkey>().unwrap() || admin_token_account.mint == pool_state.usdc_mint
+        seeds = [b"outflow_limits".as_ref(), pool_state.key().as_ref()],
+        bump
     )]
-    pub admin_token_account: Account<'info, TokenAccount>,
+    pub outflow_limits: UncheckedAccount<'info>,
 
-    /// CHECK: Validated by its constraint.
-    #[account(address = CHAINLINK_PROGRAM_ID.parse::<Pubkey>().unwrap())]
-    pub chainlink_program: AccountInfo<'info>,
+    /// The vault token account holding the asset.
+    #[account(mut, address = asset.vault @ VaultError::InvalidTokenAccount)]
+    pub vault_account: Account<'info, TokenAccount>,
 
-    /// CHECK: Validated below.
+    // Token account receiving the asset (e.g., WSOL or USDC)
     #[account(
-        address = if cfg!(feature = "devnet") {
-            DEVNET_SOL_PRICE_FEED
-        } else {
-            MAINNET_SOL_PRICE_FEED
-        }
-        .parse::<Pubkey>()
-        .unwrap()
+        mut,
+        constraint = admin_token_account.mint == asset.mint @ VaultError::InvalidTokenMint
     )]
-    pub chainlink_feed: AccountInfo<'info>,
+    pub admin_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
+/**
+ * @dev Move `amount` of an asset out of the pool, e.g. to pay positive PnL.
+ * Unlimited until `set_outflow_limits` first runs. While outflow limits are enabled, every registered asset must be passed as
+ * remaining accounts ([asset_config, oracle_program, oracle_feed] each, in
+ * index order) so the withdrawal can be valued against AUM.
+ */
 pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
-    // Manual check: Ensure that either the admin or authority role is calling.
-    if ctx.accounts.admin.key() != ctx.accounts.pool_state.admin
-        && !ctx
-            .accounts
-            .pool_state
-            .authorities
-            .contains(&ctx.accounts.admin.key())
-    {
-        return err!(VaultError::Unauthorized);
-    }
+    // Manual check: Ensure that either the admin or a settlement authority is calling.
+    let pool_id = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        let caller = ctx.accounts.admin.key();
+        if !pool_state.has_role(&caller, roles::SETTLEMENT) {
+            return err!(VaultError::Unauthorized);
+        }
+
+        // Count the withdrawal against the window's caps, valued at the asset's
+        // current price and rounded up against the caller
+        let outflow_limits_info = ctx.accounts.outflow_limits.to_account_info();
+        let outflow_limits = OutflowLimits::load(&outflow_limits_info)?;
+        require!(
+            outflow_limits.is_some() || pool_state.has_outflow_limits == 0,
+            VaultError::MissingOutflowLimits
+        );
+        if let Some(mut outflow_limits) = outflow_limits.filter(OutflowLimits::is_enabled) {
+            let aum = compute_aum(
+                ctx.accounts.pool_state.key(),
+                &pool_state,
+                ctx.remaining_accounts,
+                Rounding::Down,
+            )?;
+            let asset = &ctx.accounts.asset;
+            let amount_usd = get_usd_value(
+                amount,
+                asset.decimals,
+                aum.prices[asset.index as usize],
+                Rounding::Up,
+            )?;
+            outflow_limits.record_outflow(
+                caller,
+                amount_usd,
+                aum.total_usd,
+                Clock::get()?.unix_timestamp,
+            )?;
+            outflow_limits.store(&outflow_limits_info)?;
+        }
+
+        pool_state.pool_id.to_le_bytes()
+    };
 
     // Get pool_state's AccountInfo for CPI.
     let pool_state_info = ctx.accounts.pool_state.to_account_info();
@@ -76,28 +114,30 @@ pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
 
     // Note: Use the bump value stored on pool_state so that the signer seeds match.
     token::transfer(
-        cpi_ctx.with_signer(&[&[b"pool_state".as_ref(), &[ctx.bumps.pool_state]]]),
+        cpi_ctx.with_signer(&[&[
+            b"pool_state".as_ref(),
+            pool_id.as_ref(),
+            &[ctx.bumps.pool_state],
+        ]]),
         amount,
     )?;
 
-    // (Your existing code to decrement deposited amounts remains unchanged.)
-    if ctx.accounts.vault_account.key() == ctx.accounts.pool_state.sol_vault {
-        ctx.accounts.pool_state.sol_deposited = ctx
-            .accounts
-            .pool_state
-            .sol_deposited
-            .checked_sub(amount)
-            .ok_or_else(|| error!(VaultError::MathError))?;
-    } else if ctx.accounts.vault_account.key() == ctx.accounts.pool_state.usdc_vault {
-        ctx.accounts.pool_state.usdc_deposited = ctx
-            .accounts
-            .pool_state
-            .usdc_deposited
-            .checked_sub(amount)
-            .ok_or_else(|| error!(VaultError::MathError))?;
-    } else {
-        return err!(VaultError::InvalidTokenMint);
-    }
+    // Decrement the asset's deposited amount.
+    ctx.accounts.asset.deposited = ctx
+        .accounts
+        .asset
+        .deposited
+        .checked_sub(amount)
+        .ok_or_else(|| error!(VaultError::MathError))?;
+
+    emit!(AdminWithdrawn {
+        caller: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        mint: ctx.accounts.asset.mint,
+        amount,
+        destination: ctx.accounts.admin_token_account.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
 
     Ok(())
 }
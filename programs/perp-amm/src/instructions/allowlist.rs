@@ -0,0 +1,130 @@
+use crate::{
+    errors::VaultError, state::*, AllowlistEntryAdded, AllowlistEntryRemoved, AllowlistRootUpdated,
+    PermissionedModeUpdated,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetAllowlist<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddAllowlistEntry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AllowlistEntry::INIT_SPACE,
+        seeds = [b"allowlist".as_ref(), pool_state.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAllowlistEntry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"allowlist".as_ref(), pool_state.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+        close = admin
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+/**
+ * @dev Replace the merkle root of approved wallets. Proofs against the old root
+ * stop working straight away; allowlist entries are unaffected.
+ */
+pub fn set_allowlist_root(ctx: Context<SetAllowlist>, root: [u8; 32]) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.allowlist_root = root;
+
+    emit!(AllowlistRootUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        root,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/**
+ * @dev Turn permissioned mode on or off. While it is on, only wallets with an
+ * allowlist entry or a valid proof can deposit; withdrawals are never gated.
+ */
+pub fn set_permissioned(ctx: Context<SetAllowlist>, enabled: bool) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.permissioned = enabled as u8;
+
+    emit!(PermissionedModeUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        enabled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/**
+ * @dev Approve a single wallet without rotating the merkle root
+ */
+pub fn add_allowlist_entry(ctx: Context<AddAllowlistEntry>, wallet: Pubkey) -> Result<()> {
+    let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+    allowlist_entry.pool_state = ctx.accounts.pool_state.key();
+    allowlist_entry.wallet = wallet;
+    allowlist_entry.bump = ctx.bumps.allowlist_entry;
+
+    emit!(AllowlistEntryAdded {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        wallet,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/**
+ * @dev Revoke a wallet's allowlist entry, returning its rent to the admin.
+ * The wallet keeps its LP and can still withdraw.
+ */
+pub fn remove_allowlist_entry(ctx: Context<RemoveAllowlistEntry>) -> Result<()> {
+    emit!(AllowlistEntryRemoved {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        wallet: ctx.accounts.allowlist_entry.wallet,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
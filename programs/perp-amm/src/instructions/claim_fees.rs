@@ -1,92 +1,80 @@
-use crate::{errors::VaultError, state::*};
+use crate::{errors::VaultError, state::*, FeesClaimed};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 #[derive(Accounts)]
 pub struct ClaimFees<'info> {
+    /// The admin, or an authority with the fee collector role
     pub admin: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"pool_state".as_ref()],
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
         bump,
-        constraint = pool_state.admin == admin.key() @ VaultError::Unauthorized
+        constraint = pool_state.load()?.has_role(&admin.key(), roles::FEE_COLLECTOR) @ VaultError::Unauthorized
     )]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
-    /// SOL vault to withdraw from
+    /// Registry entry of the asset whose fees are claimed
     #[account(
         mut,
-        constraint = sol_vault.key() == pool_state.sol_vault
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
     )]
-    pub sol_vault: Account<'info, TokenAccount>,
+    pub asset: Account<'info, AssetConfig>,
 
-    /// USDC vault to withdraw from
+    /// Asset vault to withdraw from
     #[account(
         mut,
-        constraint = usdc_vault.key() == pool_state.usdc_vault
+        constraint = vault_account.key() == asset.vault
     )]
-    pub usdc_vault: Account<'info, TokenAccount>,
+    pub vault_account: Account<'info, TokenAccount>,
 
-    /// Admin's SOL token account to receive fees
+    /// Admin's token account to receive fees
     #[account(
         mut,
-        constraint = admin_sol_account.owner == admin.key()
+        constraint = admin_token_account.owner == admin.key(),
+        constraint = admin_token_account.mint == asset.mint @ VaultError::InvalidTokenMint
     )]
-    pub admin_sol_account: Account<'info, TokenAccount>,
-
-    /// Admin's USDC token account to receive fees
-    #[account(
-        mut,
-        constraint = admin_usdc_account.owner == admin.key()
-    )]
-    pub admin_usdc_account: Account<'info, TokenAccount>,
+    pub admin_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
 }
 
 pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
-    let pool_state = &mut ctx.accounts.pool_state;
+    let pool_id = ctx.accounts.pool_state.load()?.pool_id.to_le_bytes();
+    let asset = &mut ctx.accounts.asset;
 
-    // Transfer accumulated SOL fees if any
-    if pool_state.accumulated_sol_fees > 0 {
-        let sol_amount = pool_state.accumulated_sol_fees;
-        let cpi_ctx_sol = CpiContext::new(
+    // Transfer accumulated fees if any
+    if asset.accumulated_fees > 0 {
+        let amount = asset.accumulated_fees;
+        let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             Transfer {
-                from: ctx.accounts.sol_vault.to_account_info(),
-                to: ctx.accounts.admin_sol_account.to_account_info(),
-                authority: pool_state.to_account_info(),
+                from: ctx.accounts.vault_account.to_account_info(),
+                to: ctx.accounts.admin_token_account.to_account_info(),
+                authority: ctx.accounts.pool_state.to_account_info(),
             },
         );
         token::transfer(
-            cpi_ctx_sol.with_signer(&[&[b"pool_state".as_ref(), &[ctx.bumps.pool_state]]]),
-            sol_amount,
+            cpi_ctx.with_signer(&[&[
+                b"pool_state".as_ref(),
+                pool_id.as_ref(),
+                &[ctx.bumps.pool_state],
+            ]]),
+            amount,
         )?;
 
-        // Reset accumulated SOL fees
-        pool_state.accumulated_sol_fees = 0;
-    }
-
-    // Transfer accumulated USDC fees if any
-    if pool_state.accumulated_usdc_fees > 0 {
-        let usdc_amount = pool_state.accumulated_usdc_fees;
-
-        let cpi_ctx_usdc = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.usdc_vault.to_account_info(),
-                to: ctx.accounts.admin_usdc_account.to_account_info(),
-                authority: pool_state.to_account_info(),
-            },
-        );
-        token::transfer(
-            cpi_ctx_usdc.with_signer(&[&[b"pool_state".as_ref(), &[ctx.bumps.pool_state]]]),
-            usdc_amount,
-        )?;
+        // Reset accumulated fees
+        asset.accumulated_fees = 0;
 
-        // Reset accumulated USDC fees
-        pool_state.accumulated_usdc_fees = 0;
+        emit!(FeesClaimed {
+            admin: ctx.accounts.admin.key(),
+            pool: ctx.accounts.pool_state.key(),
+            mint: asset.mint,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
     }
 
     Ok(())
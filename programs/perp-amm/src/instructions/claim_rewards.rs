@@ -1,7 +1,7 @@
 use crate::state::*;
 use crate::{errors::VaultError, util::*, RewardsClaimed};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 #[derive(Accounts)]
 pub struct ClaimRewards<'info> {
@@ -9,14 +9,15 @@ pub struct ClaimRewards<'info> {
 
     #[account(
         mut,
-        seeds = [b"pool_state".as_ref()],
-        bump
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::REWARD_CLAIMS) @ VaultError::OperationPaused
     )]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
     #[account(
         mut,
-        seeds = [b"user_state".as_ref(), user.key().as_ref()],
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
         bump,
         constraint = user_state.owner == user.key()
     )]
@@ -24,7 +25,7 @@ pub struct ClaimRewards<'info> {
 
     #[account(
         mut,
-        constraint = usdc_reward_vault.key() == pool_state.usdc_reward_vault @ VaultError::InvalidRewardVault
+        constraint = usdc_reward_vault.key() == pool_state.load()?.usdc_reward_vault @ VaultError::InvalidRewardVault
     )]
     pub usdc_reward_vault: Account<'info, TokenAccount>,
 
@@ -36,19 +37,20 @@ pub struct ClaimRewards<'info> {
     pub user_usdc_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
-
-    #[account(
-        constraint = lp_token_mint.key() == pool_state.lp_token_mint @ VaultError::InvalidTokenMint
-    )]
-    pub lp_token_mint: Account<'info, Mint>,
 }
 
 pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
     // Store validation values up front
     let now = Clock::get()?.unix_timestamp as u64;
-    let reward_start_time = ctx.accounts.pool_state.reward_start_time;
-    let total_rewards_deposited = ctx.accounts.pool_state.total_rewards_deposited;
-    let total_rewards_claimed = ctx.accounts.pool_state.total_rewards_claimed;
+    let (pool_id, reward_start_time, total_rewards_deposited, total_rewards_claimed) = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        (
+            pool_state.pool_id.to_le_bytes(),
+            pool_state.reward_start_time,
+            pool_state.total_rewards_deposited,
+            pool_state.total_rewards_claimed,
+        )
+    };
     let vault_amount = ctx.accounts.usdc_reward_vault.amount;
     let pool_state_bump = ctx.bumps.pool_state;
     let user_key = ctx.accounts.user.key();
@@ -56,8 +58,9 @@ pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
     // Do validation checks with stored values
     require!(now >= reward_start_time, VaultError::RewardsNotStarted);
 
+    // Rewards settled before an unstake stay claimable with nothing left staked
     require!(
-        ctx.accounts.user_state.lp_token_balance > 0,
+        ctx.accounts.user_state.lp_token_balance > 0 || ctx.accounts.user_state.pending_rewards > 0,
         VaultError::NoLPTokens
     );
 
@@ -74,11 +77,11 @@ pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
 
     {
         // Use a block to limit the scope of mutable borrows
-        let pool_state = &mut ctx.accounts.pool_state;
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
         let user_state = &mut ctx.accounts.user_state;
 
         // 1) Update user's accrual to get an up-to-date `pending_rewards`
-        update_rewards(pool_state, user_state, &ctx.accounts.lp_token_mint)?;
+        update_rewards(&mut pool_state, user_state)?;
 
         // 2) The user now has some "pending" amount stored locally
         let pending = user_state.pending_rewards;
@@ -123,7 +126,7 @@ pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
     }
 
     // Now perform the token transfer with the pool_state as authority
-    let seeds = &[b"pool_state".as_ref(), &[pool_state_bump]];
+    let seeds = &[b"pool_state".as_ref(), pool_id.as_ref(), &[pool_state_bump]];
     let signer = &[&seeds[..]];
 
     let cpi_ctx = CpiContext::new_with_signer(
@@ -0,0 +1,118 @@
+use crate::state::*;
+use crate::{errors::VaultError, util::*, StreamRewardsClaimed};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+/// Accounts passed per stream being claimed in `remaining_accounts`:
+/// [reward_stream (writable), stream_vault (writable), user_token_account (writable)]
+pub const CLAIM_ACCOUNTS_PER_STREAM: usize = 3;
+
+/// Remaining accounts: `CLAIM_ACCOUNTS_PER_STREAM` accounts for each stream to
+/// claim, so one transaction can claim a single stream or all of them
+#[derive(Accounts)]
+pub struct ClaimStreamRewards<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::REWARD_CLAIMS) @ VaultError::OperationPaused
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = user_state.owner == user.key()
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn claim_stream_rewards(ctx: Context<ClaimStreamRewards>) -> Result<()> {
+    let pool_key = ctx.accounts.pool_state.key();
+    let (pool_id, total_staked_lp) = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        require_current_versions(&pool_state, &ctx.accounts.user_state)?;
+        (pool_state.pool_id.to_le_bytes(), pool_state.total_staked_lp)
+    };
+    let pool_seeds = &[
+        b"pool_state".as_ref(),
+        pool_id.as_ref(),
+        &[ctx.bumps.pool_state],
+    ];
+    let user_key = ctx.accounts.user.key();
+    let now = Clock::get()?.unix_timestamp;
+
+    let remaining_accounts = ctx.remaining_accounts;
+    require!(
+        !remaining_accounts.is_empty() && remaining_accounts.len() % CLAIM_ACCOUNTS_PER_STREAM == 0,
+        VaultError::InvalidRewardStreamAccounts
+    );
+
+    for accounts in remaining_accounts.chunks_exact(CLAIM_ACCOUNTS_PER_STREAM) {
+        let mut stream = load_reward_stream(&accounts[0], pool_key)?;
+        require_keys_eq!(
+            accounts[1].key(),
+            stream.vault,
+            VaultError::InvalidRewardVault
+        );
+        require_keys_eq!(
+            *accounts[2].owner,
+            token::ID,
+            VaultError::InvalidTokenAccount
+        );
+        let user_token_account =
+            TokenAccount::try_deserialize(&mut &accounts[2].try_borrow_data()?[..])?;
+        require!(
+            user_token_account.owner == user_key && user_token_account.mint == stream.mint,
+            VaultError::InvalidTokenAccount
+        );
+
+        // Bring the user's checkpoint for the stream up to date
+        update_stream_rewards(&mut stream, &mut ctx.accounts.user_state, total_staked_lp)?;
+
+        // Clamp the claim to what is left in the stream
+        let checkpoint = &mut ctx.accounts.user_state.reward_checkpoints[stream.index as usize];
+        let available = stream
+            .total_rewards_deposited
+            .saturating_sub(stream.total_rewards_claimed);
+        let amount = u64::try_from(checkpoint.pending_rewards.min(available as u128))
+            .map_err(|_| error!(VaultError::MathError))?;
+
+        if amount > 0 {
+            checkpoint.pending_rewards -= amount as u128;
+            stream.total_rewards_claimed = stream
+                .total_rewards_claimed
+                .checked_add(amount)
+                .ok_or(VaultError::MathError)?;
+
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: accounts[1].clone(),
+                        to: accounts[2].clone(),
+                        authority: ctx.accounts.pool_state.to_account_info(),
+                    },
+                )
+                .with_signer(&[pool_seeds]),
+                amount,
+            )?;
+
+            emit!(StreamRewardsClaimed {
+                user: user_key,
+                pool: pool_key,
+                mint: stream.mint,
+                amount,
+                timestamp: now,
+            });
+        }
+
+        store_reward_stream(&accounts[0], &stream)?;
+    }
+
+    Ok(())
+}
@@ -7,12 +7,12 @@ pub struct ClosePool<'info> {
 
     #[account(
         mut,
-        seeds = [b"pool_state".as_ref()],
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
         bump,
-        constraint = pool_state.admin == admin.key() @ VaultError::Unauthorized,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized,
         close = admin
     )]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
     pub system_program: Program<'info, System>,
 }
@@ -21,7 +21,7 @@ pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
     // Verify the signer is the admin
     require_keys_eq!(
         ctx.accounts.admin.key(),
-        ctx.accounts.pool_state.admin,
+        ctx.accounts.pool_state.load()?.admin,
         VaultError::Unauthorized
     );
 
@@ -7,16 +7,17 @@ pub struct CloseUserState<'info> {
 
     #[account(
         mut,
-        seeds = [b"pool_state".as_ref()],
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
         bump,
     )]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
     #[account(
         mut,
-        seeds = [b"user_state".as_ref(), user.key().as_ref()],
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
         bump,
-        constraint = (user_state.owner == user.key() || pool_state.admin == user.key()) @ VaultError::Unauthorized,
+        constraint = (user_state.owner == user.key() || pool_state.load()?.admin == user.key()) @ VaultError::Unauthorized,
+        constraint = user_state.lp_token_balance == 0 @ VaultError::StakedLpRemaining,
         close = user
     )]
     pub user_state: Account<'info, UserState>,
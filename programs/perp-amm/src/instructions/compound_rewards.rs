@@ -0,0 +1,198 @@
+use crate::{errors::VaultError, state::*, util::*, RewardsCompounded};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+
+/// Remaining accounts: [asset_config, oracle_program, oracle_feed] for every
+/// registered asset, in index order (see `compute_aum`), followed by every
+/// reward stream, writable and in index order
+#[derive(Accounts)]
+pub struct CompoundRewards<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::DEPOSITS | pause_flags::REWARD_CLAIMS) @ VaultError::OperationPaused
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = user_state.owner == user.key() @ VaultError::Unauthorized
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(
+        mut,
+        constraint = usdc_reward_vault.key() == pool_state.load()?.usdc_reward_vault @ VaultError::InvalidRewardVault
+    )]
+    pub usdc_reward_vault: Account<'info, TokenAccount>,
+
+    /// Registry entry of USDC, the asset rewards are reinvested into
+    #[account(
+        mut,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), pool_state.load()?.usdc_mint.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+
+    #[account(mut, address = asset.vault @ VaultError::InvalidTokenAccount)]
+    pub vault_account: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint @ VaultError::InvalidTokenMint)]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    /// Minted LP goes straight into the stake vault, staked for the user
+    #[account(
+        mut,
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump
+    )]
+    pub lp_stake_vault: Account<'info, TokenAccount>,
+
+    /// The user's allowlist entry; only needed in permissioned mode without a proof
+    #[account(
+        seeds = [b"allowlist".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Reinvest the user's pending USDC rewards into the pool's USDC side and stake
+/// the LP minted for them. The rewards never leave the program, so they are priced
+/// exactly like a deposit of the same USDC: one deposit fee, at the current AUM,
+/// and only for wallets that may deposit.
+pub fn compound_rewards(
+    ctx: Context<CompoundRewards>,
+    min_lp_out: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let pool_key = ctx.accounts.pool_state.key();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    let pool_id = pool_state.pool_id.to_le_bytes();
+    let asset = &mut ctx.accounts.asset;
+    let user_state = &mut ctx.accounts.user_state;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        now as u64 >= pool_state.reward_start_time,
+        VaultError::RewardsNotStarted
+    );
+
+    let (aum_accounts, reward_stream_accounts) =
+        split_aum_accounts(&pool_state, ctx.remaining_accounts)?;
+
+    // 1. Settle rewards before the user's stake grows.
+    update_rewards(&mut pool_state, user_state)?;
+    update_all_stream_rewards(pool_key, &pool_state, user_state, reward_stream_accounts)?;
+
+    // 2. Clamp to what is left in the reward pool, as `claim_rewards` does.
+    let available = pool_state
+        .total_rewards_deposited
+        .saturating_sub(pool_state.total_rewards_claimed);
+    require!(
+        ctx.accounts.usdc_reward_vault.amount >= available,
+        VaultError::InsufficientRewardBalance
+    );
+    let amount = u64::try_from(user_state.pending_rewards.min(available as u128))
+        .map_err(|_| error!(VaultError::MathError))?;
+    if amount == 0 {
+        return Ok(());
+    }
+
+    // 3. Price the reinvested USDC as a deposit.
+    let quote = quote_deposit(
+        pool_key,
+        &pool_state,
+        asset,
+        aum_accounts,
+        ctx.accounts.lp_token_mint.supply,
+        amount,
+    )?;
+    require!(quote.lp_to_mint >= min_lp_out, VaultError::SlippageExceeded);
+    pool_state.check_deposit(
+        &ctx.accounts.user.key(),
+        &proof,
+        ctx.accounts.allowlist_entry.is_some(),
+        user_state.lp_token_balance,
+        &quote,
+    )?;
+
+    // 4. Move the rewards from the user's pending balance into their stake.
+    user_state.pending_rewards -= amount as u128;
+    user_state.lp_token_balance = user_state
+        .lp_token_balance
+        .checked_add(quote.lp_to_mint as u128)
+        .ok_or(VaultError::MathError)?;
+    pool_state.total_rewards_claimed = pool_state
+        .total_rewards_claimed
+        .checked_add(amount)
+        .ok_or(VaultError::MathError)?;
+    pool_state.total_staked_lp = pool_state
+        .total_staked_lp
+        .checked_add(quote.lp_to_mint)
+        .ok_or(VaultError::MathError)?;
+
+    asset.accumulated_fees = asset
+        .accumulated_fees
+        .checked_add(quote.fee_amount)
+        .ok_or(VaultError::MathError)?;
+    asset.deposited = asset
+        .deposited
+        .checked_add(quote.deposit_amount)
+        .ok_or(VaultError::MathError)?;
+
+    // Release the pool state before CPIs that sign with it
+    drop(pool_state);
+
+    let pool_seeds = &[
+        b"pool_state".as_ref(),
+        pool_id.as_ref(),
+        &[ctx.bumps.pool_state],
+    ];
+
+    // 5. Move the USDC from the reward vault into the pool's USDC vault.
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.usdc_reward_vault.to_account_info(),
+                to: ctx.accounts.vault_account.to_account_info(),
+                authority: ctx.accounts.pool_state.to_account_info(),
+            },
+        )
+        .with_signer(&[pool_seeds]),
+        amount,
+    )?;
+
+    // 6. Mint the LP into the stake vault.
+    token::mint_to(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.lp_token_mint.to_account_info(),
+                to: ctx.accounts.lp_stake_vault.to_account_info(),
+                authority: ctx.accounts.pool_state.to_account_info(),
+            },
+        )
+        .with_signer(&[pool_seeds]),
+        quote.lp_to_mint,
+    )?;
+
+    emit!(RewardsCompounded {
+        user: ctx.accounts.user.key(),
+        pool: pool_key,
+        usdc_amount: amount,
+        fee_amount: quote.fee_amount,
+        lp_minted: quote.lp_to_mint,
+        aum_usd: quote.aum_usd,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -1,76 +1,139 @@
-use crate::{
-    errors::VaultError, state::*, util::*, CHAINLINK_PROGRAM_ID, DEVNET_SOL_PRICE_FEED,
-    MAINNET_SOL_PRICE_FEED, NATIVE_MINT,
-};
+use crate::{errors::VaultError, state::*, util::*, Deposited};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
-use chainlink_solana as chainlink;
 
+/// Remaining accounts: [asset_config, oracle_program, oracle_feed] for every
+/// registered asset, in index order (see `compute_aum`), followed by every
+/// reward stream, writable and in index order
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut, seeds = [b"pool_state".as_ref()], bump)]
-    pub pool_state: Account<'info, PoolState>,
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::DEPOSITS) @ VaultError::OperationPaused
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
 
-    // Token account, e.g WSOL or USDC
+    /// Registry entry of the asset being deposited
     #[account(
         mut,
-        constraint = user_token_account.mint == NATIVE_MINT.parse::<Pubkey>().unwrap() || user_token_account.mint == pool_state.usdc_mint
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub asset: Account<'info, AssetConfig>,
 
+    // Token account holding the asset being deposited
     #[account(
         mut,
-        constraint = vault_account.key() == pool_state.sol_vault
-            || vault_account.key() == pool_state.usdc_vault
+        constraint = user_token_account.mint == asset.mint @ VaultError::InvalidTokenMint
     )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = asset.vault @ VaultError::InvalidTokenAccount)]
     pub vault_account: Account<'info, TokenAccount>,
 
     #[account(
         init_if_needed,
         payer = user,
-        space = 8 + UserState::INIT_SPACE,
-        seeds = [b"user_state".as_ref(), user.key().as_ref()],
+        space = UserState::LEN,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
         bump
     )]
     pub user_state: Account<'info, UserState>,
 
-    #[account(mut, constraint = lp_token_mint.key() == pool_state.lp_token_mint)]
+    #[account(mut, constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint)]
     pub lp_token_mint: Account<'info, Mint>,
 
+    /// Minted LP goes straight into the stake vault, staked for the user
     #[account(
         mut,
-        constraint = user_lp_token_account.owner == user.key(),
-        constraint = user_lp_token_account.mint == lp_token_mint.key()
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump
     )]
-    pub user_lp_token_account: Account<'info, TokenAccount>,
+    pub lp_stake_vault: Account<'info, TokenAccount>,
 
-    /// CHECK: Validated in constraint.
-    #[account(address = CHAINLINK_PROGRAM_ID.parse::<Pubkey>().unwrap())]
-    pub chainlink_program: AccountInfo<'info>,
-
-    /// CHECK: Validated in constraint.
+    /// The user's allowlist entry; only needed in permissioned mode without a proof
     #[account(
-        address = if cfg!(feature = "devnet") {
-            DEVNET_SOL_PRICE_FEED
-        } else {
-            MAINNET_SOL_PRICE_FEED
-        }
-        .parse::<Pubkey>()
-        .unwrap()
+        seeds = [b"allowlist".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
     )]
-    pub chainlink_feed: AccountInfo<'info>,
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
 
     pub token_program: Program<'info, Token>,
 
     pub system_program: Program<'info, System>,
 }
 
-pub fn deposit(ctx: Context<Deposit>, token_amount: u64) -> Result<()> {
-    let pool_state = &mut ctx.accounts.pool_state;
-    let user_state = &mut ctx.accounts.user_state;
+pub fn deposit(
+    ctx: Context<Deposit>,
+    token_amount: u64,
+    min_lp_out: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    process_deposit(
+        DepositAccounts {
+            user: &ctx.accounts.user,
+            pool_state: &ctx.accounts.pool_state,
+            pool_state_bump: ctx.bumps.pool_state,
+            asset: &mut ctx.accounts.asset,
+            user_state: &mut ctx.accounts.user_state,
+            lp_token_mint: &ctx.accounts.lp_token_mint,
+            lp_stake_vault: &ctx.accounts.lp_stake_vault,
+            allowlist_entry: &ctx.accounts.allowlist_entry,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.remaining_accounts,
+        token_amount,
+        min_lp_out,
+        &proof,
+    )?;
+
+    // Transfer the deposit from user to vault.
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        token_amount,
+    )
+}
+
+/// Accounts `deposit` and `deposit_sol` share
+pub(crate) struct DepositAccounts<'a, 'info> {
+    pub user: &'a Signer<'info>,
+    pub pool_state: &'a AccountLoader<'info, PoolState>,
+    pub pool_state_bump: u8,
+    pub asset: &'a mut Account<'info, AssetConfig>,
+    pub user_state: &'a mut Account<'info, UserState>,
+    pub lp_token_mint: &'a Account<'info, Mint>,
+    pub lp_stake_vault: &'a Account<'info, TokenAccount>,
+    pub allowlist_entry: &'a Option<Account<'info, AllowlistEntry>>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+/// Everything a deposit does apart from moving the tokens into the vault, which
+/// `deposit` and `deposit_sol` each do their own way: validate and price the
+/// deposit, settle the user's rewards, book it and mint the LP staked for the user.
+pub(crate) fn process_deposit<'info>(
+    accounts: DepositAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    token_amount: u64,
+    min_lp_out: u64,
+    proof: &[[u8; 32]],
+) -> Result<()> {
+    let pool_key = accounts.pool_state.key();
+    let mut pool_state = accounts.pool_state.load_mut()?;
+    let pool_id = pool_state.pool_id.to_le_bytes();
+    let asset = accounts.asset;
+    let user_state = accounts.user_state;
     let clock = Clock::get()?;
 
     // Validate input token amount.
@@ -78,119 +141,95 @@ pub fn deposit(ctx: Context<Deposit>, token_amount: u64) -> Result<()> {
         return err!(VaultError::InvalidTokenAmount);
     }
 
+    let (aum_accounts, reward_stream_accounts) =
+        split_aum_accounts(&pool_state, remaining_accounts)?;
+
+    // New user states start on the current version; older ones must be migrated first
+    if user_state.owner == Pubkey::default() {
+        user_state.version = USER_STATE_VERSION;
+    }
+
     // 1. Update rewards for the user.
-    update_rewards(pool_state, user_state, &ctx.accounts.lp_token_mint)?;
+    update_rewards(&mut pool_state, user_state)?;
+    update_all_stream_rewards(pool_key, &pool_state, user_state, reward_stream_accounts)?;
 
     // 2. Initialize last claim timestamp for new accounts.
     if user_state.lp_token_balance == 0 && user_state.previous_cumulated_reward_per_token == 0 {
         user_state.last_claim_timestamp = clock.unix_timestamp as u64;
     }
 
-    // 3. Calculate deposit fee (0.1%) and net deposit.
-    let fee_amount = token_amount
-        .checked_div(1000)
-        .ok_or(VaultError::MathError)?;
-    let deposit_amount = token_amount
-        .checked_sub(fee_amount)
-        .ok_or(VaultError::MathError)?;
-
-    let round = chainlink::latest_round_data(
-        ctx.accounts.chainlink_program.to_account_info(),
-        ctx.accounts.chainlink_feed.to_account_info(),
+    // 3. Price the deposit: dynamic fee, net deposit and LP tokens owed.
+    let quote = quote_deposit(
+        pool_key,
+        &pool_state,
+        asset,
+        aum_accounts,
+        accounts.lp_token_mint.supply,
+        token_amount,
+    )?;
+    require!(quote.lp_to_mint >= min_lp_out, VaultError::SlippageExceeded);
+    pool_state.check_deposit(
+        &accounts.user.key(),
+        proof,
+        accounts.allowlist_entry.is_some(),
+        user_state.lp_token_balance,
+        &quote,
     )?;
 
-    let sol_usd_price = round.answer;
-
-    // 4. Update price feeds and accumulated fees.
-    if ctx.accounts.vault_account.key() == pool_state.sol_vault {
-        pool_state.accumulated_sol_fees = pool_state
-            .accumulated_sol_fees
-            .checked_add(fee_amount)
-            .ok_or(VaultError::MathError)?;
-    } else if ctx.accounts.vault_account.key() == pool_state.usdc_vault {
-        pool_state.accumulated_usdc_fees = pool_state
-            .accumulated_usdc_fees
-            .checked_add(fee_amount)
-            .ok_or(VaultError::MathError)?;
-    }
+    pool_state.total_staked_lp = pool_state
+        .total_staked_lp
+        .checked_add(quote.lp_to_mint)
+        .ok_or(VaultError::MathError)?;
 
-    // 5. Transfer the deposit from user to vault.
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_account.to_account_info(),
-                to: ctx.accounts.vault_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
-        token_amount,
-    )?;
+    // Release the pool state before CPIs that sign with it
+    drop(pool_state);
 
-    // 6. Compute initial Assets Under Management (AUM).
-    let total_sol_usd = get_sol_usd_value(pool_state.sol_deposited, sol_usd_price)?;
-    let initial_aum = total_sol_usd
-        .checked_add(
-            pool_state
-                .usdc_deposited
-                .checked_mul(100)
-                .ok_or(VaultError::MathError)?,
-        )
+    // 4. Update accumulated fees and the asset's deposited amount.
+    asset.accumulated_fees = asset
+        .accumulated_fees
+        .checked_add(quote.fee_amount)
+        .ok_or(VaultError::MathError)?;
+    asset.deposited = asset
+        .deposited
+        .checked_add(quote.deposit_amount)
         .ok_or(VaultError::MathError)?;
 
-    // 7. Update vault deposited amounts and compute the USD value of the deposit.
-    let deposit_usd: u128 = if ctx.accounts.vault_account.key() == pool_state.sol_vault {
-        pool_state.sol_deposited = pool_state
-            .sol_deposited
-            .checked_add(deposit_amount)
-            .ok_or(VaultError::MathError)?;
-        get_sol_usd_value(deposit_amount, sol_usd_price)? as u128
-    } else if ctx.accounts.vault_account.key() == pool_state.usdc_vault {
-        pool_state.usdc_deposited = pool_state
-            .usdc_deposited
-            .checked_add(deposit_amount)
-            .ok_or(VaultError::MathError)?;
-        // Convert USDC (6 decimals) to USD (8 decimals)
-        (deposit_amount as u128)
-            .checked_mul(100)
-            .ok_or(VaultError::MathError)?
-    } else {
-        return err!(VaultError::InvalidTokenMint);
-    };
-
-    // 8. Calculate how many LP tokens to mint.
-    let lp_supply = ctx.accounts.lp_token_mint.supply;
-    let lp_to_mint: u128 = if lp_supply == 0 {
-        // USD value in 10^8, LP tokens are 10^9 so we mul by 10
-        (deposit_usd.checked_mul(10).ok_or(VaultError::MathError)?) as u128
-    } else {
-        (deposit_usd
-            .checked_mul(lp_supply as u128)
-            .ok_or(VaultError::MathError)?
-            .checked_div(initial_aum.max(1) as u128)
-            .ok_or(VaultError::MathError)?) as u128
-    };
-
-    // 9. Mint LP tokens to the user.
+    // 5. Mint LP tokens into the stake vault.
     token::mint_to(
         CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
+            accounts.token_program.to_account_info(),
             MintTo {
-                mint: ctx.accounts.lp_token_mint.to_account_info(),
-                to: ctx.accounts.user_lp_token_account.to_account_info(),
-                authority: ctx.accounts.pool_state.to_account_info(),
+                mint: accounts.lp_token_mint.to_account_info(),
+                to: accounts.lp_stake_vault.to_account_info(),
+                authority: accounts.pool_state.to_account_info(),
             },
         )
-        .with_signer(&[&[b"pool_state".as_ref(), &[ctx.bumps.pool_state]]]),
-        lp_to_mint as u64,
+        .with_signer(&[&[
+            b"pool_state".as_ref(),
+            pool_id.as_ref(),
+            &[accounts.pool_state_bump],
+        ]]),
+        quote.lp_to_mint,
     )?;
 
-    // 10. Update the user's LP token balance.
-    user_state.owner = ctx.accounts.user.key();
+    // 6. Credit the user with the staked LP.
+    user_state.owner = accounts.user.key();
     user_state.lp_token_balance = user_state
         .lp_token_balance
-        .checked_add(lp_to_mint)
+        .checked_add(quote.lp_to_mint as u128)
         .ok_or(VaultError::MathError)?;
 
+    emit!(Deposited {
+        user: accounts.user.key(),
+        pool: pool_key,
+        mint: asset.mint,
+        token_amount,
+        fee_amount: quote.fee_amount,
+        lp_minted: quote.lp_to_mint,
+        asset_price: quote.asset_price,
+        aum_usd: quote.aum_usd,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
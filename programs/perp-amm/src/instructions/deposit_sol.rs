@@ -0,0 +1,108 @@
+use super::deposit::{process_deposit, DepositAccounts};
+use crate::{errors::VaultError, state::*};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{self, spl_token::native_mint, Mint, SyncNative, Token, TokenAccount};
+
+/// Deposit native SOL, wrapped directly into the SOL asset's vault.
+///
+/// Remaining accounts: [asset_config, oracle_program, oracle_feed] for every
+/// registered asset, in index order (see `compute_aum`), followed by every
+/// reward stream, writable and in index order
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::DEPOSITS) @ VaultError::OperationPaused
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Registry entry of wrapped SOL
+    #[account(
+        mut,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), native_mint::ID.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+
+    #[account(mut, address = asset.vault @ VaultError::InvalidTokenAccount)]
+    pub vault_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserState::LEN,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(mut, constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint)]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    /// Minted LP goes straight into the stake vault, staked for the user
+    #[account(
+        mut,
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump
+    )]
+    pub lp_stake_vault: Account<'info, TokenAccount>,
+
+    /// The user's allowlist entry; only needed in permissioned mode without a proof
+    #[account(
+        seeds = [b"allowlist".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn deposit_sol(
+    ctx: Context<DepositSol>,
+    lamports: u64,
+    min_lp_out: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    process_deposit(
+        DepositAccounts {
+            user: &ctx.accounts.user,
+            pool_state: &ctx.accounts.pool_state,
+            pool_state_bump: ctx.bumps.pool_state,
+            asset: &mut ctx.accounts.asset,
+            user_state: &mut ctx.accounts.user_state,
+            lp_token_mint: &ctx.accounts.lp_token_mint,
+            lp_stake_vault: &ctx.accounts.lp_stake_vault,
+            allowlist_entry: &ctx.accounts.allowlist_entry,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.remaining_accounts,
+        lamports,
+        min_lp_out,
+        &proof,
+    )?;
+
+    // Move the lamports into the vault and sync its wrapped balance.
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.vault_account.to_account_info(),
+            },
+        ),
+        lamports,
+    )?;
+    token::sync_native(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        SyncNative {
+            account: ctx.accounts.vault_account.to_account_info(),
+        },
+    ))
+}
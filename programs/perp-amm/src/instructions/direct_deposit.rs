@@ -1,7 +1,4 @@
-use crate::{
-    errors::VaultError, state::*, CHAINLINK_PROGRAM_ID, DEVNET_SOL_PRICE_FEED,
-    MAINNET_SOL_PRICE_FEED, NATIVE_MINT,
-};
+use crate::{errors::VaultError, state::*, DirectDeposited};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
@@ -11,48 +8,39 @@ pub struct DirectDeposit<'info> {
 
     #[account(
         mut,
-        seeds = [b"pool_state".as_ref()],
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
         bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::SETTLEMENTS) @ VaultError::OperationPaused
     )]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
-    // Token account, e.g WSOL or USDC
+    /// Registry entry of the asset being deposited
     #[account(
         mut,
-        constraint = depositor_token_account.mint == NATIVE_MINT.parse::<Pubkey>().unwrap() || depositor_token_account.mint == pool_state.usdc_mint
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
     )]
-    pub depositor_token_account: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub vault_account: Account<'info, TokenAccount>,
+    pub asset: Account<'info, AssetConfig>,
 
-    /// CHECK: Validated in constraint.
-    #[account(address = CHAINLINK_PROGRAM_ID.parse::<Pubkey>().unwrap())]
-    pub chainlink_program: AccountInfo<'info>,
-
-    /// CHECK: Validated in constraint.
+    // Token account holding the asset, e.g WSOL or USDC
     #[account(
-        address = if cfg!(feature = "devnet") {
-            DEVNET_SOL_PRICE_FEED
-        } else {
-            MAINNET_SOL_PRICE_FEED
-        }
-        .parse::<Pubkey>()
-        .unwrap()
+        mut,
+        constraint = depositor_token_account.mint == asset.mint @ VaultError::InvalidTokenMint
     )]
-    pub chainlink_feed: AccountInfo<'info>,
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = asset.vault @ VaultError::InvalidTokenAccount)]
+    pub vault_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 /**
- * @dev Direct deposit of WSOL or USDC into the pool.
+ * @dev Direct deposit of any registered asset into the pool.
  * Receives no LP tokens in return, just boosts the pool's AUM.
  */
 pub fn direct_deposit(ctx: Context<DirectDeposit>, amount: u64) -> Result<()> {
-    let pool_state = &mut ctx.accounts.pool_state;
-
     let transfer_ctx = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         Transfer {
@@ -63,21 +51,20 @@ pub fn direct_deposit(ctx: Context<DirectDeposit>, amount: u64) -> Result<()> {
     );
     token::transfer(transfer_ctx, amount)?;
 
-    // Validate and update the non-SOL deposit record (e.g., USDC deposit).
-    if ctx.accounts.vault_account.key() == pool_state.usdc_vault {
-        pool_state.usdc_deposited = pool_state
-            .usdc_deposited
-            .checked_add(amount)
-            .ok_or_else(|| error!(VaultError::MathError))?;
-    } else if ctx.accounts.vault_account.key() == pool_state.sol_vault {
-        // Update the pool's record of SOL deposited.
-        pool_state.sol_deposited = pool_state
-            .sol_deposited
-            .checked_add(amount)
-            .ok_or_else(|| error!(VaultError::MathError))?;
-    } else {
-        return err!(VaultError::InvalidTokenMint);
-    }
+    // Update the pool's record of the asset deposited.
+    let asset = &mut ctx.accounts.asset;
+    asset.deposited = asset
+        .deposited
+        .checked_add(amount)
+        .ok_or_else(|| error!(VaultError::MathError))?;
+
+    emit!(DirectDeposited {
+        depositor: ctx.accounts.depositor.key(),
+        pool: ctx.accounts.pool_state.key(),
+        mint: asset.mint,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
 
     Ok(())
 }
@@ -8,15 +8,15 @@ pub struct ForceCloseUserState<'info> {
 
     #[account(
         mut,
-        seeds = [b"pool_state".as_ref()],
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
         bump,
     )]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
     /// CHECK: Intentionally not deserializing
     #[account(
         mut,
-        seeds = [b"user_state".as_ref(), target_user.key().as_ref()],
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), target_user.key().as_ref()],
         bump
     )]
     pub user_state: UncheckedAccount<'info>,
@@ -31,7 +31,7 @@ pub fn force_close_user_state(ctx: Context<ForceCloseUserState>) -> Result<()> {
     // Verify admin
     require_keys_eq!(
         ctx.accounts.admin.key(),
-        ctx.accounts.pool_state.admin,
+        ctx.accounts.pool_state.load()?.admin,
         VaultError::Unauthorized
     );
 
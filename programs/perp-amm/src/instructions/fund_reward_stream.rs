@@ -0,0 +1,104 @@
+use crate::{errors::VaultError, state::*, util::*, RewardStreamFunded};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct FundRewardStream<'info> {
+    /// The admin, or an authority with the rewards manager role
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.has_role(&admin.key(), roles::REWARDS_MANAGER) @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_stream".as_ref(), pool_state.key().as_ref(), reward_stream.mint.as_ref()],
+        bump = reward_stream.bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+
+    /// Admin's account of the reward token
+    #[account(mut)]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = reward_stream.vault @ VaultError::InvalidRewardVault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Start a reward period of `duration` seconds for a stream, funded with `amount`
+/// of its token. Top-ups work as in `start_rewards`: whatever the running period
+/// has yet to emit, rounding remainder included, rolls into the new one.
+pub fn fund_reward_stream(
+    ctx: Context<FundRewardStream>,
+    amount: u64,
+    duration: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return err!(VaultError::InvalidTokenAmount);
+    }
+    require!(
+        duration > 0 && duration <= MAX_REWARD_DURATION,
+        VaultError::InvalidRewardDuration
+    );
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.admin_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let total_staked_lp = ctx.accounts.pool_state.load()?.total_staked_lp;
+    let stream = &mut ctx.accounts.reward_stream;
+
+    // Settle emissions so far at the current rate before changing it
+    accrue_stream_rewards(stream, total_staked_lp)?;
+
+    let (tokens_per_interval, rolled_over, reward_remainder) = next_reward_rate(
+        stream.reward_end_time,
+        stream.tokens_per_interval,
+        stream.reward_remainder,
+        now as u64,
+        amount,
+        duration,
+    )?;
+
+    let reward_end_time = (now as u64)
+        .checked_add(duration)
+        .ok_or(VaultError::MathError)?;
+    stream.total_rewards_deposited = stream
+        .total_rewards_deposited
+        .checked_add(amount)
+        .ok_or(VaultError::MathError)?;
+    stream.tokens_per_interval = tokens_per_interval;
+    stream.reward_remainder = reward_remainder;
+    stream.reward_start_time = now as u64;
+    stream.reward_end_time = reward_end_time;
+    stream.last_distribution_time = now as u64;
+
+    emit!(RewardStreamFunded {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        mint: stream.mint,
+        amount,
+        rolled_over,
+        duration,
+        tokens_per_interval,
+        reward_end_time,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -1,32 +1,28 @@
-use crate::state::PoolState;
+use crate::errors::VaultError;
+use crate::state::{PoolState, LP_DECIMALS, POOL_STATE_VERSION};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
 /// First instruction - initialize just the pool state
 #[derive(Accounts)]
+#[instruction(pool_id: u64)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
 
-    /// The PoolState (PDA) to store global info about the pool
+    /// The PoolState (PDA) to store global info about the pool; each id is a separate pool
     #[account(
         init,
         payer = admin,
-        space = 8 + PoolState::INIT_SPACE,
-        seeds = [b"pool_state".as_ref()],
+        space = PoolState::LEN,
+        seeds = [b"pool_state".as_ref(), &pool_id.to_le_bytes()],
         bump,
     )]
-    pub pool_state: Box<Account<'info, PoolState>>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
-    /// CHECK: Will be initialized in a separate instruction to avoid stack usage
-    pub sol_vault: UncheckedAccount<'info>,
-    /// CHECK: Will be initialized in a separate instruction to avoid stack usage
-    pub usdc_vault: UncheckedAccount<'info>,
     /// CHECK: Will be initialized in a separate instruction to avoid stack usage
     pub usdc_reward_vault: UncheckedAccount<'info>,
-    /// CHECK: Used as mint for SOL token vault
-    pub sol_mint: UncheckedAccount<'info>,
-    /// CHECK: Used as mint for USDC token vault
+    /// CHECK: Used as mint for the USDC reward vault
     pub usdc_mint: UncheckedAccount<'info>,
     /// CHECK: Will be initialized in a separate instruction to avoid stack usage
     pub lp_token_mint: UncheckedAccount<'info>,
@@ -35,7 +31,8 @@ pub struct Initialize<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-/// Initialize a vault token account in a separate instruction to avoid stack usage
+/// Initialize the reward vault token account in a separate instruction to avoid stack usage.
+/// Asset vaults are created by `add_asset`.
 #[derive(Accounts)]
 #[instruction(seed: Vec<u8>)]
 pub struct InitializeTokenVault<'info> {
@@ -43,7 +40,7 @@ pub struct InitializeTokenVault<'info> {
     pub admin: Signer<'info>,
 
     /// Pool state (PDA)
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
     /// The vault token account to initialize
     #[account(
@@ -71,13 +68,13 @@ pub struct InitializeLpMint<'info> {
     pub admin: Signer<'info>,
 
     /// Pool state that will be the mint authority
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
     /// The LP token mint to initialize
     #[account(
         init,
         payer = admin,
-        mint::decimals = 9,
+        mint::decimals = LP_DECIMALS,
         mint::authority = pool_state,
         mint::freeze_authority = pool_state,
     )]
@@ -88,33 +85,63 @@ pub struct InitializeLpMint<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-// Initialize the pool state with minimal stack usage
-pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+/// Create the vault staked LP is held in. Only staked LP earns rewards; deposits
+/// stake the LP they mint, so a pool needs this vault before it takes deposits.
+#[derive(Accounts)]
+pub struct InitializeLpStakeVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint @ VaultError::InvalidTokenMint)]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump,
+        token::mint = lp_token_mint,
+        token::authority = pool_state,
+    )]
+    pub lp_stake_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// Initialize the pool state with minimal stack usage.
+// Assets are whitelisted afterwards with `add_asset`.
+pub fn initialize(ctx: Context<Initialize>, pool_id: u64) -> Result<()> {
     // Save account keys to avoid multiple borrows
     let admin_key = ctx.accounts.admin.key();
-    let sol_vault_key = ctx.accounts.sol_vault.key();
-    let usdc_vault_key = ctx.accounts.usdc_vault.key();
     let usdc_mint_key = ctx.accounts.usdc_mint.key();
     let lp_token_mint_key = ctx.accounts.lp_token_mint.key();
     let usdc_reward_vault_key = ctx.accounts.usdc_reward_vault.key();
 
     // Set the pool state fields
-    let pool_state = &mut ctx.accounts.pool_state;
+    let mut pool_state = ctx.accounts.pool_state.load_init()?;
+    pool_state.version = POOL_STATE_VERSION;
+    pool_state.pool_id = pool_id;
 
     // Set admin and initialize empty authorities list
     pool_state.admin = admin_key;
-    pool_state.authorities = Vec::new();
+    pool_state.pending_admin = Pubkey::default();
+    pool_state.authority_count = 0;
 
-    // Set vault and mint addresses
-    pool_state.sol_vault = sol_vault_key;
-    pool_state.usdc_vault = usdc_vault_key;
+    // Set mint and reward vault addresses
     pool_state.usdc_mint = usdc_mint_key;
     pool_state.lp_token_mint = lp_token_mint_key;
     pool_state.usdc_reward_vault = usdc_reward_vault_key;
 
     // Initialize numeric fields to 0
-    pool_state.sol_deposited = 0;
-    pool_state.usdc_deposited = 0;
     pool_state.tokens_per_interval = 0;
     pool_state.reward_start_time = 0;
     pool_state.reward_end_time = 0;
@@ -122,8 +149,28 @@ pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
     pool_state.total_rewards_claimed = 0;
     pool_state.cumulative_reward_per_token = 0;
     pool_state.last_distribution_time = 0;
-    pool_state.accumulated_sol_fees = 0;
-    pool_state.accumulated_usdc_fees = 0;
+    pool_state.reward_remainder = 0;
+    pool_state.total_staked_lp = 0;
+
+    // Empty asset registry; dynamic fees stay off until the admin sets a tax
+    pool_state.asset_count = 0;
+    pool_state.total_target_weight_bps = 0;
+    pool_state.dynamic_fee_tax_bps = 0;
+
+    // No deposit caps until the admin sets them
+    pool_state.max_aum_usd = 0;
+    pool_state.max_user_lp = 0;
+
+    // Open to every wallet until the admin turns on permissioned mode
+    pool_state.allowlist_root = [0; 32];
+    pool_state.permissioned = 0;
+
+    // Stablecoins are valued at 1 USD until the admin sets a peg band
+    pool_state.depeg_pauses_deposits = 0;
+    pool_state.peg_band_bps = 0;
+
+    // Created fresh, so every user state is already keyed by this pool
+    pool_state.from_legacy_pool = 0;
 
     Ok(())
 }
@@ -147,3 +194,13 @@ pub fn initialize_lp_mint(ctx: Context<InitializeLpMint>) -> Result<()> {
     );
     Ok(())
 }
+
+// Handler function to initialize the LP stake vault
+// All initialization is handled by Anchor constraints
+pub fn initialize_lp_stake_vault(ctx: Context<InitializeLpStakeVault>) -> Result<()> {
+    msg!(
+        "Initialized LP stake vault: {}",
+        ctx.accounts.lp_stake_vault.key()
+    );
+    Ok(())
+}
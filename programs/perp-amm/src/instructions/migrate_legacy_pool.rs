@@ -0,0 +1,292 @@
+use super::add_asset::{register_asset, AddAssetParams, AssetAccounts};
+use crate::{errors::VaultError, state::*, PoolStateMigrated};
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token::{
+    self, spl_token::instruction::AuthorityType, spl_token::native_mint, Mint, SetAuthority, Token,
+    TokenAccount,
+};
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct MigrateLegacyPool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: Holds the original borsh layout, so it can't be deserialized by Anchor.
+    /// Owner, discriminator and admin are checked in the handler.
+    #[account(mut, seeds = [b"pool_state".as_ref()], bump)]
+    pub legacy_pool_state: UncheckedAccount<'info>,
+
+    /// The pool state the legacy pool moves to
+    #[account(
+        init,
+        payer = admin,
+        space = PoolState::LEN,
+        seeds = [b"pool_state".as_ref(), &pool_id.to_le_bytes()],
+        bump,
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Checked against the legacy pool state in the handler
+    #[account(mut)]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump,
+        token::mint = lp_token_mint,
+        token::authority = pool_state,
+    )]
+    pub lp_stake_vault: Box<Account<'info, TokenAccount>>,
+
+    /// Checked against the legacy pool state in the handler
+    #[account(mut)]
+    pub usdc_reward_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = native_mint::ID @ VaultError::InvalidTokenMint)]
+    pub sol_mint: Box<Account<'info, Mint>>,
+
+    /// Checked against the legacy pool state in the handler
+    #[account(mut, token::mint = sol_mint)]
+    pub sol_vault: Box<Account<'info, TokenAccount>>,
+
+    /// Registry entry for SOL, pointing at the legacy SOL vault
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AssetConfig::INIT_SPACE,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), sol_mint.key().as_ref()],
+        bump,
+    )]
+    pub sol_asset: Box<Account<'info, AssetConfig>>,
+
+    /// CHECK: Validated by reading a price from the feed below
+    pub sol_oracle_program: AccountInfo<'info>,
+
+    /// CHECK: Validated by reading a price from the feed below
+    pub sol_oracle_feed: AccountInfo<'info>,
+
+    /// Checked against the legacy pool state in the handler
+    pub usdc_mint: Box<Account<'info, Mint>>,
+
+    /// Checked against the legacy pool state in the handler
+    #[account(mut, token::mint = usdc_mint)]
+    pub usdc_vault: Box<Account<'info, TokenAccount>>,
+
+    /// Registry entry for USDC, pointing at the legacy USDC vault
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AssetConfig::INIT_SPACE,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), usdc_mint.key().as_ref()],
+        bump,
+    )]
+    pub usdc_asset: Box<Account<'info, AssetConfig>>,
+
+    /// CHECK: Validated by reading a price from the feed below (ignored for a
+    /// stable asset, as the new pool has no peg band)
+    pub usdc_oracle_program: AccountInfo<'info>,
+
+    /// CHECK: Validated by reading a price from the feed below (ignored for a
+    /// stable asset, as the new pool has no peg band)
+    pub usdc_oracle_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/**
+ * @dev Move the pool first deployed at `[b"pool_state"]` to `[b"pool_state", pool_id]`.
+ * Its rewards and authorities are copied across, and SOL and USDC are registered
+ * as assets that keep the legacy vaults along with their balances and fees. The
+ * legacy pool hands authority over the LP mint and every vault to the new pool
+ * state, then the legacy account is closed to the admin.
+ *
+ * As before LP staking, the whole LP supply counts as staked until each user
+ * state is moved by `migrate_legacy_user_state`.
+ */
+pub fn migrate_legacy_pool(
+    ctx: Context<MigrateLegacyPool>,
+    pool_id: u64,
+    sol_params: AddAssetParams,
+    usdc_params: AddAssetParams,
+) -> Result<()> {
+    let legacy_info = ctx.accounts.legacy_pool_state.to_account_info();
+    let admin_key = ctx.accounts.admin.key();
+
+    require_keys_eq!(*legacy_info.owner, crate::ID, VaultError::InvalidOwner);
+
+    let legacy = {
+        let data = legacy_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == PoolState::DISCRIMINATOR,
+            VaultError::InvalidAccountLayout
+        );
+        PoolStateV0::deserialize(&mut &data[8..])
+            .map_err(|_| error!(VaultError::InvalidAccountLayout))?
+    };
+
+    require_keys_eq!(legacy.admin, admin_key, VaultError::Unauthorized);
+    require!(
+        legacy.authorities.len() <= MAX_AUTHORITIES,
+        VaultError::MaxAuthoritiesReached
+    );
+    require_keys_eq!(
+        ctx.accounts.lp_token_mint.key(),
+        legacy.lp_token_mint,
+        VaultError::InvalidTokenMint
+    );
+    require_keys_eq!(
+        ctx.accounts.usdc_mint.key(),
+        legacy.usdc_mint,
+        VaultError::InvalidTokenMint
+    );
+    require_keys_eq!(
+        ctx.accounts.sol_vault.key(),
+        legacy.sol_vault,
+        VaultError::InvalidTokenAccount
+    );
+    require_keys_eq!(
+        ctx.accounts.usdc_vault.key(),
+        legacy.usdc_vault,
+        VaultError::InvalidTokenAccount
+    );
+    require_keys_eq!(
+        ctx.accounts.usdc_reward_vault.key(),
+        legacy.usdc_reward_vault,
+        VaultError::InvalidTokenAccount
+    );
+
+    let pool_key = ctx.accounts.pool_state.key();
+    let mut pool_state = ctx.accounts.pool_state.load_init()?;
+    pool_state.version = POOL_STATE_VERSION;
+    pool_state.pool_id = pool_id;
+    pool_state.from_legacy_pool = 1;
+
+    // Legacy authorities could do everything; keep it that way until the admin
+    // narrows them down with `set_authority_roles`
+    pool_state.admin = legacy.admin;
+    pool_state.pending_admin = Pubkey::default();
+    for authority in &legacy.authorities {
+        pool_state.add_authority(*authority, roles::ALL)?;
+    }
+
+    pool_state.usdc_mint = legacy.usdc_mint;
+    pool_state.lp_token_mint = legacy.lp_token_mint;
+    pool_state.usdc_reward_vault = legacy.usdc_reward_vault;
+
+    pool_state.tokens_per_interval = legacy.tokens_per_interval;
+    pool_state.reward_start_time = legacy.reward_start_time;
+    pool_state.reward_end_time = legacy.reward_end_time;
+    pool_state.total_rewards_deposited = legacy.total_rewards_deposited;
+    pool_state.total_rewards_claimed = legacy.total_rewards_claimed;
+    pool_state.cumulative_reward_per_token = legacy.cumulative_reward_per_token;
+    pool_state.last_distribution_time = legacy.last_distribution_time;
+    pool_state.reward_remainder = 0;
+
+    // Every legacy LP token earned rewards, so the whole supply counts as staked
+    // until `migrate_legacy_user_state` unstakes each legacy position
+    pool_state.total_staked_lp = ctx.accounts.lp_token_mint.supply;
+
+    // The legacy vaults become the SOL and USDC asset vaults, books included
+    register_asset(
+        pool_key,
+        &mut pool_state,
+        &mut ctx.accounts.sol_asset,
+        ctx.bumps.sol_asset,
+        AssetAccounts {
+            mint: &ctx.accounts.sol_mint,
+            vault: legacy.sol_vault,
+            oracle_program: &ctx.accounts.sol_oracle_program,
+            oracle_feed: &ctx.accounts.sol_oracle_feed,
+        },
+        &sol_params,
+    )?;
+    ctx.accounts.sol_asset.deposited = legacy.sol_deposited;
+    ctx.accounts.sol_asset.accumulated_fees = legacy.accumulated_sol_fees;
+
+    register_asset(
+        pool_key,
+        &mut pool_state,
+        &mut ctx.accounts.usdc_asset,
+        ctx.bumps.usdc_asset,
+        AssetAccounts {
+            mint: &ctx.accounts.usdc_mint,
+            vault: legacy.usdc_vault,
+            oracle_program: &ctx.accounts.usdc_oracle_program,
+            oracle_feed: &ctx.accounts.usdc_oracle_feed,
+        },
+        &usdc_params,
+    )?;
+    ctx.accounts.usdc_asset.deposited = legacy.usdc_deposited;
+    ctx.accounts.usdc_asset.accumulated_fees = legacy.accumulated_usdc_fees;
+
+    drop(pool_state);
+
+    // Hand the LP mint and the vaults over from the legacy pool state
+    let legacy_seeds = &[b"pool_state".as_ref(), &[ctx.bumps.legacy_pool_state]];
+    let handovers = [
+        (
+            ctx.accounts.lp_token_mint.to_account_info(),
+            AuthorityType::MintTokens,
+        ),
+        (
+            ctx.accounts.lp_token_mint.to_account_info(),
+            AuthorityType::FreezeAccount,
+        ),
+        (
+            ctx.accounts.sol_vault.to_account_info(),
+            AuthorityType::AccountOwner,
+        ),
+        (
+            ctx.accounts.usdc_vault.to_account_info(),
+            AuthorityType::AccountOwner,
+        ),
+        (
+            ctx.accounts.usdc_reward_vault.to_account_info(),
+            AuthorityType::AccountOwner,
+        ),
+    ];
+    for (account, authority_type) in handovers {
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: legacy_info.clone(),
+                    account_or_mint: account,
+                },
+                &[&legacy_seeds[..]],
+            ),
+            authority_type,
+            Some(pool_key),
+        )?;
+    }
+
+    // Close the legacy account to the admin
+    let admin_info = ctx.accounts.admin.to_account_info();
+    **admin_info.lamports.borrow_mut() = admin_info
+        .lamports()
+        .checked_add(legacy_info.lamports())
+        .ok_or(VaultError::MathError)?;
+    **legacy_info.lamports.borrow_mut() = 0;
+    legacy_info.realloc(0, false)?;
+    legacy_info.assign(&ctx.accounts.system_program.key());
+
+    emit!(PoolStateMigrated {
+        admin: admin_key,
+        pool: pool_key,
+        from_version: 0,
+        to_version: POOL_STATE_VERSION,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Migrated legacy pool state {} to {}",
+        legacy_info.key(),
+        pool_key
+    );
+    Ok(())
+}
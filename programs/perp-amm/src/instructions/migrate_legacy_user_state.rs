@@ -0,0 +1,151 @@
+use crate::{errors::VaultError, state::*, util::*};
+use anchor_lang::{prelude::*, Discriminator};
+
+/// Remaining accounts: every reward stream, writable and in index order
+#[derive(Accounts)]
+pub struct MigrateLegacyUserState<'info> {
+    /// Pays rent for the moved user state. Migration only settles rewards the user
+    /// has already earned, so anyone may run it.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.from_legacy_pool != 0 @ VaultError::NotLegacyPool
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// CHECK: Owner of the user state; receives the legacy account's rent.
+    /// Checked against the legacy user state in the handler.
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+
+    /// CHECK: Holds the legacy layout at the legacy address, so it can't be
+    /// deserialized by Anchor. Owner and discriminator are checked in the handler.
+    #[account(
+        mut,
+        seeds = [b"user_state".as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub legacy_user_state: UncheckedAccount<'info>,
+
+    /// May already exist if the user deposited into the migrated pool first
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserState::LEN,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * @dev Move a user state of the legacy pool from `[b"user_state", user]` to
+ * `[b"user_state", pool_state, user]`, upgrading it to the current version.
+ *
+ * The rewards earned on the legacy LP balance are settled and the balance is
+ * cleared: the LP stays in the user's wallet and earns again once staked with
+ * `stake_lp`. If the user already
+ * holds a position in the migrated pool, the settled rewards are added to it.
+ * The legacy account is closed to the user.
+ */
+pub fn migrate_legacy_user_state(ctx: Context<MigrateLegacyUserState>) -> Result<()> {
+    let legacy_info = ctx.accounts.legacy_user_state.to_account_info();
+    require_keys_eq!(
+        *legacy_info.owner,
+        crate::ID,
+        VaultError::InvalidAccountLayout
+    );
+
+    // Read the legacy fields, leaving the rest of the layout at its defaults
+    let mut legacy = {
+        let data = legacy_info.try_borrow_data()?;
+        require!(
+            data.len() == UserState::LEGACY_LEN && data[..8] == UserState::DISCRIMINATOR,
+            VaultError::InvalidAccountLayout
+        );
+        let mut padded = data.to_vec();
+        padded.resize(UserState::LEN, 0);
+        UserState::try_deserialize(&mut &padded[..])?
+    };
+    require_keys_eq!(
+        legacy.owner,
+        ctx.accounts.user.key(),
+        VaultError::InvalidOwner
+    );
+
+    let pool_key = ctx.accounts.pool_state.key();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    settle_legacy_balance(
+        pool_key,
+        &mut pool_state,
+        &mut legacy,
+        ctx.remaining_accounts,
+    )?;
+
+    let user_state = &mut ctx.accounts.user_state;
+    if user_state.owner == Pubkey::default() {
+        user_state.set_inner(legacy);
+    } else {
+        // Only the settled rewards carry over; the position's own checkpoints stand
+        user_state.pending_rewards = user_state
+            .pending_rewards
+            .checked_add(legacy.pending_rewards)
+            .ok_or(VaultError::MathError)?;
+        for (checkpoint, legacy_checkpoint) in user_state
+            .reward_checkpoints
+            .iter_mut()
+            .zip(legacy.reward_checkpoints.iter())
+        {
+            checkpoint.pending_rewards = checkpoint
+                .pending_rewards
+                .checked_add(legacy_checkpoint.pending_rewards)
+                .ok_or(VaultError::MathError)?;
+        }
+    }
+
+    // Close the legacy account to the user
+    let user_info = ctx.accounts.user.to_account_info();
+    **user_info.lamports.borrow_mut() = user_info
+        .lamports()
+        .checked_add(legacy_info.lamports())
+        .ok_or(VaultError::MathError)?;
+    **legacy_info.lamports.borrow_mut() = 0;
+    legacy_info.realloc(0, false)?;
+    legacy_info.assign(&ctx.accounts.system_program.key());
+
+    msg!(
+        "Migrated legacy user state {} to {}",
+        legacy_info.key(),
+        user_state.key()
+    );
+    Ok(())
+}
+
+/// Settle the rewards earned on a legacy LP balance, then take that balance out
+/// of the pool's staked total and bring the user state to the current version
+fn settle_legacy_balance<'info>(
+    pool_key: Pubkey,
+    pool_state: &mut PoolState,
+    user_state: &mut UserState,
+    reward_stream_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    user_state.version = USER_STATE_VERSION;
+
+    update_rewards(pool_state, user_state)?;
+    update_all_stream_rewards(pool_key, pool_state, user_state, reward_stream_accounts)?;
+
+    let unstaked =
+        u64::try_from(user_state.lp_token_balance).map_err(|_| error!(VaultError::MathError))?;
+    pool_state.total_staked_lp = pool_state
+        .total_staked_lp
+        .checked_sub(unstaked)
+        .ok_or(VaultError::MathError)?;
+    user_state.lp_token_balance = 0;
+    Ok(())
+}
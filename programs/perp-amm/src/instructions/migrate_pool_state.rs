@@ -0,0 +1,79 @@
+use crate::{errors::VaultError, state::*, PoolStateMigrated};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct MigratePoolState<'info> {
+    /// Pays the rent for any space the pool state grows by
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Grown to `PoolState::LEN` first, as pools from before version 11 are
+    /// smaller and can't be loaded until they are; Anchor applies `realloc`
+    /// before the constraints below read the account
+    #[account(
+        mut,
+        realloc = PoolState::LEN,
+        realloc::payer = admin,
+        realloc::zero = true,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// CHECK: Only checked for existence, to flag pools whose outflow limits
+    /// were set before version 12
+    #[account(seeds = [b"outflow_limits".as_ref(), pool_state.key().as_ref()], bump)]
+    pub outflow_limits: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * @dev Upgrade the pool state to the current layout version. Versions that take
+ * their fields out of `reserved` initialize them here; versions that grow the
+ * account get the new space from the `realloc` above, zeroed.
+ */
+pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+
+    require!(
+        pool_state.version < POOL_STATE_VERSION,
+        VaultError::AlreadyMigrated
+    );
+
+    // Version 10 is the oldest zero-copy layout in use; the original pool is
+    // moved to the current layout by `migrate_legacy_pool` instead
+    let from_version = pool_state.version;
+    require!(from_version >= 10, VaultError::InvalidAccountLayout);
+
+    // Version 11 takes `reward_remainder` out of the last of the original
+    // `reserved` space and grows the account by a new `reserved` tail. The
+    // remainder older rates were rounded down by is already lost.
+    if from_version < 11 {
+        pool_state.reward_remainder = 0;
+        pool_state.reserved = [0; 127];
+    }
+
+    // Version 12 takes `has_outflow_limits` out of `reserved`
+    if from_version < 12 {
+        pool_state.has_outflow_limits = u8::from(!ctx.accounts.outflow_limits.data_is_empty());
+    }
+
+    pool_state.version = POOL_STATE_VERSION;
+
+    emit!(PoolStateMigrated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        from_version,
+        to_version: POOL_STATE_VERSION,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Migrated pool state from version {} to {}",
+        from_version,
+        POOL_STATE_VERSION
+    );
+    Ok(())
+}
@@ -1,27 +1,77 @@
+pub mod accept_admin;
+pub mod add_asset;
 pub mod add_authority;
+pub mod add_reward_stream;
 pub mod admin_withdraw;
+pub mod allowlist;
 pub mod claim_fees;
 pub mod claim_rewards;
+pub mod claim_stream_rewards;
 pub mod close_pool;
 pub mod close_user_state;
+pub mod compound_rewards;
 pub mod deposit;
+pub mod deposit_sol;
 pub mod direct_deposit;
 pub mod force_close_user_state;
+pub mod fund_reward_stream;
 pub mod initialize;
+pub mod migrate_legacy_pool;
+pub mod migrate_legacy_user_state;
+pub mod migrate_pool_state;
+pub mod pause;
+pub mod propose_admin;
+pub mod reconcile;
 pub mod remove_authority;
+pub mod set_authority_roles;
+pub mod set_deposit_limits;
+pub mod set_fees;
+pub mod set_oracle;
+pub mod set_outflow_limits;
+pub mod set_peg_band;
+pub mod set_target_weight;
+pub mod stake_lp;
 pub mod start_rewards;
+pub mod unstake_lp;
+pub mod views;
 pub mod withdraw;
+pub mod withdraw_sol;
 
+pub use accept_admin::*;
+pub use add_asset::*;
 pub use add_authority::*;
+pub use add_reward_stream::*;
 pub use admin_withdraw::*;
+pub use allowlist::*;
 pub use claim_fees::*;
 pub use claim_rewards::*;
+pub use claim_stream_rewards::*;
 pub use close_pool::*;
 pub use close_user_state::*;
+pub use compound_rewards::*;
 pub use deposit::*;
+pub use deposit_sol::*;
 pub use direct_deposit::*;
 pub use force_close_user_state::*;
+pub use fund_reward_stream::*;
 pub use initialize::*;
+pub use migrate_legacy_pool::*;
+pub use migrate_legacy_user_state::*;
+pub use migrate_pool_state::*;
+pub use pause::*;
+pub use propose_admin::*;
+pub use reconcile::*;
 pub use remove_authority::*;
+pub use set_authority_roles::*;
+pub use set_deposit_limits::*;
+pub use set_fees::*;
+pub use set_oracle::*;
+pub use set_outflow_limits::*;
+pub use set_peg_band::*;
+pub use set_target_weight::*;
+pub use stake_lp::*;
 pub use start_rewards::*;
+pub use unstake_lp::*;
+pub use views::*;
 pub use withdraw::*;
+pub use withdraw_sol::*;
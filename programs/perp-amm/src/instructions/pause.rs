@@ -0,0 +1,57 @@
+use crate::{errors::VaultError, state::*, OperationsPaused, OperationsUnpaused};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// The admin, or an authority with the pauser role
+    pub pauser: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.has_role(&pauser.key(), roles::PAUSER) @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+/**
+ * @dev Pause the given operations (a `pause_flags` bitmask), e.g. deposits
+ * during an oracle incident. Operations that are already paused stay paused.
+ */
+pub fn pause(ctx: Context<SetPaused>, operations: u8) -> Result<()> {
+    validate_pause_flags(operations)?;
+
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.paused |= operations;
+
+    msg!("Paused operations {:#b}", operations);
+    emit!(OperationsPaused {
+        caller: ctx.accounts.pauser.key(),
+        pool: ctx.accounts.pool_state.key(),
+        operations,
+        paused: pool_state.paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/**
+ * @dev Resume the given operations (a `pause_flags` bitmask)
+ */
+pub fn unpause(ctx: Context<SetPaused>, operations: u8) -> Result<()> {
+    validate_pause_flags(operations)?;
+
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.paused &= !operations;
+
+    msg!("Unpaused operations {:#b}", operations);
+    emit!(OperationsUnpaused {
+        caller: ctx.accounts.pauser.key(),
+        pool: ctx.accounts.pool_state.key(),
+        operations,
+        paused: pool_state.paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use crate::errors::VaultError;
+use crate::state::PoolState;
+use crate::AdminProposed;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+/**
+ * @dev Nominate a new admin for the pool. Control only moves once the nominee
+ * signs `accept_admin`, so a mistyped key leaves the current admin in place and
+ * can simply be replaced. Proposing `Pubkey::default()` cancels a pending transfer.
+ */
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.pending_admin = new_admin;
+
+    msg!("Proposed new admin: {}", new_admin);
+    emit!(AdminProposed {
+        pool: ctx.accounts.pool_state.key(),
+        admin: ctx.accounts.admin.key(),
+        pending_admin: new_admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use crate::{errors::VaultError, state::*, AssetReconciled, OperationsPaused};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+/// What `reconcile` does with a mismatch between a vault and its books
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReconcileAction {
+    /// Only report the difference; anyone can do this
+    Report,
+    /// Admin: credit tokens the books don't account for to `deposited`, so LPs own them
+    SweepSurplus,
+    /// Admin: record a shortfall and pause deposits and withdrawals until it is resolved
+    FlagDeficit,
+}
+
+/// An asset vault's balance against what the pool has booked for it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Reconciliation {
+    /// Tokens actually held by the vault (asset decimals)
+    pub vault_balance: u64,
+
+    /// `deposited` plus `accumulated_fees` before any sweep (asset decimals)
+    pub booked: u64,
+
+    /// Tokens in the vault beyond what is booked (asset decimals)
+    pub surplus: u64,
+
+    /// Booked tokens missing from the vault (asset decimals)
+    pub deficit: u64,
+}
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    /// Anyone can report; sweeping and flagging need the admin
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Registry entry of the asset being reconciled
+    #[account(
+        mut,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+
+    #[account(address = asset.vault @ VaultError::InvalidTokenAccount)]
+    pub vault_account: Account<'info, TokenAccount>,
+}
+
+/**
+ * @dev Compare an asset vault's balance with the pool's books (`deposited` plus
+ * `accumulated_fees`). Tokens sent straight to the vault show up as a surplus;
+ * anything booked but missing shows up as a deficit. The result is emitted and
+ * returned; `action` optionally settles it.
+ */
+pub fn reconcile(ctx: Context<Reconcile>, action: ReconcileAction) -> Result<Reconciliation> {
+    let pool_key = ctx.accounts.pool_state.key();
+    let caller = ctx.accounts.caller.key();
+    let asset = &mut ctx.accounts.asset;
+    let now = Clock::get()?.unix_timestamp;
+
+    let vault_balance = ctx.accounts.vault_account.amount;
+    let booked = asset
+        .deposited
+        .checked_add(asset.accumulated_fees)
+        .ok_or(VaultError::MathError)?;
+    let reconciliation = Reconciliation {
+        vault_balance,
+        booked,
+        surplus: vault_balance.saturating_sub(booked),
+        deficit: booked.saturating_sub(vault_balance),
+    };
+
+    if action != ReconcileAction::Report {
+        require!(
+            ctx.accounts.pool_state.load()?.is_admin(&caller),
+            VaultError::Unauthorized
+        );
+    }
+
+    match action {
+        ReconcileAction::Report => {}
+        ReconcileAction::SweepSurplus => {
+            require!(reconciliation.surplus > 0, VaultError::NoSurplus);
+            // Counting the surplus as deposited raises AUM, and so the LP price
+            asset.deposited = asset
+                .deposited
+                .checked_add(reconciliation.surplus)
+                .ok_or(VaultError::MathError)?;
+        }
+        ReconcileAction::FlagDeficit => {
+            require!(reconciliation.deficit > 0, VaultError::NoDeficit);
+            // AUM is overstated by the deficit, so stop LPs trading at that price
+            let operations = pause_flags::DEPOSITS | pause_flags::WITHDRAWALS;
+            let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+            pool_state.paused |= operations;
+
+            emit!(OperationsPaused {
+                caller,
+                pool: pool_key,
+                operations,
+                paused: pool_state.paused,
+                timestamp: now,
+            });
+        }
+    }
+
+    emit!(AssetReconciled {
+        caller,
+        pool: pool_key,
+        mint: asset.mint,
+        vault_balance: reconciliation.vault_balance,
+        booked: reconciliation.booked,
+        surplus: reconciliation.surplus,
+        deficit: reconciliation.deficit,
+        action,
+        timestamp: now,
+    });
+
+    Ok(reconciliation)
+}
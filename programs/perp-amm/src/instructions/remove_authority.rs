@@ -1,5 +1,6 @@
 use crate::errors::ErrorCode;
 use crate::state::PoolState;
+use crate::AuthorityRemoved;
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -8,11 +9,11 @@ pub struct RemoveAuthority<'info> {
     pub admin: Signer<'info>,
 
     #[account(mut)]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 }
 
 pub fn remove_authority(ctx: Context<RemoveAuthority>, authority_to_remove: Pubkey) -> Result<()> {
-    let pool_state = &mut ctx.accounts.pool_state;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
 
     // Ensure only the admin can remove authorities
     require_keys_eq!(
@@ -23,20 +24,19 @@ pub fn remove_authority(ctx: Context<RemoveAuthority>, authority_to_remove: Pubk
 
     // Cannot remove the last authority
     require!(
-        pool_state.authorities.len() > 1,
+        pool_state.authority_count > 1,
         ErrorCode::CannotRemoveLastAuthority
     );
 
     // Find and remove the authority
-    let initial_len = pool_state.authorities.len();
-    pool_state.authorities.retain(|&a| a != authority_to_remove);
-
-    // Check if authority was found and removed
-    require!(
-        pool_state.authorities.len() < initial_len,
-        ErrorCode::AuthorityNotFound
-    );
+    pool_state.remove_authority(&authority_to_remove)?;
 
     msg!("Removed authority: {}", authority_to_remove);
+    emit!(AuthorityRemoved {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        authority: authority_to_remove,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
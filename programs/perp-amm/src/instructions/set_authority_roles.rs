@@ -0,0 +1,40 @@
+use crate::errors::ErrorCode;
+use crate::state::PoolState;
+use crate::AuthorityRolesUpdated;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetAuthorityRoles<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn set_authority_roles(
+    ctx: Context<SetAuthorityRoles>,
+    authority: Pubkey,
+    roles: u8,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+
+    // Ensure only the admin can change roles
+    require_keys_eq!(
+        pool_state.admin,
+        ctx.accounts.admin.key(),
+        ErrorCode::Unauthorized
+    );
+
+    // Replace the authority's roles (rejects unknown authorities and roles)
+    pool_state.set_authority_roles(&authority, roles)?;
+
+    msg!("Set roles of authority {} to {:#b}", authority, roles);
+    emit!(AuthorityRolesUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        authority,
+        roles,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::{errors::VaultError, state::PoolState, DepositLimitsUpdated};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetDepositLimits<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+/**
+ * @dev Cap how large deposits can grow the pool (`max_aum_usd`, 8 decimals) and
+ * the staked LP position a deposit can leave a single wallet with (`max_user_lp`).
+ * Either can be 0 for no cap. Lowering a cap below the current value only blocks
+ * further deposits; nothing is forced out.
+ */
+pub fn set_deposit_limits(
+    ctx: Context<SetDepositLimits>,
+    max_aum_usd: u64,
+    max_user_lp: u64,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.max_aum_usd = max_aum_usd;
+    pool_state.max_user_lp = max_user_lp;
+
+    emit!(DepositLimitsUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        max_aum_usd,
+        max_user_lp,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use crate::{
+    errors::VaultError,
+    state::{AssetConfig, PoolState, MAX_FEE_BPS},
+    FeesUpdated,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetFees<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+}
+
+pub fn set_fees(ctx: Context<SetFees>, deposit_fee_bps: u16, withdraw_fee_bps: u16) -> Result<()> {
+    require!(
+        deposit_fee_bps <= MAX_FEE_BPS && withdraw_fee_bps <= MAX_FEE_BPS,
+        VaultError::FeeTooHigh
+    );
+
+    let asset = &mut ctx.accounts.asset;
+    asset.deposit_fee_bps = deposit_fee_bps;
+    asset.withdraw_fee_bps = withdraw_fee_bps;
+
+    emit!(FeesUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        mint: asset.mint,
+        deposit_fee_bps,
+        withdraw_fee_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
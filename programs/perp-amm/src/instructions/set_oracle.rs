@@ -0,0 +1,82 @@
+use crate::{
+    errors::VaultError,
+    oracle::{self, OracleKind},
+    state::{AssetConfig, PoolState},
+    OracleUpdated,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetOracle<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+
+    /// CHECK: Validated by reading a price from the new feed below
+    pub oracle_program: AccountInfo<'info>,
+
+    /// CHECK: Validated by reading a price from the new feed below
+    pub oracle_feed: AccountInfo<'info>,
+}
+
+/**
+ * @dev Point an asset at a new USD oracle feed.
+ * A price is read and validated from the new feed before it is stored,
+ * so a misconfigured feed can't brick deposits and withdrawals.
+ */
+pub fn set_oracle(
+    ctx: Context<SetOracle>,
+    oracle_kind: OracleKind,
+    oracle_feed_id: [u8; 32],
+    max_price_age: u64,
+    max_confidence_bps: u16,
+) -> Result<()> {
+    require!(max_price_age > 0, VaultError::InvalidOracleConfig);
+
+    let price = oracle::read_price(
+        oracle_kind,
+        &ctx.accounts.oracle_program,
+        &ctx.accounts.oracle_feed,
+        &oracle_feed_id,
+    )?;
+    price
+        .validate(
+            Clock::get()?.unix_timestamp,
+            max_price_age,
+            max_confidence_bps,
+        )
+        .map_err(|rejection| error!(VaultError::from(rejection)))?;
+
+    let asset = &mut ctx.accounts.asset;
+    asset.oracle_kind = oracle_kind;
+    asset.oracle_program = ctx.accounts.oracle_program.key();
+    asset.oracle_feed = ctx.accounts.oracle_feed.key();
+    asset.oracle_feed_id = oracle_feed_id;
+    asset.max_price_age = max_price_age;
+    asset.max_confidence_bps = max_confidence_bps;
+
+    emit!(OracleUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        mint: asset.mint,
+        oracle_kind,
+        oracle_feed: asset.oracle_feed,
+        max_price_age,
+        max_confidence_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use crate::{
+    errors::VaultError,
+    state::{OutflowLimits, PoolState, BPS_DENOMINATOR},
+    OutflowLimitsUpdated,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetOutflowLimits<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Created the first time the pool's limits are set, which flags the pool as
+    /// having them
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = OutflowLimits::LEN,
+        seeds = [b"outflow_limits".as_ref(), pool_state.key().as_ref()],
+        bump
+    )]
+    pub outflow_limits: Account<'info, OutflowLimits>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * @dev Configure how much `admin_withdraw` may move out of the pool per window.
+ * Caps are basis points of the AUM at the start of each window; a `window_secs`
+ * of 0 turns the limits off. Withdrawals already made in the current window
+ * keep counting against the new caps.
+ */
+pub fn set_outflow_limits(
+    ctx: Context<SetOutflowLimits>,
+    window_secs: u64,
+    max_outflow_bps: u16,
+    max_caller_outflow_bps: u16,
+) -> Result<()> {
+    if window_secs > 0 {
+        require!(
+            (1..=BPS_DENOMINATOR).contains(&(max_outflow_bps as u64))
+                && (1..=max_outflow_bps).contains(&max_caller_outflow_bps),
+            VaultError::InvalidOutflowLimits
+        );
+    }
+
+    let outflow_limits = &mut ctx.accounts.outflow_limits;
+    outflow_limits.pool_state = ctx.accounts.pool_state.key();
+    outflow_limits.bump = ctx.bumps.outflow_limits;
+    outflow_limits.window_secs = window_secs;
+    outflow_limits.max_outflow_bps = max_outflow_bps;
+    outflow_limits.max_caller_outflow_bps = max_caller_outflow_bps;
+
+    // From now on `admin_withdraw` requires the account
+    ctx.accounts.pool_state.load_mut()?.has_outflow_limits = 1;
+
+    emit!(OutflowLimitsUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        window_secs,
+        max_outflow_bps,
+        max_caller_outflow_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
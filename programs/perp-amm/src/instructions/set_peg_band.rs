@@ -0,0 +1,63 @@
+use crate::{
+    errors::VaultError,
+    state::{PoolState, MAX_PEG_BAND_BPS},
+    util::{compute_aum, Rounding},
+    PegBandUpdated,
+};
+use anchor_lang::prelude::*;
+
+/// Remaining accounts: [asset_config, oracle_program, oracle_feed] for every
+/// registered asset, in index order (see `compute_aum`). Only needed when
+/// setting a non-zero band.
+#[derive(Accounts)]
+pub struct SetPegBand<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+/**
+ * @dev Set how far stablecoins may stray from 1 USD before they are valued at
+ * their oracle price, and whether deposits are rejected while one is outside
+ * the band. A band of 0 values stablecoins at exactly 1 USD without reading
+ * their oracle. Before a non-zero band is stored the whole pool is priced,
+ * so a stable asset with a missing or broken feed can't brick deposits and
+ * withdrawals; point it at a working feed with `set_oracle` first.
+ */
+pub fn set_peg_band(
+    ctx: Context<SetPegBand>,
+    peg_band_bps: u16,
+    depeg_pauses_deposits: bool,
+) -> Result<()> {
+    require!(peg_band_bps <= MAX_PEG_BAND_BPS, VaultError::InvalidPegBand);
+
+    let pool_key = ctx.accounts.pool_state.key();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.peg_band_bps = peg_band_bps;
+    pool_state.depeg_pauses_deposits = depeg_pauses_deposits as u8;
+
+    if peg_band_bps > 0 {
+        compute_aum(
+            pool_key,
+            &pool_state,
+            ctx.remaining_accounts,
+            Rounding::Down,
+        )?;
+    }
+
+    emit!(PegBandUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: pool_key,
+        peg_band_bps,
+        depeg_pauses_deposits,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::{
+    errors::VaultError,
+    state::{AssetConfig, PoolState, MAX_DYNAMIC_FEE_TAX_BPS},
+    DynamicFeeTaxUpdated, TargetWeightUpdated,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetTargetWeight<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetDynamicFeeTax<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = pool_state.load()?.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+/**
+ * @dev Set an asset's target weight. Targets are relative: an asset's target share
+ * of AUM is its weight divided by the sum of every asset's weight.
+ */
+pub fn set_target_weight(ctx: Context<SetTargetWeight>, target_weight_bps: u16) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    let asset = &mut ctx.accounts.asset;
+
+    pool_state.total_target_weight_bps = pool_state
+        .total_target_weight_bps
+        .checked_sub(asset.target_weight_bps as u32)
+        .ok_or(VaultError::MathError)?
+        .checked_add(target_weight_bps as u32)
+        .ok_or(VaultError::MathError)?;
+    asset.target_weight_bps = target_weight_bps;
+
+    emit!(TargetWeightUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        mint: asset.mint,
+        target_weight_bps,
+        total_target_weight_bps: pool_state.total_target_weight_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/**
+ * @dev Set the maximum fee rebate/surcharge applied by dynamic fees (0 disables them).
+ */
+pub fn set_dynamic_fee_tax(ctx: Context<SetDynamicFeeTax>, dynamic_fee_tax_bps: u16) -> Result<()> {
+    require!(
+        dynamic_fee_tax_bps <= MAX_DYNAMIC_FEE_TAX_BPS,
+        VaultError::FeeTooHigh
+    );
+
+    ctx.accounts.pool_state.load_mut()?.dynamic_fee_tax_bps = dynamic_fee_tax_bps;
+
+    emit!(DynamicFeeTaxUpdated {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        dynamic_fee_tax_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
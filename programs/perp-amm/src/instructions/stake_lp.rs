@@ -0,0 +1,107 @@
+use crate::{errors::VaultError, state::*, util::*, LpStaked};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+/// Remaining accounts: every reward stream, writable and in index order
+#[derive(Accounts)]
+pub struct StakeLp<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::DEPOSITS) @ VaultError::OperationPaused
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserState::LEN,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint @ VaultError::InvalidTokenMint)]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = user_lp_token_account.owner == user.key() @ VaultError::InvalidTokenAccount,
+        constraint = user_lp_token_account.mint == lp_token_mint.key() @ VaultError::InvalidTokenMint
+    )]
+    pub user_lp_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump
+    )]
+    pub lp_stake_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Stake LP tokens held in the user's wallet so they earn rewards. LP bought or
+/// received from someone else only earns once it is staked, up to the pool's
+/// per-wallet LP cap.
+pub fn stake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return err!(VaultError::InvalidTokenAmount);
+    }
+
+    let pool_key = ctx.accounts.pool_state.key();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    let user_state = &mut ctx.accounts.user_state;
+
+    // New user states start on the current version; older ones must be migrated first
+    if user_state.owner == Pubkey::default() {
+        user_state.owner = ctx.accounts.user.key();
+        user_state.version = USER_STATE_VERSION;
+        user_state.last_claim_timestamp = Clock::get()?.unix_timestamp as u64;
+    }
+
+    // Settle rewards on the old stake before it grows
+    update_rewards(&mut pool_state, user_state)?;
+    update_all_stream_rewards(pool_key, &pool_state, user_state, ctx.remaining_accounts)?;
+
+    user_state.lp_token_balance = user_state
+        .lp_token_balance
+        .checked_add(amount as u128)
+        .ok_or(VaultError::MathError)?;
+
+    // The per-wallet cap counts staked LP, so LP unstaked or bought elsewhere
+    // can't be staked past it. Staking adds nothing to AUM, so that cap doesn't apply.
+    pool_state.check_deposit_limits(0, user_state.lp_token_balance)?;
+    pool_state.total_staked_lp = pool_state
+        .total_staked_lp
+        .checked_add(amount)
+        .ok_or(VaultError::MathError)?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_lp_token_account.to_account_info(),
+                to: ctx.accounts.lp_stake_vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit!(LpStaked {
+        user: ctx.accounts.user.key(),
+        pool: pool_key,
+        amount,
+        staked_balance: user_state.lp_token_balance as u64,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,18 +1,19 @@
-use crate::{errors::VaultError, state::*};
+use crate::{errors::VaultError, state::*, util::*, RewardsStarted};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 #[derive(Accounts)]
 pub struct StartRewards<'info> {
+    /// The admin, or an authority with the rewards manager role
     pub admin: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"pool_state".as_ref()],
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
         bump,
-        constraint = pool_state.admin == admin.key() @ VaultError::Unauthorized
+        constraint = pool_state.load()?.has_role(&admin.key(), roles::REWARDS_MANAGER) @ VaultError::Unauthorized
     )]
-    pub pool_state: Account<'info, PoolState>,
+    pub pool_state: AccountLoader<'info, PoolState>,
 
     /// Admin's USDC token account
     #[account(mut)]
@@ -21,23 +22,27 @@ pub struct StartRewards<'info> {
     /// Program's USDC reward vault (PDA)
     #[account(
         mut,
-        constraint = usdc_reward_vault.key() == pool_state.usdc_reward_vault @ VaultError::InvalidRewardVault
+        constraint = usdc_reward_vault.key() == pool_state.load()?.usdc_reward_vault @ VaultError::InvalidRewardVault
     )]
     pub usdc_reward_vault: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
 }
 
-pub fn start_rewards(
-    ctx: Context<StartRewards>,
-    usdc_amount: u64, // Total rewards for the period
-) -> Result<()> {
-    let pool_state = &mut ctx.accounts.pool_state;
-
-    // Validate input USDC amount.
+/// Start a reward period of `duration` seconds funded with `usdc_amount`. Calling
+/// it while a period is running tops it up: whatever the running period has yet to
+/// emit rolls into the new one, which starts now, so the rate rises (same length)
+/// or the period extends (longer duration). So does the remainder the last rate was
+/// rounded down by, so every deposited token is eventually emitted.
+pub fn start_rewards(ctx: Context<StartRewards>, usdc_amount: u64, duration: u64) -> Result<()> {
+    // Validate input USDC amount and period length.
     if usdc_amount == 0 {
         return err!(VaultError::InvalidTokenAmount);
     }
+    require!(
+        duration > 0 && duration <= MAX_REWARD_DURATION,
+        VaultError::InvalidRewardDuration
+    );
 
     // Transfer USDC from admin to reward vault
     let cpi_ctx = CpiContext::new(
@@ -50,16 +55,47 @@ pub fn start_rewards(
     );
     token::transfer(cpi_ctx, usdc_amount)?;
 
-    // Calculate tokens per interval (per second)
-    let tokens_per_interval = usdc_amount
-        .checked_div(604800) // One week in seconds
-        .ok_or(VaultError::MathError)?;
+    let now = Clock::get()?.unix_timestamp;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+
+    // Settle emissions so far at the current rate before changing it
+    accrue_pool_rewards(&mut pool_state)?;
+
+    // Calculate tokens per interval (per second), rolling over whatever the
+    // running period has yet to emit
+    let (tokens_per_interval, rolled_over, reward_remainder) = next_reward_rate(
+        pool_state.reward_end_time,
+        pool_state.tokens_per_interval,
+        pool_state.reward_remainder,
+        now as u64,
+        usdc_amount,
+        duration,
+    )?;
 
     // Update state
-    pool_state.total_rewards_deposited = usdc_amount;
-    pool_state.total_rewards_claimed = 0;
+    let reward_end_time = (now as u64)
+        .checked_add(duration)
+        .ok_or(VaultError::MathError)?;
+    pool_state.total_rewards_deposited = pool_state
+        .total_rewards_deposited
+        .checked_add(usdc_amount)
+        .ok_or(VaultError::MathError)?;
     pool_state.tokens_per_interval = tokens_per_interval;
-    pool_state.last_distribution_time = Clock::get()?.unix_timestamp as u64;
+    pool_state.reward_remainder = reward_remainder;
+    pool_state.reward_start_time = now as u64;
+    pool_state.reward_end_time = reward_end_time;
+    pool_state.last_distribution_time = now as u64;
+
+    emit!(RewardsStarted {
+        admin: ctx.accounts.admin.key(),
+        pool: ctx.accounts.pool_state.key(),
+        usdc_amount,
+        rolled_over,
+        duration,
+        tokens_per_interval,
+        reward_end_time,
+        timestamp: now,
+    });
 
     Ok(())
 }
@@ -0,0 +1,102 @@
+use crate::{errors::VaultError, state::*, util::*, LpUnstaked};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+/// Remaining accounts: every reward stream, writable and in index order
+#[derive(Accounts)]
+pub struct UnstakeLp<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::WITHDRAWALS) @ VaultError::OperationPaused
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = user_state.owner == user.key() @ VaultError::Unauthorized
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint @ VaultError::InvalidTokenMint)]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = user_lp_token_account.owner == user.key() @ VaultError::InvalidTokenAccount,
+        constraint = user_lp_token_account.mint == lp_token_mint.key() @ VaultError::InvalidTokenMint
+    )]
+    pub user_lp_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump
+    )]
+    pub lp_stake_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Move staked LP back to the user's wallet, where it can be transferred but no
+/// longer earns rewards. Rewards earned up to now stay claimable.
+pub fn unstake_lp(ctx: Context<UnstakeLp>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return err!(VaultError::InvalidTokenAmount);
+    }
+
+    let pool_key = ctx.accounts.pool_state.key();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    let pool_id = pool_state.pool_id.to_le_bytes();
+    let user_state = &mut ctx.accounts.user_state;
+
+    require!(
+        user_state.lp_token_balance >= amount as u128,
+        VaultError::InsufficientLpBalance
+    );
+
+    // Settle rewards on the full stake before it shrinks
+    update_rewards(&mut pool_state, user_state)?;
+    update_all_stream_rewards(pool_key, &pool_state, user_state, ctx.remaining_accounts)?;
+
+    user_state.lp_token_balance -= amount as u128;
+    pool_state.total_staked_lp = pool_state
+        .total_staked_lp
+        .checked_sub(amount)
+        .ok_or(VaultError::MathError)?;
+
+    // Release the pool state before CPIs that sign with it
+    drop(pool_state);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.lp_stake_vault.to_account_info(),
+                to: ctx.accounts.user_lp_token_account.to_account_info(),
+                authority: ctx.accounts.pool_state.to_account_info(),
+            },
+        )
+        .with_signer(&[&[
+            b"pool_state".as_ref(),
+            pool_id.as_ref(),
+            &[ctx.bumps.pool_state],
+        ]]),
+        amount,
+    )?;
+
+    emit!(LpUnstaked {
+        user: ctx.accounts.user.key(),
+        pool: pool_key,
+        amount,
+        staked_balance: user_state.lp_token_balance as u64,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::{errors::VaultError, state::*, util::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+/// Read-only view of the pool. Results are returned through `set_return_data`,
+/// so they can be read with a simulated transaction.
+///
+/// Remaining accounts: [asset_config, oracle_program, oracle_feed] for every
+/// registered asset, in index order (see `compute_aum`)
+#[derive(Accounts)]
+pub struct PoolView<'info> {
+    #[account(seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()], bump)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint)]
+    pub lp_token_mint: Account<'info, Mint>,
+}
+
+/// Read-only preview of a deposit into `asset` by `user`, checked the way the
+/// deposit itself would be.
+///
+/// Remaining accounts: as for `PoolView`
+#[derive(Accounts)]
+pub struct PreviewDeposit<'info> {
+    #[account(seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()], bump)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Registry entry of the asset being deposited
+    #[account(
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+
+    #[account(constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint)]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    /// CHECK: Wallet the deposit is previewed for; only used to find its accounts
+    pub user: UncheckedAccount<'info>,
+
+    /// The user's state in the pool; omitted if they have never deposited
+    #[account(
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_state: Option<Account<'info, UserState>>,
+
+    /// The user's allowlist entry; only needed in permissioned mode without a proof
+    #[account(
+        seeds = [b"allowlist".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+}
+
+/// Read-only preview of a withdrawal from `asset` by `user`, checked the way the
+/// withdrawal itself would be.
+///
+/// Remaining accounts: as for `PoolView`
+#[derive(Accounts)]
+pub struct PreviewWithdraw<'info> {
+    #[account(seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()], bump)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Registry entry of the asset being withdrawn
+    #[account(
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+
+    #[account(constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint)]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    /// CHECK: Wallet the withdrawal is previewed for; only used to find its state
+    pub user: UncheckedAccount<'info>,
+
+    /// The user's state in the pool; omitted if they have never deposited, in
+    /// which case they have nothing to withdraw
+    #[account(
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_state: Option<Account<'info, UserState>>,
+}
+
+// AUM as withdrawals see it, with every asset's value rounded down
+pub fn get_aum(ctx: Context<PoolView>) -> Result<AumSnapshot> {
+    let pool_state = ctx.accounts.pool_state.load()?;
+    compute_aum(
+        ctx.accounts.pool_state.key(),
+        &pool_state,
+        ctx.remaining_accounts,
+        Rounding::Down,
+    )
+}
+
+pub fn lp_price(ctx: Context<PoolView>) -> Result<u64> {
+    let pool_state = ctx.accounts.pool_state.load()?;
+    let aum = compute_aum(
+        ctx.accounts.pool_state.key(),
+        &pool_state,
+        ctx.remaining_accounts,
+        Rounding::Down,
+    )?;
+    lp_token_price(aum.total_usd, ctx.accounts.lp_token_mint.supply)
+}
+
+pub fn preview_deposit(
+    ctx: Context<PreviewDeposit>,
+    token_amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<DepositQuote> {
+    if token_amount == 0 {
+        return err!(VaultError::InvalidTokenAmount);
+    }
+
+    let pool_state = ctx.accounts.pool_state.load()?;
+    let quote = quote_deposit(
+        ctx.accounts.pool_state.key(),
+        &pool_state,
+        &ctx.accounts.asset,
+        ctx.remaining_accounts,
+        ctx.accounts.lp_token_mint.supply,
+        token_amount,
+    )?;
+    pool_state.check_deposit(
+        &ctx.accounts.user.key(),
+        &proof,
+        ctx.accounts.allowlist_entry.is_some(),
+        ctx.accounts
+            .user_state
+            .as_ref()
+            .map_or(0, |user_state| user_state.lp_token_balance),
+        &quote,
+    )?;
+    Ok(quote)
+}
+
+pub fn preview_withdraw(
+    ctx: Context<PreviewWithdraw>,
+    lp_token_amount: u64,
+) -> Result<WithdrawalQuote> {
+    if lp_token_amount == 0 {
+        return err!(VaultError::InvalidTokenAmount);
+    }
+
+    let pool_state = ctx.accounts.pool_state.load()?;
+    pool_state.check_withdraw(
+        ctx.accounts
+            .user_state
+            .as_ref()
+            .map_or(0, |user_state| user_state.lp_token_balance),
+        lp_token_amount,
+    )?;
+    quote_withdrawal(
+        ctx.accounts.pool_state.key(),
+        &pool_state,
+        &ctx.accounts.asset,
+        ctx.remaining_accounts,
+        ctx.accounts.lp_token_mint.supply,
+        lp_token_amount,
+    )
+}
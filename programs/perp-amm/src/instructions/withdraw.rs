@@ -1,195 +1,209 @@
-use crate::{
-    errors::VaultError, state::*, util::*, CHAINLINK_PROGRAM_ID, DEVNET_SOL_PRICE_FEED,
-    MAINNET_SOL_PRICE_FEED, NATIVE_MINT,
-};
+use crate::{errors::VaultError, state::*, util::*, Withdrawn};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
-use chainlink_solana as chainlink;
 
+/// Remaining accounts: [asset_config, oracle_program, oracle_feed] for every
+/// registered asset, in index order (see `compute_aum`), followed by every
+/// reward stream, writable and in index order
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut, seeds = [b"pool_state".as_ref()], bump)]
-    pub pool_state: Account<'info, PoolState>,
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::WITHDRAWALS) @ VaultError::OperationPaused
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
 
     #[account(
         init_if_needed,
         payer = user,
-        space = 8 + UserState::INIT_SPACE,
-        seeds = [b"user_state".as_ref(), user.key().as_ref()],
+        space = UserState::LEN,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
         bump
     )]
     pub user_state: Account<'info, UserState>,
 
-    #[account(mut, constraint = lp_token_mint.key() == pool_state.lp_token_mint)]
+    #[account(mut, constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint)]
     pub lp_token_mint: Account<'info, Mint>,
 
+    /// Withdrawals burn the user's staked LP out of the stake vault
     #[account(
         mut,
-        constraint = user_lp_token_account.owner == user.key(),
-        constraint = user_lp_token_account.mint == lp_token_mint.key()
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump
     )]
-    pub user_lp_token_account: Account<'info, TokenAccount>,
+    pub lp_stake_vault: Account<'info, TokenAccount>,
 
+    /// Registry entry of the asset being withdrawn
     #[account(
         mut,
-        constraint = vault_account.key() == pool_state.sol_vault || vault_account.key() == pool_state.usdc_vault
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), asset.mint.as_ref()],
+        bump = asset.bump
     )]
-    pub vault_account: Account<'info, TokenAccount>,
-
-    // For SOL withdrawals, require this account; it is a temporary WSOL account
-    // that will be closed (unwrapped) returning native SOL to the user.
-    #[account(mut, constraint = user_token_account.mint == NATIVE_MINT.parse::<Pubkey>().unwrap() || user_token_account.mint == pool_state.usdc_mint)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub asset: Account<'info, AssetConfig>,
 
-    /// CHECK: Validated in constraint
-    #[account(address = CHAINLINK_PROGRAM_ID.parse::<Pubkey>().unwrap())]
-    pub chainlink_program: AccountInfo<'info>,
+    #[account(mut, address = asset.vault @ VaultError::InvalidTokenAccount)]
+    pub vault_account: Account<'info, TokenAccount>,
 
-    /// CHECK: Validated in constraint
+    // Token account receiving the withdrawn asset
     #[account(
-        address = if cfg!(feature = "devnet") {
-            DEVNET_SOL_PRICE_FEED
-        } else {
-            MAINNET_SOL_PRICE_FEED
-        }
-        .parse::<Pubkey>()
-        .unwrap()
+        mut,
+        constraint = user_token_account.mint == asset.mint @ VaultError::InvalidTokenMint
     )]
-    pub chainlink_feed: AccountInfo<'info>,
+    pub user_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
 
     pub system_program: Program<'info, System>,
 }
 
-pub fn withdraw(ctx: Context<Withdraw>, lp_token_amount: u64) -> Result<()> {
-    // --- Pre-burn & reward update logic remains unchanged ---
-    let pool_state_info = ctx.accounts.pool_state.to_account_info();
-    let pool_state_bump = ctx.bumps.pool_state;
-    let pool_state = &mut ctx.accounts.pool_state;
-    let user_state = &mut ctx.accounts.user_state;
+pub fn withdraw(ctx: Context<Withdraw>, lp_token_amount: u64, min_tokens_out: u64) -> Result<()> {
+    let withdrawal_amount = process_withdrawal(
+        WithdrawAccounts {
+            user: &ctx.accounts.user,
+            pool_state: &ctx.accounts.pool_state,
+            pool_state_bump: ctx.bumps.pool_state,
+            asset: &mut ctx.accounts.asset,
+            user_state: &mut ctx.accounts.user_state,
+            lp_token_mint: &ctx.accounts.lp_token_mint,
+            lp_stake_vault: &ctx.accounts.lp_stake_vault,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.remaining_accounts,
+        lp_token_amount,
+        min_tokens_out,
+    )?;
+
+    let pool_id = ctx.accounts.pool_state.load()?.pool_id.to_le_bytes();
+
+    // Transfer tokens from vault to user's token account
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.pool_state.to_account_info(),
+            },
+        )
+        .with_signer(&[&[
+            b"pool_state".as_ref(),
+            pool_id.as_ref(),
+            &[ctx.bumps.pool_state],
+        ]]),
+        withdrawal_amount,
+    )
+}
+
+/// Accounts `withdraw` and `withdraw_sol` share
+pub(crate) struct WithdrawAccounts<'a, 'info> {
+    pub user: &'a Signer<'info>,
+    pub pool_state: &'a AccountLoader<'info, PoolState>,
+    pub pool_state_bump: u8,
+    pub asset: &'a mut Account<'info, AssetConfig>,
+    pub user_state: &'a mut Account<'info, UserState>,
+    pub lp_token_mint: &'a Account<'info, Mint>,
+    pub lp_stake_vault: &'a Account<'info, TokenAccount>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+/// Everything a withdrawal does apart from paying it out of the vault, which
+/// `withdraw` and `withdraw_sol` each do their own way: settle the user's rewards,
+/// price the withdrawal, book it and burn the LP. Returns the amount to pay out.
+pub(crate) fn process_withdrawal<'info>(
+    accounts: WithdrawAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    lp_token_amount: u64,
+    min_tokens_out: u64,
+) -> Result<u64> {
+    let pool_key = accounts.pool_state.key();
+    let mut pool_state = accounts.pool_state.load_mut()?;
+    let pool_id = pool_state.pool_id.to_le_bytes();
+    let asset = accounts.asset;
+    let user_state = accounts.user_state;
 
     // Validate input token amount.
     if lp_token_amount == 0 {
         return err!(VaultError::InvalidTokenAmount);
     }
 
-    if user_state.lp_token_balance < lp_token_amount as u128 {
-        return err!(VaultError::InsufficientLpBalance);
-    }
+    pool_state.check_withdraw(user_state.lp_token_balance, lp_token_amount)?;
 
-    update_rewards(pool_state, user_state, &ctx.accounts.lp_token_mint)?;
+    let (aum_accounts, reward_stream_accounts) =
+        split_aum_accounts(&pool_state, remaining_accounts)?;
 
-    token::burn(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Burn {
-                mint: ctx.accounts.lp_token_mint.to_account_info(),
-                from: ctx.accounts.user_lp_token_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
-        lp_token_amount,
-    )?;
+    update_rewards(&mut pool_state, user_state)?;
+    update_all_stream_rewards(pool_key, &pool_state, user_state, reward_stream_accounts)?;
 
     user_state.lp_token_balance = user_state
         .lp_token_balance
         .checked_sub(lp_token_amount as u128)
         .ok_or(VaultError::MathError)?;
+    pool_state.total_staked_lp = pool_state
+        .total_staked_lp
+        .checked_sub(lp_token_amount)
+        .ok_or(VaultError::MathError)?;
 
-    let sol_vault = pool_state.sol_vault;
-    let usdc_vault = pool_state.usdc_vault;
-
-    let round = chainlink::latest_round_data(
-        ctx.accounts.chainlink_program.to_account_info(),
-        ctx.accounts.chainlink_feed.to_account_info(),
+    // Price the withdrawal against the LP supply before the burn below
+    let quote = quote_withdrawal(
+        pool_key,
+        &pool_state,
+        asset,
+        aum_accounts,
+        accounts.lp_token_mint.supply,
+        lp_token_amount,
     )?;
+    require!(
+        quote.withdrawal_amount >= min_tokens_out,
+        VaultError::SlippageExceeded
+    );
 
-    let sol_usd_price = round.answer;
+    // Release the pool state before CPIs that sign with it
+    drop(pool_state);
 
-    let total_sol_usd = get_sol_usd_value(pool_state.sol_deposited, sol_usd_price)?;
-
-    let current_aum = total_sol_usd
-        .checked_add(
-            pool_state
-                .usdc_deposited
-                .checked_mul(100)
-                .ok_or(VaultError::MathError)?,
-        )
-        .ok_or(VaultError::MathError)?;
-
-    let lp_supply = ctx.accounts.lp_token_mint.supply.max(1);
-    let withdrawal_usd_value: u128 = (lp_token_amount as u128)
-        .checked_mul(current_aum as u128)
-        .ok_or(VaultError::MathError)?
-        .checked_div(lp_supply as u128)
-        .ok_or(VaultError::MathError)?;
-
-    let token_amount = if ctx.accounts.vault_account.key() == sol_vault {
-        get_sol_amount_from_usd(withdrawal_usd_value as u64, sol_usd_price)?
-    } else if ctx.accounts.vault_account.key() == usdc_vault {
-        // Convert USD (8 decimals) to USDC (6 decimals)
-        (withdrawal_usd_value
-            .checked_div(100)
-            .ok_or(VaultError::MathError)?) as u64
-    } else {
-        return err!(VaultError::InvalidTokenMint);
-    };
-
-    let fee_amount = token_amount
-        .checked_mul(1)
-        .ok_or(VaultError::MathError)?
-        .checked_div(1000)
-        .ok_or(VaultError::MathError)?;
-    let withdrawal_amount = token_amount
-        .checked_sub(fee_amount)
+    asset.accumulated_fees = asset
+        .accumulated_fees
+        .checked_add(quote.fee_amount)
         .ok_or(VaultError::MathError)?;
+    asset.deposited = asset
+        .deposited
+        .checked_sub(quote.token_amount)
+        .ok_or(VaultError::InsufficientFunds)?;
 
-    // --- Fee update logic remains unchanged ---
-    if ctx.accounts.vault_account.key() == sol_vault {
-        pool_state.accumulated_sol_fees = pool_state
-            .accumulated_sol_fees
-            .checked_add(fee_amount)
-            .ok_or(VaultError::MathError)?;
-    } else {
-        pool_state.accumulated_usdc_fees = pool_state
-            .accumulated_usdc_fees
-            .checked_add(fee_amount)
-            .ok_or(VaultError::MathError)?;
-    }
-
-    // Create pool seeds for signing
-    let pool_seeds = &[b"pool_state".as_ref(), &[pool_state_bump]];
-
-    // Transfer tokens from vault to user's token account
-    token::transfer(
+    // Burn the withdrawn LP out of the stake vault
+    token::burn(
         CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_account.to_account_info(),
-                to: ctx.accounts.user_token_account.to_account_info(),
-                authority: pool_state_info,
+            accounts.token_program.to_account_info(),
+            Burn {
+                mint: accounts.lp_token_mint.to_account_info(),
+                from: accounts.lp_stake_vault.to_account_info(),
+                authority: accounts.pool_state.to_account_info(),
             },
         )
-        .with_signer(&[pool_seeds]),
-        withdrawal_amount,
+        .with_signer(&[&[
+            b"pool_state".as_ref(),
+            pool_id.as_ref(),
+            &[accounts.pool_state_bump],
+        ]]),
+        lp_token_amount,
     )?;
 
-    // --- Update pool deposit totals ---
-    if ctx.accounts.vault_account.key() == sol_vault {
-        pool_state.sol_deposited = pool_state
-            .sol_deposited
-            .checked_sub(token_amount)
-            .ok_or(VaultError::MathError)?;
-    } else if ctx.accounts.vault_account.key() == usdc_vault {
-        pool_state.usdc_deposited = pool_state
-            .usdc_deposited
-            .checked_sub(token_amount)
-            .ok_or(VaultError::MathError)?;
-    }
-
-    Ok(())
+    emit!(Withdrawn {
+        user: accounts.user.key(),
+        pool: pool_key,
+        mint: asset.mint,
+        lp_burned: lp_token_amount,
+        token_amount: quote.token_amount,
+        fee_amount: quote.fee_amount,
+        amount_out: quote.withdrawal_amount,
+        asset_price: quote.asset_price,
+        aum_usd: quote.aum_usd,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(quote.withdrawal_amount)
 }
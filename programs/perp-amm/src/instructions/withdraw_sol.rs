@@ -0,0 +1,131 @@
+use super::withdraw::{process_withdrawal, WithdrawAccounts};
+use crate::{errors::VaultError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    self, spl_token::native_mint, CloseAccount, Mint, Token, TokenAccount, Transfer,
+};
+
+/// Withdraw from the SOL asset as native SOL. The wrapped SOL is moved into a
+/// temporary token account which is closed straight back to the user, returning
+/// the withdrawal and the account's rent as lamports.
+///
+/// Remaining accounts: [asset_config, oracle_program, oracle_feed] for every
+/// registered asset, in index order (see `compute_aum`), followed by every
+/// reward stream, writable and in index order
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state".as_ref(), &pool_state.load()?.pool_id.to_le_bytes()],
+        bump,
+        constraint = !pool_state.load()?.is_paused(pause_flags::WITHDRAWALS) @ VaultError::OperationPaused
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [b"user_state".as_ref(), pool_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(mut, constraint = lp_token_mint.key() == pool_state.load()?.lp_token_mint)]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    /// Withdrawals burn the user's staked LP out of the stake vault
+    #[account(
+        mut,
+        seeds = [b"lp_stake_vault".as_ref(), pool_state.key().as_ref(), lp_token_mint.key().as_ref()],
+        bump
+    )]
+    pub lp_stake_vault: Account<'info, TokenAccount>,
+
+    /// Registry entry of wrapped SOL
+    #[account(
+        mut,
+        seeds = [b"asset".as_ref(), pool_state.key().as_ref(), native_mint::ID.as_ref()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, AssetConfig>,
+
+    #[account(mut, address = asset.vault @ VaultError::InvalidTokenAccount)]
+    pub vault_account: Account<'info, TokenAccount>,
+
+    #[account(address = native_mint::ID @ VaultError::InvalidTokenMint)]
+    pub native_mint: Account<'info, Mint>,
+
+    /// Temporary wrapped SOL account, closed to the user before the instruction ends
+    #[account(
+        init,
+        payer = user,
+        token::mint = native_mint,
+        token::authority = pool_state,
+        seeds = [b"sol_unwrap".as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub unwrap_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn withdraw_sol(
+    ctx: Context<WithdrawSol>,
+    lp_token_amount: u64,
+    min_tokens_out: u64,
+) -> Result<()> {
+    let withdrawal_amount = process_withdrawal(
+        WithdrawAccounts {
+            user: &ctx.accounts.user,
+            pool_state: &ctx.accounts.pool_state,
+            pool_state_bump: ctx.bumps.pool_state,
+            asset: &mut ctx.accounts.asset,
+            user_state: &mut ctx.accounts.user_state,
+            lp_token_mint: &ctx.accounts.lp_token_mint,
+            lp_stake_vault: &ctx.accounts.lp_stake_vault,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.remaining_accounts,
+        lp_token_amount,
+        min_tokens_out,
+    )?;
+
+    let pool_state_info = ctx.accounts.pool_state.to_account_info();
+    let pool_id = ctx.accounts.pool_state.load()?.pool_id.to_le_bytes();
+    let pool_seeds = &[
+        b"pool_state".as_ref(),
+        pool_id.as_ref(),
+        &[ctx.bumps.pool_state],
+    ];
+
+    // Move the wrapped SOL into the temporary account...
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_account.to_account_info(),
+                to: ctx.accounts.unwrap_account.to_account_info(),
+                authority: pool_state_info.clone(),
+            },
+        )
+        .with_signer(&[pool_seeds]),
+        withdrawal_amount,
+    )?;
+
+    // ...and close it to the user, unwrapping it into lamports
+    token::close_account(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.unwrap_account.to_account_info(),
+                destination: ctx.accounts.user.to_account_info(),
+                authority: pool_state_info,
+            },
+        )
+        .with_signer(&[pool_seeds]),
+    )
+}
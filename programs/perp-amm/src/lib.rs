@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
 
 use instructions::*;
+use oracle::OracleKind;
+use util::{AumSnapshot, DepositQuote, WithdrawalQuote};
 
 pub mod errors;
 pub mod instructions;
+pub mod oracle;
 pub mod state;
 pub mod util;
 
@@ -12,13 +15,6 @@ declare_id!("55CV34QX5ZtPpQ5CJQBeCjHwWgAVQrdiHJXuPmsvdV5v");
 
 pub const NATIVE_MINT: &str = "So11111111111111111111111111111111111111112";
 
-// Chainlink Program ID (same on all networks)
-pub const CHAINLINK_PROGRAM_ID: &str = "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny";
-
-// SOL/USD Price Feed Addresses
-pub const MAINNET_SOL_PRICE_FEED: &str = "CH31Xns5z3M1cTAbKW34jcxPPciazARpijcHj9rxtemt";
-pub const DEVNET_SOL_PRICE_FEED: &str = "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR";
-
 #[event]
 pub struct RewardsClaimed {
     pub user: Pubkey,
@@ -27,18 +23,352 @@ pub struct RewardsClaimed {
     pub total_claimed: u64,
 }
 
+#[event]
+pub struct RewardsCompounded {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub usdc_amount: u64,
+    pub fee_amount: u64,
+    pub lp_minted: u64,
+    pub aum_usd: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssetAdded {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub index: u8,
+    pub vault: Pubkey,
+    pub is_stable: bool,
+    pub oracle_kind: OracleKind,
+    pub oracle_feed: Pubkey,
+    pub target_weight_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub oracle_kind: OracleKind,
+    pub oracle_feed: Pubkey,
+    pub max_price_age: u64,
+    pub max_confidence_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TargetWeightUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub target_weight_bps: u16,
+    pub total_target_weight_bps: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DynamicFeeTaxUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub dynamic_fee_tax_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Deposited {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub token_amount: u64,
+    pub fee_amount: u64,
+    pub lp_minted: u64,
+    pub asset_price: u128,
+    pub aum_usd: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub lp_burned: u64,
+    pub token_amount: u64,
+    pub fee_amount: u64,
+    pub amount_out: u64,
+    pub asset_price: u128,
+    pub aum_usd: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminWithdrawn {
+    pub caller: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DirectDeposited {
+    pub depositor: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsStarted {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub usdc_amount: u64,
+    pub rolled_over: u64,
+    pub duration: u64,
+    pub tokens_per_interval: u64,
+    pub reward_end_time: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesClaimed {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub pool: Pubkey,
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OutflowLimitsUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub window_secs: u64,
+    pub max_outflow_bps: u16,
+    pub max_caller_outflow_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositLimitsUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub max_aum_usd: u64,
+    pub max_user_lp: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PegBandUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub peg_band_bps: u16,
+    pub depeg_pauses_deposits: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllowlistRootUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub root: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PermissionedModeUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllowlistEntryAdded {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllowlistEntryRemoved {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssetReconciled {
+    pub caller: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    /// Tokens held by the asset vault
+    pub vault_balance: u64,
+    /// `deposited` plus `accumulated_fees` before any sweep
+    pub booked: u64,
+    pub surplus: u64,
+    pub deficit: u64,
+    pub action: ReconcileAction,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationsPaused {
+    pub caller: Pubkey,
+    pub pool: Pubkey,
+    /// Operations paused by this call, see `state::pause_flags`
+    pub operations: u8,
+    /// Every operation paused afterwards
+    pub paused: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationsUnpaused {
+    pub caller: Pubkey,
+    pub pool: Pubkey,
+    /// Operations resumed by this call, see `state::pause_flags`
+    pub operations: u8,
+    /// Every operation still paused afterwards
+    pub paused: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityAdded {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub roles: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityRolesUpdated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub roles: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityRemoved {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardStreamAdded {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub index: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardStreamFunded {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub rolled_over: u64,
+    pub duration: u64,
+    pub tokens_per_interval: u64,
+    pub reward_end_time: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamRewardsClaimed {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpStaked {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub staked_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpUnstaked {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub staked_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolStateMigrated {
+    pub admin: Pubkey,
+    pub pool: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
 /// The main vault program.
 /// It includes instructions for initialize, deposit, withdraw, admin deposit/withdraw, etc.
 #[program]
 pub mod perp_amm {
     use super::*;
 
-    /// Initialize the liquidity pool
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        instructions::initialize::initialize(ctx)
+    /// Initialize a liquidity pool, addressed by `pool_id`
+    pub fn initialize(ctx: Context<Initialize>, pool_id: u64) -> Result<()> {
+        instructions::initialize::initialize(ctx, pool_id)
+    }
+
+    /// Admin function to whitelist a new asset
+    pub fn add_asset(ctx: Context<AddAsset>, params: AddAssetParams) -> Result<()> {
+        instructions::add_asset::add_asset(ctx, params)
     }
 
-    /// Initialize a token vault (sol_vault, usdc_vault, or usdc_reward_vault)
+    /// Initialize the usdc_reward_vault token account
     pub fn initialize_token_vault(ctx: Context<InitializeTokenVault>, seed: Vec<u8>) -> Result<()> {
         instructions::initialize::initialize_token_vault(ctx, &seed)
     }
@@ -48,6 +378,11 @@ pub mod perp_amm {
         instructions::initialize::initialize_lp_mint(ctx)
     }
 
+    /// Initialize the vault staked LP tokens are held in (admin only)
+    pub fn initialize_lp_stake_vault(ctx: Context<InitializeLpStakeVault>) -> Result<()> {
+        instructions::initialize::initialize_lp_stake_vault(ctx)
+    }
+
     /// Close the pool (admin only)
     pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
         instructions::close_pool::close_pool(ctx)
@@ -58,14 +393,42 @@ pub mod perp_amm {
         instructions::close_user_state::close_user_state(ctx)
     }
 
-    /// Deposit SOL or USDC into the pool
-    pub fn deposit(ctx: Context<Deposit>, token_amount: u64) -> Result<()> {
-        instructions::deposit::deposit(ctx, token_amount)
+    /// Deposit a whitelisted asset into the pool, minting at least `min_lp_out` LP tokens
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        token_amount: u64,
+        min_lp_out: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::deposit::deposit(ctx, token_amount, min_lp_out, proof)
     }
 
-    /// Withdraw tokens from the pool
-    pub fn withdraw(ctx: Context<Withdraw>, lp_token_amount: u64) -> Result<()> {
-        instructions::withdraw::withdraw(ctx, lp_token_amount)
+    /// Deposit native SOL, wrapped inside the program
+    pub fn deposit_sol(
+        ctx: Context<DepositSol>,
+        lamports: u64,
+        min_lp_out: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::deposit_sol::deposit_sol(ctx, lamports, min_lp_out, proof)
+    }
+
+    /// Withdraw tokens from the pool, paying out at least `min_tokens_out` after fees
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        lp_token_amount: u64,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        instructions::withdraw::withdraw(ctx, lp_token_amount, min_tokens_out)
+    }
+
+    /// Withdraw from the SOL asset as native SOL, unwrapped inside the program
+    pub fn withdraw_sol(
+        ctx: Context<WithdrawSol>,
+        lp_token_amount: u64,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        instructions::withdraw_sol::withdraw_sol(ctx, lp_token_amount, min_tokens_out)
     }
 
     /// Admin function to withdraw tokens (market making losses)
@@ -78,9 +441,13 @@ pub mod perp_amm {
         instructions::direct_deposit::direct_deposit(ctx, amount)
     }
 
-    /// Admin function to start new reward distribution
-    pub fn start_rewards(ctx: Context<StartRewards>, usdc_amount: u64) -> Result<()> {
-        instructions::start_rewards::start_rewards(ctx, usdc_amount)
+    /// Admin function to start a reward period of `duration` seconds, or top up the running one
+    pub fn start_rewards(
+        ctx: Context<StartRewards>,
+        usdc_amount: u64,
+        duration: u64,
+    ) -> Result<()> {
+        instructions::start_rewards::start_rewards(ctx, usdc_amount, duration)
     }
 
     /// Claim user rewards
@@ -88,6 +455,45 @@ pub mod perp_amm {
         instructions::claim_rewards::claim_rewards(ctx)
     }
 
+    /// Reinvest pending USDC rewards into the pool as staked LP, minting at least `min_lp_out`.
+    /// `proof` and the optional allowlist entry work as in `deposit`
+    pub fn compound_rewards(
+        ctx: Context<CompoundRewards>,
+        min_lp_out: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::compound_rewards::compound_rewards(ctx, min_lp_out, proof)
+    }
+
+    /// Admin function to register an additional reward token for LPs
+    pub fn add_reward_stream(ctx: Context<AddRewardStream>) -> Result<()> {
+        instructions::add_reward_stream::add_reward_stream(ctx)
+    }
+
+    /// Admin function to fund a reward stream for `duration` seconds, or top up its running period
+    pub fn fund_reward_stream(
+        ctx: Context<FundRewardStream>,
+        amount: u64,
+        duration: u64,
+    ) -> Result<()> {
+        instructions::fund_reward_stream::fund_reward_stream(ctx, amount, duration)
+    }
+
+    /// Claim user rewards from one or more reward streams
+    pub fn claim_stream_rewards(ctx: Context<ClaimStreamRewards>) -> Result<()> {
+        instructions::claim_stream_rewards::claim_stream_rewards(ctx)
+    }
+
+    /// Stake LP tokens from the user's wallet so they earn rewards
+    pub fn stake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+        instructions::stake_lp::stake_lp(ctx, amount)
+    }
+
+    /// Unstake LP tokens back to the user's wallet, where they stop earning rewards
+    pub fn unstake_lp(ctx: Context<UnstakeLp>, amount: u64) -> Result<()> {
+        instructions::unstake_lp::unstake_lp(ctx, amount)
+    }
+
     pub fn force_close_user_state(ctx: Context<ForceCloseUserState>) -> Result<()> {
         instructions::force_close_user_state::force_close_user_state(ctx)
     }
@@ -97,8 +503,32 @@ pub mod perp_amm {
         instructions::claim_fees::claim_fees(ctx)
     }
 
-    pub fn add_authority(ctx: Context<AddAuthority>, new_authority: Pubkey) -> Result<()> {
-        instructions::add_authority::add_authority(ctx, new_authority)
+    /// Admin function to nominate a new admin; takes effect on `accept_admin`
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin::propose_admin(ctx, new_admin)
+    }
+
+    /// Called by the proposed admin to take over the pool
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin::accept_admin(ctx)
+    }
+
+    /// Admin function to register an authority with a bitmask of `state::roles`
+    pub fn add_authority(
+        ctx: Context<AddAuthority>,
+        new_authority: Pubkey,
+        roles: u8,
+    ) -> Result<()> {
+        instructions::add_authority::add_authority(ctx, new_authority, roles)
+    }
+
+    /// Admin function to replace the roles of an existing authority
+    pub fn set_authority_roles(
+        ctx: Context<SetAuthorityRoles>,
+        authority: Pubkey,
+        roles: u8,
+    ) -> Result<()> {
+        instructions::set_authority_roles::set_authority_roles(ctx, authority, roles)
     }
 
     pub fn remove_authority(
@@ -107,4 +537,167 @@ pub mod perp_amm {
     ) -> Result<()> {
         instructions::remove_authority::remove_authority(ctx, authority_to_remove)
     }
+
+    /// Pauser function to stop a set of operations (`state::pause_flags`)
+    pub fn pause(ctx: Context<SetPaused>, operations: u8) -> Result<()> {
+        instructions::pause::pause(ctx, operations)
+    }
+
+    /// Pauser function to resume a set of operations (`state::pause_flags`)
+    pub fn unpause(ctx: Context<SetPaused>, operations: u8) -> Result<()> {
+        instructions::pause::unpause(ctx, operations)
+    }
+
+    /// Admin function to cap how much `admin_withdraw` can move out per window
+    pub fn set_outflow_limits(
+        ctx: Context<SetOutflowLimits>,
+        window_secs: u64,
+        max_outflow_bps: u16,
+        max_caller_outflow_bps: u16,
+    ) -> Result<()> {
+        instructions::set_outflow_limits::set_outflow_limits(
+            ctx,
+            window_secs,
+            max_outflow_bps,
+            max_caller_outflow_bps,
+        )
+    }
+
+    /// Admin function to cap the pool's AUM and each wallet's LP position
+    pub fn set_deposit_limits(
+        ctx: Context<SetDepositLimits>,
+        max_aum_usd: u64,
+        max_user_lp: u64,
+    ) -> Result<()> {
+        instructions::set_deposit_limits::set_deposit_limits(ctx, max_aum_usd, max_user_lp)
+    }
+
+    /// Admin function to rotate the merkle root of wallets allowed to deposit
+    pub fn set_allowlist_root(ctx: Context<SetAllowlist>, root: [u8; 32]) -> Result<()> {
+        instructions::allowlist::set_allowlist_root(ctx, root)
+    }
+
+    /// Admin function to restrict deposits to allowlisted wallets, or lift the restriction
+    pub fn set_permissioned(ctx: Context<SetAllowlist>, enabled: bool) -> Result<()> {
+        instructions::allowlist::set_permissioned(ctx, enabled)
+    }
+
+    /// Admin function to allowlist a single wallet
+    pub fn add_allowlist_entry(ctx: Context<AddAllowlistEntry>, wallet: Pubkey) -> Result<()> {
+        instructions::allowlist::add_allowlist_entry(ctx, wallet)
+    }
+
+    /// Admin function to remove a wallet's allowlist entry
+    pub fn remove_allowlist_entry(ctx: Context<RemoveAllowlistEntry>) -> Result<()> {
+        instructions::allowlist::remove_allowlist_entry(ctx)
+    }
+
+    /// Compare an asset vault with the pool's books, via an event and return data.
+    /// The admin can also sweep a surplus to LPs or flag a deficit
+    pub fn reconcile(ctx: Context<Reconcile>, action: ReconcileAction) -> Result<Reconciliation> {
+        instructions::reconcile::reconcile(ctx, action)
+    }
+
+    /// Admin function to point an asset at a different oracle feed
+    pub fn set_oracle(
+        ctx: Context<SetOracle>,
+        oracle_kind: OracleKind,
+        oracle_feed_id: [u8; 32],
+        max_price_age: u64,
+        max_confidence_bps: u16,
+    ) -> Result<()> {
+        instructions::set_oracle::set_oracle(
+            ctx,
+            oracle_kind,
+            oracle_feed_id,
+            max_price_age,
+            max_confidence_bps,
+        )
+    }
+
+    /// Admin function to set how far stablecoins may drift from 1 USD before
+    /// they are priced from their oracle, and whether a depeg pauses deposits
+    pub fn set_peg_band(
+        ctx: Context<SetPegBand>,
+        peg_band_bps: u16,
+        depeg_pauses_deposits: bool,
+    ) -> Result<()> {
+        instructions::set_peg_band::set_peg_band(ctx, peg_band_bps, depeg_pauses_deposits)
+    }
+
+    /// Admin function to update an asset's deposit and withdrawal fee rates (basis points)
+    pub fn set_fees(
+        ctx: Context<SetFees>,
+        deposit_fee_bps: u16,
+        withdraw_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::set_fees::set_fees(ctx, deposit_fee_bps, withdraw_fee_bps)
+    }
+
+    /// Admin function to set an asset's target weight
+    pub fn set_target_weight(ctx: Context<SetTargetWeight>, target_weight_bps: u16) -> Result<()> {
+        instructions::set_target_weight::set_target_weight(ctx, target_weight_bps)
+    }
+
+    /// Admin function to set the dynamic fee rebate/surcharge cap (basis points)
+    pub fn set_dynamic_fee_tax(
+        ctx: Context<SetDynamicFeeTax>,
+        dynamic_fee_tax_bps: u16,
+    ) -> Result<()> {
+        instructions::set_target_weight::set_dynamic_fee_tax(ctx, dynamic_fee_tax_bps)
+    }
+
+    /// Admin function to upgrade the pool state to the current layout version
+    pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+        instructions::migrate_pool_state::migrate_pool_state(ctx)
+    }
+
+    /// Admin function to move the pool first deployed at `["pool_state"]` to `pool_id`
+    pub fn migrate_legacy_pool(
+        ctx: Context<MigrateLegacyPool>,
+        pool_id: u64,
+        sol_params: AddAssetParams,
+        usdc_params: AddAssetParams,
+    ) -> Result<()> {
+        instructions::migrate_legacy_pool::migrate_legacy_pool(
+            ctx,
+            pool_id,
+            sol_params,
+            usdc_params,
+        )
+    }
+
+    /// Move a legacy pool user state to its pool-keyed address (anyone may pay for it)
+    pub fn migrate_legacy_user_state(ctx: Context<MigrateLegacyUserState>) -> Result<()> {
+        instructions::migrate_legacy_user_state::migrate_legacy_user_state(ctx)
+    }
+
+    /// View: current AUM and per-asset prices and values, via return data
+    pub fn get_aum(ctx: Context<PoolView>) -> Result<AumSnapshot> {
+        instructions::views::get_aum(ctx)
+    }
+
+    /// View: USD value (8 decimals) of one whole LP token, via return data
+    pub fn lp_price(ctx: Context<PoolView>) -> Result<u64> {
+        instructions::views::lp_price(ctx)
+    }
+
+    /// View: fee, net deposit and LP minted for `user` depositing `token_amount`,
+    /// via return data. Fails whenever the deposit itself would be rejected.
+    pub fn preview_deposit(
+        ctx: Context<PreviewDeposit>,
+        token_amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<DepositQuote> {
+        instructions::views::preview_deposit(ctx, token_amount, proof)
+    }
+
+    /// View: tokens, fee and payout for `user` burning `lp_token_amount`, via
+    /// return data. Fails whenever the withdrawal itself would be rejected.
+    pub fn preview_withdraw(
+        ctx: Context<PreviewWithdraw>,
+        lp_token_amount: u64,
+    ) -> Result<WithdrawalQuote> {
+        instructions::views::preview_withdraw(ctx, lp_token_amount)
+    }
 }
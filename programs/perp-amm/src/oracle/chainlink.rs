@@ -0,0 +1,24 @@
+use super::{scale_to_price_decimals, OraclePrice, PriceOracle};
+use anchor_lang::prelude::*;
+use chainlink_solana as chainlink;
+
+/// Chainlink OCR2 feeds, read through a CPI into the Chainlink store program
+pub struct ChainlinkOracle;
+
+impl PriceOracle for ChainlinkOracle {
+    fn read_price<'info>(
+        oracle_program: &AccountInfo<'info>,
+        feed: &AccountInfo<'info>,
+        _feed_id: &[u8; 32],
+    ) -> Result<OraclePrice> {
+        let round = chainlink::latest_round_data(oracle_program.clone(), feed.clone())?;
+        let decimals = chainlink::decimals(oracle_program.clone(), feed.clone())?;
+
+        Ok(OraclePrice {
+            price: scale_to_price_decimals(round.answer, -(decimals as i32))?,
+            // Chainlink doesn't publish a confidence interval
+            confidence: 0,
+            publish_time: round.timestamp as i64,
+        })
+    }
+}
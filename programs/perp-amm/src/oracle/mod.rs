@@ -0,0 +1,122 @@
+pub mod chainlink;
+pub mod pyth;
+pub mod switchboard;
+
+pub use chainlink::*;
+pub use pyth::*;
+pub use switchboard::*;
+
+use crate::errors::VaultError;
+use anchor_lang::prelude::*;
+
+/// All oracle prices are normalized to 8 decimals (1 USD = 100_000_000),
+/// matching the USD precision used by the AUM math.
+pub const PRICE_DECIMALS: i32 = 8;
+
+/// Which oracle network a price feed belongs to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum OracleKind {
+    Chainlink,
+    Pyth,
+    Switchboard,
+}
+
+/// A price read from an oracle, normalized to `PRICE_DECIMALS`
+#[derive(Clone, Copy, Debug)]
+pub struct OraclePrice {
+    /// Price with 8 decimals
+    pub price: i128,
+    /// Confidence interval with 8 decimals (0 if the oracle doesn't publish one)
+    pub confidence: u128,
+    /// Unix timestamp at which the price was published
+    pub publish_time: i64,
+}
+
+/// Reasons a price read from an oracle can be rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceRejection {
+    /// The price was published more than `max_age` seconds ago
+    Stale,
+    /// The price is zero or negative
+    NonPositive,
+    /// The confidence interval is too wide relative to the price
+    ConfidenceTooWide,
+}
+
+impl OraclePrice {
+    /// Check that the price is fresh, positive and has a tight enough confidence interval.
+    /// A `max_confidence_bps` of 0 disables the confidence check.
+    /// Returns the price (8 decimals) as an unsigned value.
+    pub fn validate(
+        &self,
+        now: i64,
+        max_age: u64,
+        max_confidence_bps: u16,
+    ) -> std::result::Result<u128, PriceRejection> {
+        // Publish times slightly ahead of the validator clock count as fresh
+        let age = now.saturating_sub(self.publish_time).max(0) as u64;
+        if age > max_age {
+            return Err(PriceRejection::Stale);
+        }
+
+        if self.price <= 0 {
+            return Err(PriceRejection::NonPositive);
+        }
+        let price = self.price as u128;
+
+        if max_confidence_bps > 0 {
+            let max_confidence = price.saturating_mul(max_confidence_bps as u128) / 10_000;
+            if self.confidence > max_confidence {
+                return Err(PriceRejection::ConfidenceTooWide);
+            }
+        }
+
+        Ok(price)
+    }
+}
+
+/// Common interface implemented by every oracle adapter
+pub trait PriceOracle {
+    /// Read the latest price from `feed`, which must belong to `oracle_program`.
+    /// Adapters whose feed accounts can hold any asset's price also check that
+    /// it is for `feed_id`; the others ignore it.
+    fn read_price<'info>(
+        oracle_program: &AccountInfo<'info>,
+        feed: &AccountInfo<'info>,
+        feed_id: &[u8; 32],
+    ) -> Result<OraclePrice>;
+}
+
+/// Read the latest price from the adapter matching `kind`
+pub fn read_price<'info>(
+    kind: OracleKind,
+    oracle_program: &AccountInfo<'info>,
+    feed: &AccountInfo<'info>,
+    feed_id: &[u8; 32],
+) -> Result<OraclePrice> {
+    match kind {
+        OracleKind::Chainlink => ChainlinkOracle::read_price(oracle_program, feed, feed_id),
+        OracleKind::Pyth => PythOracle::read_price(oracle_program, feed, feed_id),
+        OracleKind::Switchboard => SwitchboardOracle::read_price(oracle_program, feed, feed_id),
+    }
+}
+
+/// Rescale a fixed-point `value * 10^exponent` to `PRICE_DECIMALS`
+pub fn scale_to_price_decimals(value: i128, exponent: i32) -> Result<i128> {
+    let shift = exponent
+        .checked_add(PRICE_DECIMALS)
+        .ok_or(VaultError::MathError)?;
+
+    let factor = 10i128
+        .checked_pow(shift.unsigned_abs())
+        .ok_or(VaultError::MathError)?;
+
+    let scaled = if shift >= 0 {
+        value.checked_mul(factor)
+    } else {
+        value.checked_div(factor)
+    }
+    .ok_or(VaultError::MathError)?;
+
+    Ok(scaled)
+}
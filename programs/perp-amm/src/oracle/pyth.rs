@@ -0,0 +1,40 @@
+use super::{scale_to_price_decimals, OraclePrice, PriceOracle};
+use crate::errors::VaultError;
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+
+/// Pyth pull-oracle feeds, read from a `PriceUpdateV2` account owned by the Pyth receiver.
+/// Anyone can post an update account for any feed, so only fully verified updates
+/// for the configured feed id are accepted.
+pub struct PythOracle;
+
+impl PriceOracle for PythOracle {
+    fn read_price<'info>(
+        oracle_program: &AccountInfo<'info>,
+        feed: &AccountInfo<'info>,
+        feed_id: &[u8; 32],
+    ) -> Result<OraclePrice> {
+        require_keys_eq!(
+            *feed.owner,
+            oracle_program.key(),
+            VaultError::InvalidOracleFeed
+        );
+
+        let data = feed.try_borrow_data()?;
+        let price_update = PriceUpdateV2::try_deserialize(&mut &data[..])?;
+        require!(
+            matches!(price_update.verification_level, VerificationLevel::Full),
+            VaultError::UnverifiedOraclePrice
+        );
+
+        let message = price_update.price_message;
+        require!(message.feed_id == *feed_id, VaultError::InvalidOracleFeed);
+
+        Ok(OraclePrice {
+            price: scale_to_price_decimals(message.price as i128, message.exponent)?,
+            confidence: scale_to_price_decimals(message.conf as i128, message.exponent)?
+                .unsigned_abs(),
+            publish_time: message.publish_time,
+        })
+    }
+}
@@ -0,0 +1,33 @@
+use super::{scale_to_price_decimals, OraclePrice, PriceOracle};
+use crate::errors::VaultError;
+use anchor_lang::prelude::*;
+use switchboard_on_demand::{PullFeedAccountData, PRECISION};
+
+/// Switchboard on-demand pull feeds
+pub struct SwitchboardOracle;
+
+impl PriceOracle for SwitchboardOracle {
+    fn read_price<'info>(
+        oracle_program: &AccountInfo<'info>,
+        feed: &AccountInfo<'info>,
+        _feed_id: &[u8; 32],
+    ) -> Result<OraclePrice> {
+        require_keys_eq!(
+            *feed.owner,
+            oracle_program.key(),
+            VaultError::InvalidOracleFeed
+        );
+
+        let feed_data = PullFeedAccountData::parse(feed.data.borrow())
+            .map_err(|_| error!(VaultError::InvalidOracleFeed))?;
+
+        // Switchboard results are fixed-point with 18 decimals
+        let exponent = -(PRECISION as i32);
+
+        Ok(OraclePrice {
+            price: scale_to_price_decimals(feed_data.result.value, exponent)?,
+            confidence: scale_to_price_decimals(feed_data.result.std_dev, exponent)?.unsigned_abs(),
+            publish_time: feed_data.last_update_timestamp,
+        })
+    }
+}
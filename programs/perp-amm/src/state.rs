@@ -1,73 +1,137 @@
+use crate::errors::VaultError;
+use crate::oracle::{self, OracleKind, PRICE_DECIMALS};
+use crate::util::allowlist::is_allowlisted;
+use crate::util::liquidity::DepositQuote;
+use crate::util::math::{mul_div, Rounding};
 use anchor_lang::prelude::*;
 
 // -----------------------------------------------
-// Context structs for Chainlink usage
+// Data structures for the pool
 // -----------------------------------------------
 
-#[derive(Accounts)]
-pub struct UpdateSolUsdPrice<'info> {
-    #[account(mut)]
-    pub pool_state: Account<'info, PoolState>,
+// Maximum number of authorities allowed
+pub const MAX_AUTHORITIES: usize = 10;
 
-    /// CHECK: This is the Chainlink program's address
-    pub chainlink_program: AccountInfo<'info>,
+// Roles an authority can hold, combined into a bitmask per authority.
+// The pool admin holds every role.
+pub mod roles {
+    /// Move funds out of the pool to settle positive PnL (`admin_withdraw`, `execute_withdrawal`)
+    pub const SETTLEMENT: u8 = 1 << 0;
+    /// Liquidate margin accounts
+    pub const LIQUIDATOR: u8 = 1 << 1;
+    /// Claim accumulated fees
+    pub const FEE_COLLECTOR: u8 = 1 << 2;
+    /// Start and fund rewards and reward streams
+    pub const REWARDS_MANAGER: u8 = 1 << 3;
+    /// Pause and unpause operations, see `pause_flags`
+    pub const PAUSER: u8 = 1 << 4;
+
+    pub const ALL: u8 = SETTLEMENT | LIQUIDATOR | FEE_COLLECTOR | REWARDS_MANAGER | PAUSER;
+}
 
-    /// CHECK: This is the Chainlink feed account
-    pub chainlink_feed: AccountInfo<'info>,
+// Operations that can be paused, combined into a bitmask.
+// `PoolState` and `MarginVault` each keep their own set of paused operations;
+// a flag that none of a program's instructions check has no effect there.
+pub mod pause_flags {
+    /// LP deposits and margin deposits
+    pub const DEPOSITS: u8 = 1 << 0;
+    /// LP withdrawals and margin withdrawal requests
+    pub const WITHDRAWALS: u8 = 1 << 1;
+    /// Paying out or collecting PnL (`admin_withdraw`, `execute_withdrawal`)
+    pub const SETTLEMENTS: u8 = 1 << 2;
+    /// Liquidating margin accounts (margin program only)
+    pub const LIQUIDATIONS: u8 = 1 << 3;
+    /// Claiming and compounding rewards (pool only)
+    pub const REWARD_CLAIMS: u8 = 1 << 4;
+
+    pub const ALL: u8 = DEPOSITS | WITHDRAWALS | SETTLEMENTS | LIQUIDATIONS | REWARD_CLAIMS;
 }
 
-// -----------------------------------------------
-// Data structures for the pool
-// -----------------------------------------------
+// Callers `OutflowLimits` tracks per window: every authority plus the admin
+pub const MAX_OUTFLOW_CALLERS: usize = MAX_AUTHORITIES + 1;
 
-// Maximum number of authorities allowed
-pub const MAX_AUTHORITIES: usize = 10;
+// Maximum number of whitelisted assets a pool can hold
+pub const MAX_ASSETS: u8 = 8;
+
+// Maximum number of reward streams a pool can run alongside its USDC rewards
+pub const MAX_REWARD_STREAMS: usize = 4;
+
+// Fee rates are expressed in basis points (1 bps = 0.01%)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Upper bound on any deposit/withdrawal fee (1%)
+pub const MAX_FEE_BPS: u16 = 100;
+
+// Upper bound on the dynamic fee rebate/surcharge (1%)
+pub const MAX_DYNAMIC_FEE_TAX_BPS: u16 = 100;
+
+// One USD at oracle precision; the price of a stablecoin while it holds its peg
+pub const ONE_USD: u128 = 10u128.pow(PRICE_DECIMALS as u32);
+
+// Widest peg band stablecoins can be given (10%)
+pub const MAX_PEG_BAND_BPS: u16 = 1_000;
+
+// Virtual LP supply and AUM added to the pool whenever LP tokens are priced.
+// Together they set the first-mint rate (10 LP units per USD unit, as LP uses 9
+// decimals and USD 8) and soak up most of any value donated to a near-empty pool,
+// which makes inflating the LP price against the next depositor unprofitable.
+pub const VIRTUAL_LP_SUPPLY: u128 = 1_000_000_000; // 1 LP
+pub const VIRTUAL_AUM_USD: u128 = 100_000_000; // $1
+
+// Longest reward period `start_rewards` and `fund_reward_stream` accept (one year)
+pub const MAX_REWARD_DURATION: u64 = 365 * 24 * 60 * 60;
+
+// LP token decimals, and the base units in one whole LP token
+pub const LP_DECIMALS: u8 = 9;
+pub const LP_TOKEN_UNIT: u128 = 1_000_000_000;
+
+// Current `PoolState` layout version, bumped whenever fields are carved out of the
+// reserved space or the account grows, see `migrate_pool_state`
+pub const POOL_STATE_VERSION: u8 = 12;
+
+// Current `UserState` version. Version 2 counts staked LP only; legacy user states
+// are brought to it by `migrate_legacy_user_state`.
+pub const USER_STATE_VERSION: u8 = 2;
 
 /// PoolState holds global info about the liquidity pool.
-#[account]
-#[derive(InitSpace)]
+/// Per-asset balances, fees and oracles live in `AssetConfig` accounts.
+///
+/// New fields are taken from `reserved` at the end, and existing pools upgraded by
+/// `migrate_pool_state`. Once `reserved` runs out, the struct grows and the same
+/// instruction reallocates older pools to the new size, as version 11 did.
+/// Fields are ordered by alignment so the struct has no implicit padding.
+#[account(zero_copy)]
 pub struct PoolState {
-    /// Admin authority who can withdraw funds and set rewards
-    pub admin: Pubkey,
-
-    /// Authorities that can perform admin operations (e.g. margin program)
-    /// Vec stored on heap, reduces stack usage
-    #[max_len(MAX_AUTHORITIES)]
-    pub authorities: Vec<Pubkey>,
+    pub cumulative_reward_per_token: u128, // Using u128 for precision
 
-    /// SOL vault account (token account for wrapped SOL or special handling)
-    pub sol_vault: Pubkey,
+    /// Admin authority who can withdraw funds and set rewards.
+    /// Handed over with `propose_admin` / `accept_admin`
+    pub admin: Pubkey,
 
-    /// USDC vault account (USDC uses 6 decimals, so 1 USDC = 1_000_000)
-    /// Note: While USDC uses 6 decimals, USD values are handled with 8 decimals (1 USD = 100_000_000)
-    pub usdc_vault: Pubkey,
+    /// Authorities that can perform admin operations (e.g. margin program);
+    /// only the first `authority_count` entries are set
+    pub authorities: [Pubkey; MAX_AUTHORITIES],
 
+    /// USDC mint, used to pay out rewards (USDC uses 6 decimals, so 1 USDC = 1_000_000)
     pub usdc_mint: Pubkey,
 
     /// LP token mint
     pub lp_token_mint: Pubkey,
 
-    /// How many SOL tokens are currently deposited in total (9 decimals, 1 SOL = 1_000_000_000)
-    pub sol_deposited: u64,
-
-    /// How many USDC tokens are currently deposited in total (6 decimals, 1 USDC = 1_000_000)
-    /// Note: USD values derived from USDC use 8 decimals (1 USD = 100_000_000)
-    pub usdc_deposited: u64,
+    /// Vault holding USDC rewards
+    pub usdc_reward_vault: Pubkey,
 
     /// USDC earned per second per LP token (6 decimals)
     /// Note: All USDC amounts use 6 decimals, even though USD values use 8 decimals
     pub tokens_per_interval: u64,
 
-    /// Timestamp when current reward distribution started
+    /// Timestamp when the current reward period started (or was last topped up)
     pub reward_start_time: u64,
 
-    /// Timestamp when rewards stop accruing (start + 604800)
+    /// Timestamp when rewards stop accruing (start + duration)
     pub reward_end_time: u64,
 
-    /// Vault holding USDC rewards
-    pub usdc_reward_vault: Pubkey,
-
-    /// How many USDC tokens the admin deposited for this reward period (6 decimals)
+    /// How many USDC tokens the admin has deposited across all reward periods (6 decimals)
     /// Note: These are raw USDC amounts, not USD values
     pub total_rewards_deposited: u64,
 
@@ -75,31 +139,418 @@ pub struct PoolState {
     /// Note: These are raw USDC amounts, not USD values
     pub total_rewards_claimed: u64,
 
-    pub cumulative_reward_per_token: u128, // Using u128 for precision
-
     pub last_distribution_time: u64,
 
+    /// Id the pool's PDA is derived from (`[b"pool_state", pool_id]`), so one
+    /// deployment can host several risk-isolated pools
+    pub pool_id: u64,
+
+    /// LP tokens held in the LP stake vault; rewards are shared out over this,
+    /// not the LP mint supply, so LP held elsewhere earns nothing
+    pub total_staked_lp: u64,
+
     // -----------------------------------------------
-    // Fee tracking fields
+    // Asset registry
     // -----------------------------------------------
-    /// Accumulated SOL fees from deposits/withdrawals (9 decimals)
-    pub accumulated_sol_fees: u64,
+    /// Sum of every asset's target weight, used to turn weights into shares of AUM
+    pub total_target_weight_bps: u32,
 
-    /// Accumulated USDC fees from deposits/withdrawals (6 decimals)
-    /// Note: These are raw USDC amounts, not USD values (which use 8 decimals)
-    pub accumulated_usdc_fees: u64,
+    /// Maximum fee rebate/surcharge for flows that move the pool toward/away from its targets
+    /// (0 disables dynamic fees, so only the per-asset base rates apply)
+    pub dynamic_fee_tax_bps: u16,
+
+    /// Layout version, see POOL_STATE_VERSION
+    pub version: u8,
+
+    /// Number of entries in use in `authorities`
+    pub authority_count: u8,
+
+    /// Number of whitelisted assets; assets are indexed 0..asset_count
+    pub asset_count: u8,
+
+    /// Number of reward streams; streams are indexed 0..reward_stream_count
+    pub reward_stream_count: u8,
+
+    /// Admin proposed by `propose_admin`; takes over once it calls `accept_admin`.
+    /// `Pubkey::default()` when no transfer is pending
+    pub pending_admin: Pubkey,
+
+    /// Role bitmask of each entry in `authorities`, see `roles`
+    pub authority_roles: [u8; MAX_AUTHORITIES],
+
+    /// Operations currently paused, see `pause_flags`
+    pub paused: u8,
+
+    /// Non-zero for the pool `migrate_legacy_pool` moved out of the original
+    /// `[b"pool_state"]` account; its users' states may still be at the legacy
+    /// `[b"user_state", user]` address until `migrate_legacy_user_state` moves them
+    pub from_legacy_pool: u8,
+
+    /// Explicit padding so the u64 fields below are 8-byte aligned
+    pub padding: [u8; 2],
+
+    /// Largest AUM deposits may grow the pool to, in USD (8 decimals); 0 for no cap
+    pub max_aum_usd: u64,
+
+    /// Largest staked LP position a deposit may leave a wallet with; 0 for no cap
+    pub max_user_lp: u64,
+
+    /// Merkle root of wallets approved to deposit in permissioned mode,
+    /// see `util::allowlist`; all zeroes when unset
+    pub allowlist_root: [u8; 32],
+
+    /// Non-zero when only allowlisted wallets may deposit
+    pub permissioned: u8,
+
+    /// Non-zero when deposits are rejected while a stablecoin is outside its peg band
+    pub depeg_pauses_deposits: u8,
+
+    /// How far a stablecoin's oracle price may stray from 1 USD, in basis points,
+    /// before it is valued at that price instead; 0 values stablecoins at exactly
+    /// 1 USD without reading their oracle
+    pub peg_band_bps: u16,
+
+    /// USDC the reward period's rate was rounded down by, still to be emitted;
+    /// rolls into the next period along with what the running one has yet to emit.
+    /// Always below the period's duration, so it fits in 32 bits
+    pub reward_remainder: u32,
+
+    /// Non-zero once `set_outflow_limits` has created the pool's `OutflowLimits`
+    /// account, after which `admin_withdraw` refuses to run without it
+    pub has_outflow_limits: u8,
+
+    /// Space for future fields; pools before version 11 end before it
+    pub reserved: [u8; 127],
 }
 
 impl PoolState {
+    pub const LEN: usize = 8 + std::mem::size_of::<PoolState>();
+
+    /// Authorities currently registered
+    pub fn authorities(&self) -> &[Pubkey] {
+        &self.authorities[..self.authority_count as usize]
+    }
+
     /// Check if a public key is an authorized authority
     pub fn is_authority(&self, key: &Pubkey) -> bool {
-        self.authorities.iter().any(|auth| auth == key)
+        self.authorities().iter().any(|auth| auth == key)
     }
 
     /// Check if a public key is the admin
     pub fn is_admin(&self, key: &Pubkey) -> bool {
         self.admin == *key
     }
+
+    /// Check if a public key holds every role in `role`, either as the admin or
+    /// as an authority granted it
+    pub fn has_role(&self, key: &Pubkey, role: u8) -> bool {
+        self.is_admin(key)
+            || self
+                .authority_index(key)
+                .is_some_and(|index| self.authority_roles[index] & role == role)
+    }
+
+    /// Check if any of the operations in `operations` is paused
+    pub fn is_paused(&self, operations: u8) -> bool {
+        self.paused & operations != 0
+    }
+
+    /// Check everything that decides whether `wallet` may make the deposit priced
+    /// by `quote`: deposits aren't paused, the wallet is allowlisted and the pool's
+    /// caps hold. Shared by every instruction that mints LP for a deposit and by
+    /// `preview_deposit`, so a preview fails exactly when the deposit would.
+    pub fn check_deposit(
+        &self,
+        wallet: &Pubkey,
+        proof: &[[u8; 32]],
+        has_allowlist_entry: bool,
+        user_lp: u128,
+        quote: &DepositQuote,
+    ) -> Result<()> {
+        require!(
+            !self.is_paused(pause_flags::DEPOSITS),
+            VaultError::OperationPaused
+        );
+        self.check_allowlisted(wallet, proof, has_allowlist_entry)?;
+        self.check_deposit_limits(
+            quote.aum_usd as u128 + quote.deposit_usd as u128,
+            user_lp.saturating_add(quote.lp_to_mint as u128),
+        )
+    }
+
+    /// Everything a withdrawal of `lp_token_amount` must pass apart from slippage:
+    /// withdrawals aren't paused, and the user has that much LP staked
+    pub fn check_withdraw(&self, user_lp: u128, lp_token_amount: u64) -> Result<()> {
+        require!(
+            !self.is_paused(pause_flags::WITHDRAWALS),
+            VaultError::OperationPaused
+        );
+        require!(
+            user_lp >= lp_token_amount as u128,
+            VaultError::InsufficientLpBalance
+        );
+        Ok(())
+    }
+
+    /// Check a deposit against the pool's caps, given the AUM and the user's
+    /// staked LP once it has gone through
+    pub fn check_deposit_limits(&self, aum_after_usd: u128, user_lp_after: u128) -> Result<()> {
+        require!(
+            self.max_aum_usd == 0 || aum_after_usd <= self.max_aum_usd as u128,
+            VaultError::AumCapExceeded
+        );
+        require!(
+            self.max_user_lp == 0 || user_lp_after <= self.max_user_lp as u128,
+            VaultError::UserLpCapExceeded
+        );
+        Ok(())
+    }
+
+    /// In permissioned mode, require `wallet` to hold an allowlist entry or
+    /// prove its place under `allowlist_root`
+    pub fn check_allowlisted(
+        &self,
+        wallet: &Pubkey,
+        proof: &[[u8; 32]],
+        has_entry: bool,
+    ) -> Result<()> {
+        require!(
+            self.permissioned == 0
+                || is_allowlisted(&self.allowlist_root, wallet, proof, has_entry),
+            VaultError::NotAllowlisted
+        );
+        Ok(())
+    }
+
+    fn authority_index(&self, key: &Pubkey) -> Option<usize> {
+        self.authorities().iter().position(|auth| auth == key)
+    }
+
+    /// Register a new authority with the given roles
+    pub fn add_authority(&mut self, authority: Pubkey, roles: u8) -> Result<()> {
+        validate_roles(roles)?;
+        require!(
+            !self.is_authority(&authority),
+            VaultError::AuthorityAlreadyExists
+        );
+        require!(
+            (self.authority_count as usize) < MAX_AUTHORITIES,
+            VaultError::MaxAuthoritiesReached
+        );
+
+        let index = self.authority_count as usize;
+        self.authorities[index] = authority;
+        self.authority_roles[index] = roles;
+        self.authority_count += 1;
+        Ok(())
+    }
+
+    /// Replace the roles of an existing authority
+    pub fn set_authority_roles(&mut self, authority: &Pubkey, roles: u8) -> Result<()> {
+        validate_roles(roles)?;
+        let index = self
+            .authority_index(authority)
+            .ok_or(VaultError::AuthorityNotFound)?;
+
+        self.authority_roles[index] = roles;
+        Ok(())
+    }
+
+    /// Remove an authority, keeping the remaining entries contiguous
+    pub fn remove_authority(&mut self, authority: &Pubkey) -> Result<()> {
+        let count = self.authority_count as usize;
+        let index = self.authorities[..count]
+            .iter()
+            .position(|auth| auth == authority)
+            .ok_or(VaultError::AuthorityNotFound)?;
+
+        self.authorities[index] = self.authorities[count - 1];
+        self.authority_roles[index] = self.authority_roles[count - 1];
+        self.authorities[count - 1] = Pubkey::default();
+        self.authority_roles[count - 1] = 0;
+        self.authority_count -= 1;
+        Ok(())
+    }
+}
+
+/// Reject an empty set of operations or bits that don't name one
+pub fn validate_pause_flags(operations: u8) -> Result<()> {
+    require!(
+        operations != 0 && operations & !pause_flags::ALL == 0,
+        VaultError::InvalidPauseFlags
+    );
+    Ok(())
+}
+
+/// Reject an empty role set or bits that don't name a role
+pub fn validate_roles(roles: u8) -> Result<()> {
+    require!(
+        roles != 0 && roles & !roles::ALL == 0,
+        VaultError::InvalidRoles
+    );
+    Ok(())
+}
+
+const _: () = assert!(std::mem::size_of::<PoolState>() == 768);
+
+/// Borsh layout of `PoolState` as first deployed, before pool ids, the asset
+/// registry and versioning, when the single pool lived at `[b"pool_state"]`.
+/// Only read when migrating that pool.
+#[derive(AnchorDeserialize)]
+pub struct PoolStateV0 {
+    pub admin: Pubkey,
+    pub authorities: Vec<Pubkey>,
+    pub sol_vault: Pubkey,
+    pub usdc_vault: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub sol_deposited: u64,
+    pub usdc_deposited: u64,
+    pub tokens_per_interval: u64,
+    pub reward_start_time: u64,
+    pub reward_end_time: u64,
+    pub usdc_reward_vault: Pubkey,
+    pub total_rewards_deposited: u64,
+    pub total_rewards_claimed: u64,
+    pub cumulative_reward_per_token: u128,
+    pub last_distribution_time: u64,
+    pub accumulated_sol_fees: u64,
+    pub accumulated_usdc_fees: u64,
+}
+
+/// Approves one wallet to deposit while the pool is permissioned, as an
+/// alternative to a merkle proof against `PoolState::allowlist_root`.
+/// PDA seeds: [b"allowlist", pool_state, wallet]
+#[account]
+#[derive(InitSpace)]
+pub struct AllowlistEntry {
+    pub pool_state: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+/// AssetConfig is the registry entry for a single whitelisted mint.
+/// PDA seeds: [b"asset", pool_state, mint]
+#[account]
+#[derive(InitSpace)]
+pub struct AssetConfig {
+    /// Pool this asset belongs to
+    pub pool_state: Pubkey,
+
+    /// Position in the pool's registry (AUM accounts are passed in index order)
+    pub index: u8,
+
+    /// Token mint of the asset
+    pub mint: Pubkey,
+
+    /// Token account holding the pool's balance of this asset
+    pub vault: Pubkey,
+
+    /// Decimals of the asset's mint
+    pub decimals: u8,
+
+    /// Stablecoins are valued at exactly 1 USD while their oracle stays within
+    /// the pool's peg band, see `PoolState::peg_band_bps`
+    pub is_stable: bool,
+
+    /// How many tokens are currently deposited in total (native decimals)
+    pub deposited: u64,
+
+    /// Accumulated fees from deposits/withdrawals (native decimals)
+    pub accumulated_fees: u64,
+
+    // -----------------------------------------------
+    // Oracle configuration
+    // -----------------------------------------------
+    /// Oracle network used to price the asset
+    pub oracle_kind: OracleKind,
+
+    /// Program that owns the price feed (Chainlink store, Pyth receiver or Switchboard on-demand)
+    pub oracle_program: Pubkey,
+
+    /// Asset/USD price feed account
+    pub oracle_feed: Pubkey,
+
+    /// Feed id the price in `oracle_feed` must be published for (Pyth only)
+    pub oracle_feed_id: [u8; 32],
+
+    /// Maximum age of an oracle price, in seconds, before it is rejected as stale
+    pub max_price_age: u64,
+
+    /// Maximum oracle confidence interval as basis points of the price (0 disables the check)
+    pub max_confidence_bps: u16,
+
+    // -----------------------------------------------
+    // Fee configuration (basis points)
+    // -----------------------------------------------
+    /// Fee charged on deposits, capped at MAX_FEE_BPS
+    pub deposit_fee_bps: u16,
+
+    /// Fee charged on withdrawals, capped at MAX_FEE_BPS
+    pub withdraw_fee_bps: u16,
+
+    /// Share of AUM the pool aims to hold in this asset, relative to the pool's total weight
+    pub target_weight_bps: u16,
+
+    pub bump: u8,
+}
+
+impl AssetConfig {
+    /// Price the asset in USD with 8 decimals. Stablecoins are worth exactly
+    /// 1 USD while `peg_band_bps` is 0 or their oracle price is within
+    /// `peg_band_bps` of 1 USD; outside the band they take the oracle price.
+    pub fn read_price<'info>(
+        &self,
+        oracle_program: &AccountInfo<'info>,
+        oracle_feed: &AccountInfo<'info>,
+        peg_band_bps: u16,
+    ) -> Result<u128> {
+        if self.is_stable && peg_band_bps == 0 {
+            return Ok(ONE_USD);
+        }
+
+        let price = self.read_oracle_price(oracle_program, oracle_feed)?;
+        if self.is_stable && is_within_peg(price, peg_band_bps) {
+            return Ok(ONE_USD);
+        }
+        Ok(price)
+    }
+
+    /// Read the asset/USD price from the configured oracle, rejecting stale,
+    /// non-positive or low-confidence prices. Returns the price with 8 decimals.
+    fn read_oracle_price<'info>(
+        &self,
+        oracle_program: &AccountInfo<'info>,
+        oracle_feed: &AccountInfo<'info>,
+    ) -> Result<u128> {
+        require_keys_eq!(
+            oracle_program.key(),
+            self.oracle_program,
+            VaultError::InvalidOracleFeed
+        );
+        require_keys_eq!(
+            oracle_feed.key(),
+            self.oracle_feed,
+            VaultError::InvalidOracleFeed
+        );
+
+        let price = oracle::read_price(
+            self.oracle_kind,
+            oracle_program,
+            oracle_feed,
+            &self.oracle_feed_id,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+
+        price
+            .validate(now, self.max_price_age, self.max_confidence_bps)
+            .map_err(|rejection| error!(VaultError::from(rejection)))
+    }
+}
+
+/// Check whether `price` (8 decimals) is within `peg_band_bps` of 1 USD
+pub fn is_within_peg(price: u128, peg_band_bps: u16) -> bool {
+    price.abs_diff(ONE_USD) <= ONE_USD * peg_band_bps as u128 / BPS_DENOMINATOR as u128
 }
 
 /// UserState stores user-specific info (in practice often combined into a single PDA).
@@ -109,7 +560,7 @@ pub struct UserState {
     /// User pubkey
     pub owner: Pubkey,
 
-    /// User's LP token balance (tracked within the program, not minted supply)
+    /// LP tokens the user has staked in the pool's LP stake vault; only these earn rewards
     pub lp_token_balance: u128,
 
     /// Last time user claimed (or had rewards updated)
@@ -120,50 +571,256 @@ pub struct UserState {
 
     /// Previous cumulative reward per token
     pub previous_cumulated_reward_per_token: u128,
+
+    /// Layout version, see USER_STATE_VERSION
+    pub version: u8,
+
+    /// Accrual state for each of the pool's reward streams, by stream index
+    pub reward_checkpoints: [RewardCheckpoint; MAX_REWARD_STREAMS],
+}
+
+impl UserState {
+    pub const LEN: usize = 8 + UserState::INIT_SPACE;
+
+    /// Size before `version` and `reward_checkpoints` were added
+    pub const LEGACY_LEN: usize =
+        UserState::LEN - 1 - MAX_REWARD_STREAMS * RewardCheckpoint::INIT_SPACE;
+}
+
+/// A user's position in one reward stream
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct RewardCheckpoint {
+    /// Stream's cumulative reward per token when the user was last updated
+    pub previous_cumulated_reward_per_token: u128,
+
+    /// Rewards accrued from the stream that have not yet been claimed (stream mint decimals)
+    pub pending_rewards: u128,
+}
+
+/// RewardStream is the registry entry for a reward token paid to LPs on its own
+/// schedule, alongside the pool's USDC rewards.
+/// PDA seeds: [b"reward_stream", pool_state, mint]
+#[account]
+#[derive(InitSpace)]
+pub struct RewardStream {
+    /// Pool this stream pays out to
+    pub pool_state: Pubkey,
+
+    /// Position in the pool's registry (streams are passed in index order)
+    pub index: u8,
+
+    /// Mint of the reward token
+    pub mint: Pubkey,
+
+    /// Token account holding the stream's undistributed rewards
+    pub vault: Pubkey,
+
+    /// Reward tokens emitted per second across all LP tokens (stream mint decimals)
+    pub tokens_per_interval: u64,
+
+    /// Timestamp when the current reward period started (or was last topped up)
+    pub reward_start_time: u64,
+
+    /// Timestamp when rewards stop accruing (start + duration)
+    pub reward_end_time: u64,
+
+    /// Timestamp up to which `cumulative_reward_per_token` has been accrued
+    pub last_distribution_time: u64,
+
+    /// Rewards emitted per LP token since the stream was added (scaled by 1e12)
+    pub cumulative_reward_per_token: u128,
+
+    /// Reward tokens deposited across all periods (stream mint decimals)
+    pub total_rewards_deposited: u64,
+
+    /// Reward tokens claimed by users so far (stream mint decimals)
+    pub total_rewards_claimed: u64,
+
+    /// Reward tokens the period's rate was rounded down by, as for the pool's
+    /// `reward_remainder`
+    pub reward_remainder: u32,
+
+    pub bump: u8,
 }
 
 // -----------------------------------------------
-// Chainlink conversion helpers
+// Oracle conversion helpers
 // -----------------------------------------------
 
-/// Helper function for SOL -> USD conversions using the `sol_usd_price` from Chainlink.
+/// Helper function for token -> USD conversions using a validated oracle price.
 ///
 /// Input:
-///   - sol_amount: Amount of SOL with 9 decimals (1 SOL = 1_000_000_000)
-///   - sol_usd_price: Chainlink price with 8 decimals
+///   - amount: Token amount in the mint's native decimals
+///   - decimals: Decimals of the token's mint
+///   - price: Validated oracle price with 8 decimals
+///   - rounding: Direction to round the result in
 /// Output:
 ///   - USD value with 8 decimals (1 USD = 100_000_000)
-pub fn get_sol_usd_value(sol_amount: u64, sol_usd_price: i128) -> Result<u64> {
-    // Convert SOL to USD with proper decimal handling:
-    // 1. Multiply SOL (9 decimals) by price (8 decimals)
-    // 2. Divide by 10^8 (Chainlink decimals) to get to raw USD
-    // 3. Divide by 10 (9 - 8 = 1) to convert from 9 to 8 decimals
-    let usd = (sol_amount as u128)
-        .checked_mul(sol_usd_price as u128)
-        .ok_or(crate::errors::VaultError::MathError)?
-        .checked_div(100_000_000) // Remove Chainlink's 8 decimals
-        .ok_or(crate::errors::VaultError::MathError)?
-        .checked_div(10) // Convert from 9 to 8 decimals
-        .ok_or(crate::errors::VaultError::MathError)?;
-
-    Ok(usd as u64)
-}
-
-/// Helper function for USD -> SOL conversions using the `sol_usd_price` from Chainlink.
+pub fn get_usd_value(amount: u64, decimals: u8, price: u128, rounding: Rounding) -> Result<u64> {
+    // amount * price has (decimals + 8) decimals; dividing by 10^decimals leaves 8
+    let usd = mul_div(amount as u128, price, 10u128.pow(decimals as u32), rounding)?;
+
+    u64::try_from(usd).map_err(|_| error!(VaultError::MathError))
+}
+
+/// Helper function for USD -> token conversions using a validated oracle price.
 ///
 /// Input:
 ///   - usd_value: USD amount with 8 decimals (1 USD = 100_000_000)
-///   - sol_usd_price: Chainlink price with 8 decimals
+///   - decimals: Decimals of the token's mint
+///   - price: Validated oracle price with 8 decimals
+///   - rounding: Direction to round the result in
 /// Output:
-///   - SOL amount with 9 decimals (1 SOL = 1_000_000_000)
-pub fn get_sol_amount_from_usd(usd_value: u64, sol_usd_price: i128) -> Result<u64> {
-    let sol = (usd_value as u128)
-        .checked_mul(100_000_000) // Add Chainlink's 8 decimals
-        .ok_or(crate::errors::VaultError::MathError)?
-        .checked_mul(10) // Convert from 8 to 9 decimals
-        .ok_or(crate::errors::VaultError::MathError)?
-        .checked_div(sol_usd_price as u128)
-        .ok_or(crate::errors::VaultError::MathError)?;
-
-    Ok(sol as u64)
+///   - Token amount in the mint's native decimals
+pub fn get_token_amount_from_usd(
+    usd_value: u64,
+    decimals: u8,
+    price: u128,
+    rounding: Rounding,
+) -> Result<u64> {
+    require!(price > 0, VaultError::InvalidOraclePrice);
+
+    let amount = mul_div(
+        usd_value as u128,
+        10u128.pow(decimals as u32),
+        price,
+        rounding,
+    )?;
+
+    u64::try_from(amount).map_err(|_| error!(VaultError::MathError))
+}
+
+/// Caps on how much value `admin_withdraw` can move out of a pool per time window,
+/// both in total and per caller, as a share of AUM at the start of the window.
+/// PDA seeds: [b"outflow_limits", pool_state]
+#[account]
+#[derive(InitSpace)]
+pub struct OutflowLimits {
+    /// Pool the limits apply to
+    pub pool_state: Pubkey,
+
+    /// Length of a window in seconds (0 disables the limits)
+    pub window_secs: u64,
+
+    /// Most that can leave the pool per window, as basis points of the window's AUM
+    pub max_outflow_bps: u16,
+
+    /// Most that any single caller can withdraw per window, as basis points of the window's AUM
+    pub max_caller_outflow_bps: u16,
+
+    /// Timestamp the current window started
+    pub window_start: i64,
+
+    /// AUM when the current window started (8 decimals)
+    pub window_aum_usd: u64,
+
+    /// USD value withdrawn so far in the current window (8 decimals)
+    pub window_outflow_usd: u64,
+
+    /// USD value each caller has withdrawn in the current window
+    pub caller_outflows: [CallerOutflow; MAX_OUTFLOW_CALLERS],
+
+    pub bump: u8,
+}
+
+/// Value one caller has withdrawn in the current outflow window
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct CallerOutflow {
+    pub caller: Pubkey,
+
+    /// USD value withdrawn (8 decimals)
+    pub outflow_usd: u64,
+}
+
+impl OutflowLimits {
+    pub const LEN: usize = 8 + OutflowLimits::INIT_SPACE;
+
+    pub fn is_enabled(&self) -> bool {
+        self.window_secs > 0
+    }
+
+    /// Deserialize the limits at the `[b"outflow_limits", pool_state]` address
+    /// `info` was checked against, or `None` if `set_outflow_limits` has never
+    /// created them
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        let data = info.try_borrow_data()?;
+        Ok(Some(OutflowLimits::try_deserialize(&mut &data[..])?))
+    }
+
+    /// Write limits loaded with `load` back to their account
+    pub fn store(&self, info: &AccountInfo) -> Result<()> {
+        let mut data = info.try_borrow_mut_data()?;
+        self.try_serialize(&mut &mut data[..])
+    }
+
+    /// Record `amount_usd` leaving the pool on behalf of `caller`, starting a new
+    /// window at `aum_usd` if the current one has ended. Fails if either the
+    /// pool-wide or the caller's cap for the window would be exceeded.
+    pub fn record_outflow(
+        &mut self,
+        caller: Pubkey,
+        amount_usd: u64,
+        aum_usd: u64,
+        now: i64,
+    ) -> Result<()> {
+        let window_end = self
+            .window_start
+            .checked_add(self.window_secs as i64)
+            .ok_or(VaultError::MathError)?;
+        if now >= window_end {
+            self.window_start = now;
+            self.window_aum_usd = aum_usd;
+            self.window_outflow_usd = 0;
+            self.caller_outflows = [CallerOutflow::default(); MAX_OUTFLOW_CALLERS];
+        }
+
+        let window_outflow = self
+            .window_outflow_usd
+            .checked_add(amount_usd)
+            .ok_or(VaultError::MathError)?;
+        require!(
+            window_outflow <= self.cap(self.max_outflow_bps)?,
+            VaultError::OutflowLimitExceeded
+        );
+
+        let caller_cap = self.cap(self.max_caller_outflow_bps)?;
+        let entry = self
+            .caller_outflows
+            .iter()
+            .position(|entry| entry.caller == caller)
+            .or_else(|| {
+                self.caller_outflows
+                    .iter()
+                    .position(|entry| entry.caller == Pubkey::default())
+            })
+            .ok_or(VaultError::MaxAuthoritiesReached)?;
+        let entry = &mut self.caller_outflows[entry];
+        let caller_outflow = entry
+            .outflow_usd
+            .checked_add(amount_usd)
+            .ok_or(VaultError::MathError)?;
+        require!(
+            caller_outflow <= caller_cap,
+            VaultError::CallerOutflowLimitExceeded
+        );
+
+        entry.caller = caller;
+        entry.outflow_usd = caller_outflow;
+        self.window_outflow_usd = window_outflow;
+        Ok(())
+    }
+
+    /// `bps` of the window's AUM, rounded down
+    fn cap(&self, bps: u16) -> Result<u64> {
+        let cap = mul_div(
+            self.window_aum_usd as u128,
+            bps as u128,
+            BPS_DENOMINATOR as u128,
+            Rounding::Down,
+        )?;
+        Ok(cap as u64)
+    }
 }
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+/// Merkle leaf for `wallet`: keccak256 of its 32 bytes
+pub fn allowlist_leaf(wallet: &Pubkey) -> [u8; 32] {
+    keccak::hash(wallet.as_ref()).to_bytes()
+}
+
+/// Check `leaf` is in the tree committed to by `root`. Pairs are hashed in
+/// sorted order, so a proof is just the sibling hashes from the leaf up.
+pub fn verify_merkle_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
+/// Whether `wallet` is approved for a permissioned deposit, either through an
+/// allowlist entry or a proof against `root`. An all-zero root approves nobody.
+pub fn is_allowlisted(
+    root: &[u8; 32],
+    wallet: &Pubkey,
+    proof: &[[u8; 32]],
+    has_entry: bool,
+) -> bool {
+    has_entry || (*root != [0; 32] && verify_merkle_proof(root, allowlist_leaf(wallet), proof))
+}
@@ -0,0 +1,96 @@
+use crate::{errors::VaultError, state::*, util::math::Rounding};
+use anchor_lang::prelude::*;
+
+/// Accounts passed per registered asset in `remaining_accounts`:
+/// [asset_config, oracle_program, oracle_feed], in asset index order.
+/// While the pool's peg band is 0 stable assets skip the oracle, so any account
+/// can fill their oracle slots.
+pub const AUM_ACCOUNTS_PER_ASSET: usize = 3;
+
+/// Prices and USD values of every registered asset at a point in time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AumSnapshot {
+    /// Validated price of each asset (8 decimals), by asset index
+    pub prices: Vec<u128>,
+
+    /// USD value of each asset's deposits (8 decimals), by asset index
+    pub asset_usd: Vec<u64>,
+
+    /// Total Assets Under Management (8 decimals)
+    pub total_usd: u64,
+
+    /// Whether any stable asset is priced outside the pool's peg band
+    pub depegged: bool,
+}
+
+/// Sum the USD value of every asset registered to `pool_state` (stored at `pool_key`).
+/// Every asset must be supplied so AUM can't be understated by omitting one.
+/// Each asset's value is rounded in the `rounding` direction: up when pricing
+/// deposits, down when pricing withdrawals.
+pub fn compute_aum<'info>(
+    pool_key: Pubkey,
+    pool_state: &PoolState,
+    remaining_accounts: &[AccountInfo<'info>],
+    rounding: Rounding,
+) -> Result<AumSnapshot> {
+    let asset_count = pool_state.asset_count as usize;
+    require!(
+        remaining_accounts.len() == asset_count * AUM_ACCOUNTS_PER_ASSET,
+        VaultError::InvalidAssetAccounts
+    );
+
+    let mut snapshot = AumSnapshot {
+        prices: Vec::with_capacity(asset_count),
+        asset_usd: Vec::with_capacity(asset_count),
+        total_usd: 0,
+        depegged: false,
+    };
+
+    for (index, accounts) in remaining_accounts
+        .chunks_exact(AUM_ACCOUNTS_PER_ASSET)
+        .enumerate()
+    {
+        let asset = load_asset_config(&accounts[0])?;
+        require!(
+            asset.pool_state == pool_key && asset.index as usize == index,
+            VaultError::InvalidAssetAccounts
+        );
+
+        let price = asset.read_price(&accounts[1], &accounts[2], pool_state.peg_band_bps)?;
+        // Stablecoins only move off 1 USD once they leave the peg band
+        snapshot.depegged |= asset.is_stable && price != ONE_USD;
+        let usd = get_usd_value(asset.deposited, asset.decimals, price, rounding)?;
+
+        snapshot.prices.push(price);
+        snapshot.asset_usd.push(usd);
+        snapshot.total_usd = snapshot
+            .total_usd
+            .checked_add(usd)
+            .ok_or(VaultError::MathError)?;
+    }
+
+    Ok(snapshot)
+}
+
+/// Split remaining accounts into the AUM accounts `compute_aum` expects and
+/// whatever follows them
+pub fn split_aum_accounts<'a, 'info>(
+    pool_state: &PoolState,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let aum_len = pool_state.asset_count as usize * AUM_ACCOUNTS_PER_ASSET;
+    require!(
+        remaining_accounts.len() >= aum_len,
+        VaultError::InvalidAssetAccounts
+    );
+
+    Ok(remaining_accounts.split_at(aum_len))
+}
+
+/// Deserialize an `AssetConfig` passed as a remaining account
+fn load_asset_config(info: &AccountInfo) -> Result<AssetConfig> {
+    require_keys_eq!(*info.owner, crate::ID, VaultError::InvalidAssetAccounts);
+
+    let data = info.try_borrow_data()?;
+    AssetConfig::try_deserialize(&mut &data[..])
+}
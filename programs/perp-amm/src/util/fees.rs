@@ -0,0 +1,77 @@
+use crate::{
+    errors::VaultError,
+    state::BPS_DENOMINATOR,
+    util::math::{mul_div, Rounding},
+};
+use anchor_lang::prelude::*;
+
+/// Fee owed on `amount` at `fee_bps` basis points, rounded up in the pool's favor
+pub fn calculate_fee(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = mul_div(
+        amount as u128,
+        fee_bps as u128,
+        BPS_DENOMINATOR as u128,
+        Rounding::Up,
+    )?;
+
+    Ok(fee as u64)
+}
+
+/// GLP-style dynamic fee for a flow that moves one asset's USD value from `asset_usd`
+/// to `next_asset_usd`.
+///
+/// Flows that move the asset toward its target weight get up to `tax_bps` knocked off
+/// `base_fee_bps`; flows that push it further away pay up to `tax_bps` extra, scaled by
+/// how far from target the asset ends up. All USD values use 8 decimals.
+pub fn dynamic_fee_bps(
+    base_fee_bps: u16,
+    tax_bps: u16,
+    asset_usd: u128,
+    next_asset_usd: u128,
+    target_weight_bps: u16,
+    total_target_weight_bps: u32,
+    aum_usd: u128,
+) -> Result<u16> {
+    // No weights configured, nothing to steer towards
+    if tax_bps == 0 || total_target_weight_bps == 0 {
+        return Ok(base_fee_bps);
+    }
+
+    let target_usd = aum_usd
+        .checked_mul(target_weight_bps as u128)
+        .ok_or(VaultError::MathError)?
+        .checked_div(total_target_weight_bps as u128)
+        .ok_or(VaultError::MathError)?;
+
+    // Empty pool or zero weight
+    if target_usd == 0 {
+        return Ok(base_fee_bps);
+    }
+
+    let initial_diff = asset_usd.abs_diff(target_usd);
+    let next_diff = next_asset_usd.abs_diff(target_usd);
+
+    // Moving toward the target: rebate proportional to the starting imbalance
+    if next_diff < initial_diff {
+        let rebate = (tax_bps as u128)
+            .checked_mul(initial_diff)
+            .ok_or(VaultError::MathError)?
+            .checked_div(target_usd)
+            .ok_or(VaultError::MathError)?;
+        return Ok((base_fee_bps as u128).saturating_sub(rebate) as u16);
+    }
+
+    // Moving away from the target: surcharge proportional to the average imbalance
+    let average_diff = (initial_diff
+        .checked_add(next_diff)
+        .ok_or(VaultError::MathError)?
+        / 2)
+    .min(target_usd);
+    let surcharge = (tax_bps as u128)
+        .checked_mul(average_diff)
+        .ok_or(VaultError::MathError)?
+        .checked_div(target_usd)
+        .ok_or(VaultError::MathError)?;
+
+    Ok((base_fee_bps as u128 + surcharge) as u16)
+}
@@ -0,0 +1,184 @@
+use crate::{errors::VaultError, state::*, util::*};
+use anchor_lang::prelude::*;
+
+/// Outcome of pricing a deposit against the current pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DepositQuote {
+    /// Portion of the deposit kept as fees (asset decimals)
+    pub fee_amount: u64,
+
+    /// Portion of the deposit credited to the pool (asset decimals)
+    pub deposit_amount: u64,
+
+    /// Value of `deposit_amount`, rounded down (8 decimals)
+    pub deposit_usd: u64,
+
+    /// LP tokens to mint for the deposit
+    pub lp_to_mint: u64,
+
+    /// Validated price of the asset (8 decimals)
+    pub asset_price: u128,
+
+    /// AUM the deposit was priced against, rounded up (8 decimals)
+    pub aum_usd: u64,
+}
+
+/// Outcome of pricing a withdrawal against the current pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WithdrawalQuote {
+    /// Gross amount of the asset the burned LP tokens are worth (asset decimals)
+    pub token_amount: u64,
+
+    /// Portion of `token_amount` kept as fees (asset decimals)
+    pub fee_amount: u64,
+
+    /// Amount paid out to the user (asset decimals)
+    pub withdrawal_amount: u64,
+
+    /// Validated price of the asset (8 decimals)
+    pub asset_price: u128,
+
+    /// AUM the withdrawal was priced against, rounded down (8 decimals)
+    pub aum_usd: u64,
+}
+
+/// Price a deposit of `token_amount` of `asset`, including its dynamic fee and
+/// the LP tokens it is worth. Every rounding step favors the pool: AUM rounds up,
+/// fees round up, and the deposit's value and LP minted round down.
+pub fn quote_deposit<'info>(
+    pool_key: Pubkey,
+    pool_state: &PoolState,
+    asset: &AssetConfig,
+    remaining_accounts: &[AccountInfo<'info>],
+    lp_supply: u64,
+    token_amount: u64,
+) -> Result<DepositQuote> {
+    // Compute initial Assets Under Management (AUM) across every registered asset.
+    let aum = compute_aum(pool_key, pool_state, remaining_accounts, Rounding::Up)?;
+    require!(
+        !aum.depegged || pool_state.depeg_pauses_deposits == 0,
+        VaultError::StablecoinDepegged
+    );
+    let initial_aum = aum.total_usd;
+    let price = aum.prices[asset.index as usize];
+    let asset_usd = aum.asset_usd[asset.index as usize];
+
+    // Calculate the deposit fee, steered by how the deposit moves the pool
+    // relative to its target weights, and the net deposit.
+    let gross_deposit_usd = get_usd_value(token_amount, asset.decimals, price, Rounding::Down)?;
+    let fee_bps = dynamic_fee_bps(
+        asset.deposit_fee_bps,
+        pool_state.dynamic_fee_tax_bps,
+        asset_usd as u128,
+        (asset_usd as u128)
+            .checked_add(gross_deposit_usd as u128)
+            .ok_or(VaultError::MathError)?,
+        asset.target_weight_bps,
+        pool_state.total_target_weight_bps,
+        initial_aum as u128,
+    )?;
+    let fee_amount = calculate_fee(token_amount, fee_bps)?;
+    let deposit_amount = token_amount
+        .checked_sub(fee_amount)
+        .ok_or(VaultError::MathError)?;
+    let deposit_usd = get_usd_value(deposit_amount, asset.decimals, price, Rounding::Down)?;
+
+    // Price LP against the pool plus its virtual supply and AUM, so an empty pool
+    // mints 10 LP units per USD unit and donations can't inflate the LP price
+    let lp_to_mint = mul_div(
+        deposit_usd as u128,
+        (lp_supply as u128)
+            .checked_add(VIRTUAL_LP_SUPPLY)
+            .ok_or(VaultError::MathError)?,
+        (initial_aum as u128)
+            .checked_add(VIRTUAL_AUM_USD)
+            .ok_or(VaultError::MathError)?,
+        Rounding::Down,
+    )?;
+    require!(lp_to_mint > 0, VaultError::DepositTooSmall);
+
+    Ok(DepositQuote {
+        fee_amount,
+        deposit_amount,
+        deposit_usd,
+        lp_to_mint: u64::try_from(lp_to_mint).map_err(|_| error!(VaultError::MathError))?,
+        asset_price: price,
+        aum_usd: initial_aum,
+    })
+}
+
+/// Price burning `lp_token_amount` LP tokens for `asset`, including its dynamic fee.
+/// Every rounding step favors the pool: AUM, the LP tokens' value and the tokens
+/// paid out round down, and fees round up.
+pub fn quote_withdrawal<'info>(
+    pool_key: Pubkey,
+    pool_state: &PoolState,
+    asset: &AssetConfig,
+    remaining_accounts: &[AccountInfo<'info>],
+    lp_supply: u64,
+    lp_token_amount: u64,
+) -> Result<WithdrawalQuote> {
+    // Value the pool across every registered asset
+    let aum = compute_aum(pool_key, pool_state, remaining_accounts, Rounding::Down)?;
+    let current_aum = aum.total_usd;
+    let price = aum.prices[asset.index as usize];
+    let asset_usd = aum.asset_usd[asset.index as usize];
+
+    // Value the LP tokens against the same virtual supply and AUM used when minting
+    let withdrawal_usd_value = mul_div(
+        lp_token_amount as u128,
+        (current_aum as u128)
+            .checked_add(VIRTUAL_AUM_USD)
+            .ok_or(VaultError::MathError)?,
+        (lp_supply as u128)
+            .checked_add(VIRTUAL_LP_SUPPLY)
+            .ok_or(VaultError::MathError)?,
+        Rounding::Down,
+    )?;
+
+    let token_amount = get_token_amount_from_usd(
+        u64::try_from(withdrawal_usd_value).map_err(|_| error!(VaultError::MathError))?,
+        asset.decimals,
+        price,
+        Rounding::Down,
+    )?;
+
+    // Fee is steered by how the withdrawal moves the pool relative to its target weights
+    let fee_bps = dynamic_fee_bps(
+        asset.withdraw_fee_bps,
+        pool_state.dynamic_fee_tax_bps,
+        asset_usd as u128,
+        (asset_usd as u128).saturating_sub(withdrawal_usd_value),
+        asset.target_weight_bps,
+        pool_state.total_target_weight_bps,
+        current_aum as u128,
+    )?;
+    let fee_amount = calculate_fee(token_amount, fee_bps)?;
+    let withdrawal_amount = token_amount
+        .checked_sub(fee_amount)
+        .ok_or(VaultError::MathError)?;
+
+    Ok(WithdrawalQuote {
+        token_amount,
+        fee_amount,
+        withdrawal_amount,
+        asset_price: price,
+        aum_usd: current_aum,
+    })
+}
+
+/// USD value (8 decimals) of one whole LP token, valued the same way withdrawals
+/// are: against the pool plus its virtual supply and AUM, rounded down.
+pub fn lp_token_price(aum_usd: u64, lp_supply: u64) -> Result<u64> {
+    let price = mul_div(
+        LP_TOKEN_UNIT,
+        (aum_usd as u128)
+            .checked_add(VIRTUAL_AUM_USD)
+            .ok_or(VaultError::MathError)?,
+        (lp_supply as u128)
+            .checked_add(VIRTUAL_LP_SUPPLY)
+            .ok_or(VaultError::MathError)?,
+        Rounding::Down,
+    )?;
+    u64::try_from(price).map_err(|_| error!(VaultError::MathError))
+}
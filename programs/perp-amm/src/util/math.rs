@@ -0,0 +1,23 @@
+use crate::errors::VaultError;
+use anchor_lang::prelude::*;
+
+/// Direction to round a division in. Pool math always picks the direction that
+/// favors the pool over the user.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// `a * b / c`, rounded in the given direction
+pub fn mul_div(a: u128, b: u128, c: u128, rounding: Rounding) -> Result<u128> {
+    require!(c > 0, VaultError::MathError);
+
+    let product = a.checked_mul(b).ok_or(VaultError::MathError)?;
+    let quotient = product / c;
+
+    if rounding == Rounding::Up && product % c != 0 {
+        return quotient.checked_add(1).ok_or(error!(VaultError::MathError));
+    }
+    Ok(quotient)
+}
@@ -1,3 +1,13 @@
+pub mod allowlist;
+pub mod aum;
+pub mod fees;
+pub mod liquidity;
+pub mod math;
 pub mod update_rewards;
 
+pub use allowlist::*;
+pub use aum::*;
+pub use fees::*;
+pub use liquidity::*;
+pub use math::*;
 pub use update_rewards::*;
@@ -1,61 +1,136 @@
 use crate::{
     errors::VaultError,
-    state::{PoolState, UserState},
+    state::{PoolState, RewardStream, UserState, POOL_STATE_VERSION, USER_STATE_VERSION},
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
 
-pub fn update_rewards(
-    pool_state: &mut PoolState,
-    user_state: &mut UserState,
-    lp_token_mint: &Account<Mint>,
-) -> Result<()> {
-    const PRECISION: u128 = 1_000_000_000_000;
-
-    let now = Clock::get()?.unix_timestamp as u64;
+const PRECISION: u128 = 1_000_000_000_000;
 
-    if lp_token_mint.supply == 0 {
-        pool_state.last_distribution_time = now;
+/// Advance a reward accumulator to `now`, or to the end of its reward period if
+/// that came first.
+///
+/// While nothing is staked there is no one to emit to, so the period is paused
+/// instead: its end moves out by the idle time, keeping what it has yet to emit
+fn accrue(
+    cumulative_reward_per_token: &mut u128,
+    last_distribution_time: &mut u64,
+    reward_end_time: &mut u64,
+    tokens_per_interval: u64,
+    total_staked_lp: u64,
+    now: u64,
+) -> Result<()> {
+    if total_staked_lp == 0 {
+        if *reward_end_time > *last_distribution_time {
+            *reward_end_time = now
+                .checked_add(*reward_end_time - *last_distribution_time)
+                .ok_or(VaultError::MathError)?;
+        }
+        *last_distribution_time = now;
         return Ok(());
     }
 
-    let effective_end_time = pool_state.reward_end_time.min(now);
+    let reward_end_time = *reward_end_time;
+
+    let effective_end_time = reward_end_time.min(now);
 
-    if pool_state.last_distribution_time >= pool_state.reward_end_time {
+    if *last_distribution_time >= effective_end_time {
         return Ok(());
     }
 
-    let time_diff = effective_end_time.saturating_sub(pool_state.last_distribution_time);
-    if time_diff > 0 {
-        let pending_rewards = (pool_state.tokens_per_interval as u128)
-            .checked_mul(time_diff as u128)
-            .ok_or(VaultError::MathError)?;
+    let time_diff = effective_end_time - *last_distribution_time;
+    let pending_rewards = (tokens_per_interval as u128)
+        .checked_mul(time_diff as u128)
+        .ok_or(VaultError::MathError)?;
+
+    let reward_per_token = pending_rewards
+        .checked_mul(PRECISION)
+        .ok_or(VaultError::MathError)?
+        .checked_div(total_staked_lp as u128)
+        .ok_or(VaultError::MathError)?;
 
-        let reward_per_token = pending_rewards
-            .checked_mul(PRECISION)
-            .ok_or(VaultError::MathError)?
-            .checked_div(lp_token_mint.supply as u128)
-            .ok_or(VaultError::MathError)?;
+    *cumulative_reward_per_token = cumulative_reward_per_token
+        .checked_add(reward_per_token)
+        .ok_or(VaultError::MathError)?;
 
-        pool_state.cumulative_reward_per_token = pool_state
-            .cumulative_reward_per_token
-            .checked_add(reward_per_token)
-            .ok_or(VaultError::MathError)?;
+    *last_distribution_time = effective_end_time;
 
-        pool_state.last_distribution_time = effective_end_time;
-    }
+    Ok(())
+}
 
-    let user_reward = (user_state.lp_token_balance as u128)
+/// Rewards earned by `lp_token_balance` since the user's checkpoint
+fn earned(
+    lp_token_balance: u128,
+    cumulative_reward_per_token: u128,
+    previous_cumulated_reward_per_token: u128,
+) -> Result<u128> {
+    let reward = lp_token_balance
         .checked_mul(
-            pool_state
-                .cumulative_reward_per_token
-                .checked_sub(user_state.previous_cumulated_reward_per_token)
+            cumulative_reward_per_token
+                .checked_sub(previous_cumulated_reward_per_token)
                 .ok_or(VaultError::MathError)?,
         )
         .ok_or(VaultError::MathError)?
         .checked_div(PRECISION)
         .ok_or(VaultError::MathError)?;
 
+    Ok(reward)
+}
+
+/// Advance the pool's USDC reward accumulator to now, or to the end of the reward
+/// period if that came first
+pub fn accrue_pool_rewards(pool_state: &mut PoolState) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp as u64;
+    let total_staked_lp = pool_state.total_staked_lp;
+    accrue(
+        &mut pool_state.cumulative_reward_per_token,
+        &mut pool_state.last_distribution_time,
+        &mut pool_state.reward_end_time,
+        pool_state.tokens_per_interval,
+        total_staked_lp,
+        now,
+    )
+}
+
+/// Advance a reward stream's accumulator to now, or to the end of its reward
+/// period if that came first
+pub fn accrue_stream_rewards(stream: &mut RewardStream, total_staked_lp: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp as u64;
+    accrue(
+        &mut stream.cumulative_reward_per_token,
+        &mut stream.last_distribution_time,
+        &mut stream.reward_end_time,
+        stream.tokens_per_interval,
+        total_staked_lp,
+        now,
+    )
+}
+
+/// Reject pools and users still on a layout that predates staking, whose reward
+/// ledgers don't yet match the LP stake vault
+pub fn require_current_versions(pool_state: &PoolState, user_state: &UserState) -> Result<()> {
+    require!(
+        pool_state.version == POOL_STATE_VERSION,
+        VaultError::NotMigrated
+    );
+    require!(
+        user_state.version == USER_STATE_VERSION,
+        VaultError::NotMigrated
+    );
+    Ok(())
+}
+
+pub fn update_rewards(pool_state: &mut PoolState, user_state: &mut UserState) -> Result<()> {
+    require_current_versions(pool_state, user_state)?;
+    accrue_pool_rewards(pool_state)?;
+
+    // Always checkpoint the user, even when nothing accrued, so a new position
+    // never earns rewards accumulated before it existed
+    let user_reward = earned(
+        user_state.lp_token_balance,
+        pool_state.cumulative_reward_per_token,
+        user_state.previous_cumulated_reward_per_token,
+    )?;
+
     user_state.pending_rewards = user_state
         .pending_rewards
         .checked_add(user_reward)
@@ -65,3 +140,128 @@ pub fn update_rewards(
 
     Ok(())
 }
+
+/// Accrue `stream` and move what the user has earned from it into their
+/// checkpoint for the stream
+pub fn update_stream_rewards(
+    stream: &mut RewardStream,
+    user_state: &mut UserState,
+    total_staked_lp: u64,
+) -> Result<()> {
+    accrue_stream_rewards(stream, total_staked_lp)?;
+
+    let checkpoint = &mut user_state.reward_checkpoints[stream.index as usize];
+    let user_reward = earned(
+        user_state.lp_token_balance,
+        stream.cumulative_reward_per_token,
+        checkpoint.previous_cumulated_reward_per_token,
+    )?;
+
+    checkpoint.pending_rewards = checkpoint
+        .pending_rewards
+        .checked_add(user_reward)
+        .ok_or(VaultError::MathError)?;
+
+    checkpoint.previous_cumulated_reward_per_token = stream.cumulative_reward_per_token;
+
+    Ok(())
+}
+
+/// Update the user in every one of the pool's reward streams. Must run before
+/// the user's staked LP changes.
+///
+/// `reward_streams` holds every stream registered to `pool_state` (stored at
+/// `pool_key`), writable and in index order.
+pub fn update_all_stream_rewards<'info>(
+    pool_key: Pubkey,
+    pool_state: &PoolState,
+    user_state: &mut UserState,
+    reward_streams: &[AccountInfo<'info>],
+) -> Result<()> {
+    require!(
+        reward_streams.len() == pool_state.reward_stream_count as usize,
+        VaultError::InvalidRewardStreamAccounts
+    );
+
+    for (index, info) in reward_streams.iter().enumerate() {
+        let mut stream = load_reward_stream(info, pool_key)?;
+        require!(
+            stream.index as usize == index,
+            VaultError::InvalidRewardStreamAccounts
+        );
+
+        update_stream_rewards(&mut stream, user_state, pool_state.total_staked_lp)?;
+        store_reward_stream(info, &stream)?;
+    }
+
+    Ok(())
+}
+
+/// Deserialize a writable `RewardStream` of `pool_key` passed as a remaining account
+pub fn load_reward_stream(info: &AccountInfo, pool_key: Pubkey) -> Result<RewardStream> {
+    require_keys_eq!(
+        *info.owner,
+        crate::ID,
+        VaultError::InvalidRewardStreamAccounts
+    );
+    require!(info.is_writable, VaultError::InvalidRewardStreamAccounts);
+
+    let stream = {
+        let data = info.try_borrow_data()?;
+        RewardStream::try_deserialize(&mut &data[..])?
+    };
+    require_keys_eq!(
+        stream.pool_state,
+        pool_key,
+        VaultError::InvalidRewardStreamAccounts
+    );
+
+    Ok(stream)
+}
+
+/// Write a `RewardStream` loaded with `load_reward_stream` back to its account
+pub fn store_reward_stream(info: &AccountInfo, stream: &RewardStream) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    stream.try_serialize(&mut &mut data[..])
+}
+
+/// Emission rate for a new reward period of `duration` seconds funded with
+/// `amount`, plus everything the running period (ending at `reward_end_time`) has
+/// not emitted: what is left at its rate and the `reward_remainder` that rate was
+/// rounded down by. Returns the new rate, the amount rolled over and the new
+/// remainder.
+///
+/// Must run right after the period is accrued to `now`.
+pub fn next_reward_rate(
+    reward_end_time: u64,
+    tokens_per_interval: u64,
+    reward_remainder: u32,
+    now: u64,
+    amount: u64,
+    duration: u64,
+) -> Result<(u64, u64, u32)> {
+    let rolled_over = (reward_end_time.saturating_sub(now) as u128)
+        .checked_mul(tokens_per_interval as u128)
+        .ok_or(VaultError::MathError)?
+        .checked_add(reward_remainder as u128)
+        .ok_or(VaultError::MathError)?;
+
+    let funded = rolled_over
+        .checked_add(amount as u128)
+        .ok_or(VaultError::MathError)?;
+    let tokens_per_interval = funded
+        .checked_div(duration as u128)
+        .ok_or(VaultError::MathError)?;
+    require!(tokens_per_interval > 0, VaultError::RewardRateTooLow);
+
+    // Below `duration`, which is at most MAX_REWARD_DURATION
+    let reward_remainder = funded
+        .checked_rem(duration as u128)
+        .ok_or(VaultError::MathError)?;
+
+    Ok((
+        u64::try_from(tokens_per_interval).map_err(|_| error!(VaultError::MathError))?,
+        u64::try_from(rolled_over).map_err(|_| error!(VaultError::MathError))?,
+        u32::try_from(reward_remainder).map_err(|_| error!(VaultError::MathError))?,
+    ))
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use perp_amm::oracle::PriceRejection;
 
 #[error_code]
 pub enum ErrorCode {
@@ -64,6 +65,49 @@ pub enum ErrorCode {
     
     #[msg("Authority not found")]
     AuthorityNotFound,
+
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfig,
+
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+
+    #[msg("Oracle price is zero or negative")]
+    InvalidOraclePrice,
+
+    #[msg("Oracle confidence interval is too wide")]
+    OracleConfidenceTooWide,
+
+    #[msg("Account is already on the current layout version")]
+    AlreadyMigrated,
+
+    #[msg("Account data does not match a known layout")]
+    InvalidAccountLayout,
+
+    #[msg("Pool is not the one this market is bound to")]
+    InvalidPool,
+
+    #[msg("Roles must be a non-empty combination of known roles")]
+    InvalidRoles,
+
+    #[msg("Operation is paused")]
+    OperationPaused,
+
+    #[msg("Pause flags must be a non-empty combination of known operations")]
+    InvalidPauseFlags,
+
+    #[msg("Wallet is not on the allowlist")]
+    NotAllowlisted,
+}
+
+impl From<PriceRejection> for ErrorCode {
+    fn from(rejection: PriceRejection) -> Self {
+        match rejection {
+            PriceRejection::Stale => ErrorCode::StaleOraclePrice,
+            PriceRejection::NonPositive => ErrorCode::InvalidOraclePrice,
+            PriceRejection::ConfidenceTooWide => ErrorCode::OracleConfidenceTooWide,
+        }
+    }
 }
 
 // For backward compatibility with existing code
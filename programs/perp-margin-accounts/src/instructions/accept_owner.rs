@@ -0,0 +1,35 @@
+use crate::errors::ErrorCode;
+use crate::state::MarginVault;
+use crate::OwnerTransferred;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptOwner<'info> {
+    pub new_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump,
+        constraint = margin_vault.pending_owner == new_owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+}
+
+// Complete an ownership transfer started by `propose_owner`. Signed by the
+// proposed key, which proves it is controlled before it takes over.
+pub fn accept_owner(ctx: Context<AcceptOwner>) -> Result<()> {
+    let margin_vault = &mut ctx.accounts.margin_vault;
+
+    let previous_owner = margin_vault.owner;
+    margin_vault.owner = ctx.accounts.new_owner.key();
+    margin_vault.pending_owner = Pubkey::default();
+
+    msg!("Owner transferred to: {}", margin_vault.owner);
+    emit!(OwnerTransferred {
+        previous_owner,
+        new_owner: margin_vault.owner,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
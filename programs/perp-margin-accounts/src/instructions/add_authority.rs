@@ -1,6 +1,5 @@
 use crate::errors::ErrorCode;
 use crate::state::MarginVault;
-use crate::state::MAX_AUTHORITIES;
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -12,29 +11,22 @@ pub struct AddAuthority<'info> {
     pub margin_vault: Account<'info, MarginVault>,
 }
 
-pub fn add_authority(ctx: Context<AddAuthority>, new_authority: Pubkey) -> Result<()> {
+pub fn add_authority(ctx: Context<AddAuthority>, new_authority: Pubkey, roles: u8) -> Result<()> {
     let margin_vault = &mut ctx.accounts.margin_vault;
 
-    // Ensure only an existing authority can add new authorities
+    // Ensure only the owner can add new authorities
     require!(
-        margin_vault.is_authority(&ctx.accounts.authority.key()),
+        margin_vault.is_owner(&ctx.accounts.authority.key()),
         ErrorCode::Unauthorized
     );
 
-    // Check if the authority already exists in the list
-    if margin_vault.authorities.iter().any(|a| *a == new_authority) {
-        return Err(ErrorCode::AuthorityAlreadyExists.into());
-    }
+    // Add the new authority (rejects duplicates, a full list and unknown roles)
+    margin_vault.add_authority(new_authority, roles)?;
 
-    // Check if we've reached the maximum number of authorities
-    require!(
-        margin_vault.authorities.len() < MAX_AUTHORITIES,
-        ErrorCode::MaxAuthoritiesReached
+    msg!(
+        "Added new authority: {} with roles {:#b}",
+        new_authority,
+        roles
     );
-
-    // Add the new authority
-    margin_vault.authorities.push(new_authority);
-
-    msg!("Added new authority: {}", new_authority);
     Ok(())
 }
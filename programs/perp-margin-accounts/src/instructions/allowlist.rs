@@ -0,0 +1,116 @@
+use crate::errors::ErrorCode;
+use crate::state::{AllowlistEntry, MarginVault};
+use crate::{
+    AllowlistEntryAdded, AllowlistEntryRemoved, AllowlistRootUpdated, PermissionedModeUpdated,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetAllowlist<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump,
+        constraint = margin_vault.is_owner(&owner.key()) @ ErrorCode::Unauthorized
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddAllowlistEntry<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump,
+        constraint = margin_vault.is_owner(&owner.key()) @ ErrorCode::Unauthorized
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + AllowlistEntry::INIT_SPACE,
+        seeds = [b"allowlist", wallet.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAllowlistEntry<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump,
+        constraint = margin_vault.is_owner(&owner.key()) @ ErrorCode::Unauthorized
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+
+    #[account(
+        mut,
+        seeds = [b"allowlist", allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+        close = owner
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+// Replace the merkle root of approved wallets. Proofs against the old root
+// stop working straight away; allowlist entries are unaffected.
+pub fn set_allowlist_root(ctx: Context<SetAllowlist>, root: [u8; 32]) -> Result<()> {
+    ctx.accounts.margin_vault.allowlist_root = root;
+
+    emit!(AllowlistRootUpdated {
+        owner: ctx.accounts.owner.key(),
+        root,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Turn permissioned mode on or off. While it is on, only wallets with an
+// allowlist entry or a valid proof can deposit margin; withdrawals are never gated.
+pub fn set_permissioned(ctx: Context<SetAllowlist>, enabled: bool) -> Result<()> {
+    ctx.accounts.margin_vault.permissioned = enabled;
+
+    emit!(PermissionedModeUpdated {
+        owner: ctx.accounts.owner.key(),
+        enabled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Approve a single wallet without rotating the merkle root
+pub fn add_allowlist_entry(ctx: Context<AddAllowlistEntry>, wallet: Pubkey) -> Result<()> {
+    let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+    allowlist_entry.wallet = wallet;
+    allowlist_entry.bump = ctx.bumps.allowlist_entry;
+
+    emit!(AllowlistEntryAdded {
+        owner: ctx.accounts.owner.key(),
+        wallet,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Revoke a wallet's allowlist entry, returning its rent to the owner.
+// Existing margin stays where it is and can still be withdrawn.
+pub fn remove_allowlist_entry(ctx: Context<RemoveAllowlistEntry>) -> Result<()> {
+    emit!(AllowlistEntryRemoved {
+        owner: ctx.accounts.owner.key(),
+        wallet: ctx.accounts.allowlist_entry.wallet,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
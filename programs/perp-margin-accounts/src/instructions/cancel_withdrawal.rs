@@ -1,6 +1,8 @@
 use crate::errors::MarginError;
 use crate::state::{MarginAccount, MarginVault};
+use crate::WithdrawalCancelled;
 use anchor_lang::prelude::*;
+use perp_amm::state::{pause_flags, roles};
 
 #[derive(Accounts)]
 pub struct CancelWithdrawal<'info> {
@@ -14,12 +16,13 @@ pub struct CancelWithdrawal<'info> {
 
     #[account(
         seeds = [b"margin_vault"],
-        bump = margin_vault.bump
+        bump = margin_vault.bump,
+        constraint = !margin_vault.is_paused(pause_flags::WITHDRAWALS) @ MarginError::OperationPaused
     )]
     pub margin_vault: Account<'info, MarginVault>,
 
     #[account(
-        constraint = authority.key() == margin_account.owner || margin_vault.authorities.contains(&authority.key()) @ MarginError::UnauthorizedExecution
+        constraint = authority.key() == margin_account.owner || margin_vault.has_role(&authority.key(), roles::SETTLEMENT) @ MarginError::UnauthorizedExecution
     )]
     pub authority: Signer<'info>,
 }
@@ -27,9 +30,19 @@ pub struct CancelWithdrawal<'info> {
 pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
     let margin_account = &mut ctx.accounts.margin_account;
 
+    emit!(WithdrawalCancelled {
+        owner: margin_account.owner,
+        margin_account: margin_account.key(),
+        cancelled_by: ctx.accounts.authority.key(),
+        sol_amount: margin_account.pending_sol_withdrawal,
+        usdc_amount: margin_account.pending_usdc_withdrawal,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     // Clear pending withdrawals
     margin_account.pending_sol_withdrawal = 0;
     margin_account.pending_usdc_withdrawal = 0;
+    margin_account.unwrap_sol_withdrawal = false;
 
     Ok(())
 }
@@ -1,7 +1,9 @@
 use crate::errors::MarginError;
 use crate::state::MarginVault;
+use crate::MarginFeesClaimed;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perp_amm::state::{pause_flags, roles};
 
 #[derive(Accounts)]
 pub struct ClaimFees<'info> {
@@ -9,7 +11,8 @@ pub struct ClaimFees<'info> {
         mut,
         seeds = [b"margin_vault"],
         bump = margin_vault.bump,
-        constraint = margin_vault.authorities.contains(&authority.key()) @ MarginError::UnauthorizedExecution,
+        constraint = margin_vault.has_role(&authority.key(), roles::FEE_COLLECTOR) @ MarginError::UnauthorizedExecution,
+        constraint = !margin_vault.is_paused(pause_flags::SETTLEMENTS) @ MarginError::OperationPaused,
     )]
     pub margin_vault: Account<'info, MarginVault>,
 
@@ -45,10 +48,11 @@ pub struct ClaimFees<'info> {
 
 pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
     let margin_vault = &mut ctx.accounts.margin_vault;
+    let sol_fees = margin_vault.sol_fees_accumulated;
+    let usdc_fees = margin_vault.usdc_fees_accumulated;
 
     // Claim accumulated SOL fees if any
-    if margin_vault.sol_fees_accumulated > 0 {
-        let sol_fees = margin_vault.sol_fees_accumulated;
+    if sol_fees > 0 {
         let cpi_accounts = Transfer {
             from: ctx.accounts.margin_sol_vault.to_account_info(),
             to: ctx.accounts.admin_sol_account.to_account_info(),
@@ -67,8 +71,7 @@ pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
     }
 
     // Claim accumulated USDC fees if any
-    if margin_vault.usdc_fees_accumulated > 0 {
-        let usdc_fees = margin_vault.usdc_fees_accumulated;
+    if usdc_fees > 0 {
         let cpi_accounts = Transfer {
             from: ctx.accounts.margin_usdc_vault.to_account_info(),
             to: ctx.accounts.admin_usdc_account.to_account_info(),
@@ -85,5 +88,13 @@ pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
         token::transfer(cpi_ctx, usdc_fees)?;
         margin_vault.usdc_fees_accumulated = 0;
     }
+
+    emit!(MarginFeesClaimed {
+        authority: ctx.accounts.authority.key(),
+        sol_amount: sol_fees,
+        usdc_amount: usdc_fees,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -1,7 +1,9 @@
 use crate::errors::MarginError;
-use crate::state::{MarginAccount, MarginVault};
+use crate::state::{AllowlistEntry, MarginAccount, MarginVault, MARGIN_ACCOUNT_VERSION};
+use crate::MarginDeposited;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perp_amm::state::pause_flags;
 
 #[derive(Accounts)]
 pub struct DepositMargin<'info> {
@@ -17,7 +19,8 @@ pub struct DepositMargin<'info> {
 
     #[account(
         seeds = [b"margin_vault"],
-        bump = margin_vault.bump
+        bump = margin_vault.bump,
+        constraint = !margin_vault.is_paused(pause_flags::DEPOSITS) @ MarginError::OperationPaused
     )]
     pub margin_vault: Account<'info, MarginVault>,
 
@@ -36,21 +39,39 @@ pub struct DepositMargin<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// The owner's allowlist entry; only needed in permissioned mode without a proof
+    #[account(
+        seeds = [b"allowlist", owner.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn deposit_margin(ctx: Context<DepositMargin>, amount: u64) -> Result<()> {
+pub fn deposit_margin(
+    ctx: Context<DepositMargin>,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
     let margin_account = &mut ctx.accounts.margin_account;
 
     if amount == 0 {
         return Err(MarginError::ZeroDepositAmount.into());
     }
 
+    ctx.accounts.margin_vault.check_allowlisted(
+        &ctx.accounts.owner.key(),
+        &proof,
+        ctx.accounts.allowlist_entry.is_some(),
+    )?;
+
     // Initialize margin account if new
     if margin_account.owner == Pubkey::default() {
         margin_account.owner = ctx.accounts.owner.key();
         margin_account.bump = ctx.bumps.margin_account;
+        margin_account.version = MARGIN_ACCOUNT_VERSION;
     }
 
     // Transfer tokens to vault
@@ -78,5 +99,13 @@ pub fn deposit_margin(ctx: Context<DepositMargin>, amount: u64) -> Result<()> {
             .ok_or(MarginError::ArithmeticOverflow)?;
     }
 
+    emit!(MarginDeposited {
+        owner: ctx.accounts.owner.key(),
+        margin_account: margin_account.key(),
+        mint: ctx.accounts.vault_token_account.mint,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -0,0 +1,102 @@
+use crate::errors::MarginError;
+use crate::state::{AllowlistEntry, MarginAccount, MarginVault, MARGIN_ACCOUNT_VERSION};
+use crate::MarginDeposited;
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{self, SyncNative, Token, TokenAccount};
+use perp_amm::state::pause_flags;
+
+#[derive(Accounts)]
+pub struct DepositMarginSol<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = MarginAccount::LEN,
+        seeds = [b"margin_account", owner.key().as_ref()],
+        bump,
+        constraint = margin_account.owner == owner.key() || margin_account.owner == Pubkey::default()
+    )]
+    pub margin_account: Account<'info, MarginAccount>,
+
+    #[account(
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump,
+        constraint = !margin_vault.is_paused(pause_flags::DEPOSITS) @ MarginError::OperationPaused
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+
+    #[account(
+        mut,
+        constraint = margin_sol_vault.key() == margin_vault.margin_sol_vault
+    )]
+    pub margin_sol_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The owner's allowlist entry; only needed in permissioned mode without a proof
+    #[account(
+        seeds = [b"allowlist", owner.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Deposit native SOL, wrapped directly into the SOL margin vault.
+pub fn deposit_margin_sol(
+    ctx: Context<DepositMarginSol>,
+    lamports: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let margin_account = &mut ctx.accounts.margin_account;
+
+    if lamports == 0 {
+        return Err(MarginError::ZeroDepositAmount.into());
+    }
+
+    ctx.accounts.margin_vault.check_allowlisted(
+        &ctx.accounts.owner.key(),
+        &proof,
+        ctx.accounts.allowlist_entry.is_some(),
+    )?;
+
+    // Initialize margin account if new
+    if margin_account.owner == Pubkey::default() {
+        margin_account.owner = ctx.accounts.owner.key();
+        margin_account.bump = ctx.bumps.margin_account;
+        margin_account.version = MARGIN_ACCOUNT_VERSION;
+    }
+
+    // Move the lamports into the vault
+    let cpi_accounts = system_program::Transfer {
+        from: ctx.accounts.owner.to_account_info(),
+        to: ctx.accounts.margin_sol_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+    system_program::transfer(cpi_ctx, lamports)?;
+
+    // Sync the vault's wrapped balance with its lamports
+    let cpi_accounts = SyncNative {
+        account: ctx.accounts.margin_sol_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::sync_native(cpi_ctx)?;
+
+    // Update margin account balance
+    margin_account.sol_balance = margin_account
+        .sol_balance
+        .checked_add(lamports)
+        .ok_or(MarginError::ArithmeticOverflow)?;
+
+    emit!(MarginDeposited {
+        owner: ctx.accounts.owner.key(),
+        margin_account: margin_account.key(),
+        mint: ctx.accounts.margin_sol_vault.mint,
+        amount: lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
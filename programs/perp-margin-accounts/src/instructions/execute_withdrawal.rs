@@ -3,9 +3,16 @@ use crate::state::{MarginAccount, MarginVault};
 use crate::util::fees::process_fees;
 use crate::util::pnl::process_pnl_update;
 use crate::util::validate::validate_balances;
-use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use perp_amm::{program::PerpAmm, state::PoolState};
+use crate::WithdrawalExecuted;
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{
+    self, spl_token::native_mint, CloseAccount, InitializeAccount3, Mint, Token, TokenAccount,
+    Transfer,
+};
+use perp_amm::{
+    program::PerpAmm,
+    state::{pause_flags, roles, AssetConfig, PoolState},
+};
 
 #[derive(Accounts)]
 pub struct ExecuteWithdrawal<'info> {
@@ -20,7 +27,8 @@ pub struct ExecuteWithdrawal<'info> {
     #[account(
         seeds = [b"margin_vault"],
         bump = margin_vault.bump,
-        constraint = margin_vault.authorities.contains(&authority.key()) @ MarginError::InvalidAuthority
+        constraint = margin_vault.has_role(&authority.key(), roles::SETTLEMENT) @ MarginError::InvalidAuthority,
+        constraint = !margin_vault.is_paused(pause_flags::SETTLEMENTS) @ MarginError::OperationPaused
     )]
     pub margin_vault: Account<'info, MarginVault>,
 
@@ -55,27 +63,64 @@ pub struct ExecuteWithdrawal<'info> {
     )]
     pub authority_token_account: Account<'info, TokenAccount>,
 
-    /// The liquidity pool's state account
-    #[account(mut)]
-    pub pool_state: Account<'info, PoolState>,
+    /// The liquidity pool's state account, which must be the pool this market is bound to
+    #[account(mut, address = margin_vault.pool_state @ MarginError::InvalidPool)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The liquidity pool's registry entry for the settlement asset (SOL or USDC)
+    #[account(
+        mut,
+        constraint = pool_asset.pool_state == pool_state.key(),
+        constraint = pool_asset.mint == margin_sol_vault.mint || pool_asset.mint == margin_usdc_vault.mint
+    )]
+    pub pool_asset: Account<'info, AssetConfig>,
 
     /// The liquidity pool's vault account that matches the token being withdrawn
     #[account(
         mut,
-        constraint = pool_vault_account.key() == pool_state.sol_vault || pool_vault_account.key() == pool_state.usdc_vault
+        constraint = pool_vault_account.key() == pool_asset.vault
     )]
     pub pool_vault_account: Account<'info, TokenAccount>,
 
+    /// CHECK: The liquidity pool's outflow limits, which cap positive PnL paid out
+    /// per window. Passed through to `admin_withdraw`, which treats an account that
+    /// was never created as no limits until the pool is flagged as having them.
+    #[account(
+        mut,
+        seeds = [b"outflow_limits".as_ref(), pool_state.key().as_ref()],
+        bump,
+        seeds::program = liquidity_pool_program.key()
+    )]
+    pub pool_outflow_limits: UncheckedAccount<'info>,
+
     /// CHECK: Validated in constraint against stored value in margin vault
-    #[account(address = margin_vault.chainlink_program)]
-    pub chainlink_program: AccountInfo<'info>,
+    #[account(address = margin_vault.oracle_program)]
+    pub oracle_program: AccountInfo<'info>,
 
     /// CHECK: Validated in constraint against stored value in margin vault
-    #[account(address = margin_vault.chainlink_feed)]
-    pub chainlink_feed: AccountInfo<'info>,
+    #[account(address = margin_vault.oracle_feed)]
+    pub oracle_feed: AccountInfo<'info>,
+
+    /// The margin account owner's wallet, paid directly for native SOL withdrawals
+    #[account(mut, address = margin_account.owner @ MarginError::InvalidOwner)]
+    pub owner: SystemAccount<'info>,
 
+    /// CHECK: Temporary wrapped SOL account used to unwrap native SOL withdrawals;
+    /// created and closed within the instruction
     #[account(
-        constraint = margin_vault.authorities.contains(&authority.key()) @ MarginError::UnauthorizedExecution
+        mut,
+        seeds = [b"sol_unwrap", margin_account.key().as_ref()],
+        bump
+    )]
+    pub sol_unwrap_account: UncheckedAccount<'info>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Box<Account<'info, Mint>>,
+
+    /// Pays the temporary unwrap account's rent, which is refunded when it closes
+    #[account(
+        mut,
+        constraint = margin_vault.has_role(&authority.key(), roles::SETTLEMENT) @ MarginError::UnauthorizedExecution
     )]
     pub authority: Signer<'info>,
 
@@ -111,40 +156,59 @@ pub fn execute_withdrawal(
     validate_balances(&ctx.accounts.margin_account, locked_sol, locked_usdc)?;
 
     // Process withdrawals
+    let margin_account = &ctx.accounts.margin_account;
+    let event = WithdrawalExecuted {
+        owner: margin_account.owner,
+        margin_account: margin_account.key(),
+        authority: ctx.accounts.authority.key(),
+        sol_amount: margin_account.pending_sol_withdrawal,
+        usdc_amount: margin_account.pending_usdc_withdrawal,
+        unwrap_sol: margin_account.unwrap_sol_withdrawal,
+        pnl_update,
+        sol_fees_owed,
+        usdc_fees_owed,
+        timestamp: Clock::get()?.unix_timestamp,
+    };
     process_withdrawals(&mut ctx)?;
 
+    emit!(event);
+
     Ok(())
 }
 
 // Helper function to process withdrawals
 fn process_withdrawals(ctx: &mut Context<ExecuteWithdrawal>) -> Result<()> {
-    let margin_account = &mut ctx.accounts.margin_account;
-
     // Process SOL withdrawal if pending.
-    if margin_account.pending_sol_withdrawal > 0 {
+    if ctx.accounts.margin_account.pending_sol_withdrawal > 0 {
+        let margin_account = &mut ctx.accounts.margin_account;
         let sol_amount = margin_account.pending_sol_withdrawal;
         margin_account.sol_balance = margin_account
             .sol_balance
             .checked_sub(sol_amount)
             .ok_or(MarginError::ArithmeticOverflow)?;
 
-        let seeds = &[b"margin_vault".as_ref(), &[ctx.accounts.margin_vault.bump]];
-        let signer = &[&seeds[..]];
+        if margin_account.unwrap_sol_withdrawal {
+            unwrap_sol_withdrawal(ctx, sol_amount)?;
+        } else {
+            let seeds = &[b"margin_vault".as_ref(), &[ctx.accounts.margin_vault.bump]];
+            let signer = &[&seeds[..]];
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.margin_sol_vault.to_account_info(),
-            to: ctx.accounts.user_sol_account.to_account_info(),
-            authority: ctx.accounts.margin_vault.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            cpi_accounts,
-            signer,
-        );
-        token::transfer(cpi_ctx, sol_amount)?;
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.margin_sol_vault.to_account_info(),
+                to: ctx.accounts.user_sol_account.to_account_info(),
+                authority: ctx.accounts.margin_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            );
+            token::transfer(cpi_ctx, sol_amount)?;
+        }
     }
 
     // Process USDC withdrawal if pending.
+    let margin_account = &mut ctx.accounts.margin_account;
     if margin_account.pending_usdc_withdrawal > 0 {
         let usdc_amount = margin_account.pending_usdc_withdrawal;
         margin_account.usdc_balance = margin_account
@@ -171,6 +235,98 @@ fn process_withdrawals(ctx: &mut Context<ExecuteWithdrawal>) -> Result<()> {
     // Clear pending withdrawals.
     margin_account.pending_sol_withdrawal = 0;
     margin_account.pending_usdc_withdrawal = 0;
+    margin_account.unwrap_sol_withdrawal = false;
 
     Ok(())
 }
+
+// Pay a SOL withdrawal to the owner's wallet as native SOL. The wrapped SOL is moved
+// into a temporary token account which is closed to the authority, who forwards the
+// withdrawn lamports to the owner and keeps the rent they fronted, along with anything
+// else that had been sent to the temporary account's address.
+fn unwrap_sol_withdrawal(ctx: &Context<ExecuteWithdrawal>, sol_amount: u64) -> Result<()> {
+    let vault_seeds = &[b"margin_vault".as_ref(), &[ctx.accounts.margin_vault.bump]];
+    let margin_account_key = ctx.accounts.margin_account.key();
+    let unwrap_seeds = &[
+        b"sol_unwrap".as_ref(),
+        margin_account_key.as_ref(),
+        &[ctx.bumps.sol_unwrap_account],
+    ];
+
+    // Create the temporary account, with the authority topping up its rent. This is
+    // done as transfer/allocate/assign rather than create_account, which fails if
+    // anyone has already sent lamports to the address.
+    let unwrap_info = ctx.accounts.sol_unwrap_account.to_account_info();
+    let rent_shortfall = Rent::get()?
+        .minimum_balance(TokenAccount::LEN)
+        .saturating_sub(unwrap_info.lamports());
+    if rent_shortfall > 0 {
+        let cpi_accounts = system_program::Transfer {
+            from: ctx.accounts.authority.to_account_info(),
+            to: unwrap_info.clone(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        system_program::transfer(cpi_ctx, rent_shortfall)?;
+    }
+
+    let cpi_accounts = system_program::Allocate {
+        account_to_allocate: unwrap_info.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.system_program.to_account_info(),
+        cpi_accounts,
+        &[&unwrap_seeds[..]],
+    );
+    system_program::allocate(cpi_ctx, TokenAccount::LEN as u64)?;
+
+    let cpi_accounts = system_program::Assign {
+        account_to_assign: unwrap_info,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.system_program.to_account_info(),
+        cpi_accounts,
+        &[&unwrap_seeds[..]],
+    );
+    system_program::assign(cpi_ctx, &ctx.accounts.token_program.key())?;
+
+    let cpi_accounts = InitializeAccount3 {
+        account: ctx.accounts.sol_unwrap_account.to_account_info(),
+        mint: ctx.accounts.native_mint.to_account_info(),
+        authority: ctx.accounts.margin_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::initialize_account3(cpi_ctx)?;
+
+    // Move the withdrawal into it and close it, unwrapping everything to the authority
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.margin_sol_vault.to_account_info(),
+        to: ctx.accounts.sol_unwrap_account.to_account_info(),
+        authority: ctx.accounts.margin_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        &[&vault_seeds[..]],
+    );
+    token::transfer(cpi_ctx, sol_amount)?;
+
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.sol_unwrap_account.to_account_info(),
+        destination: ctx.accounts.authority.to_account_info(),
+        authority: ctx.accounts.margin_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        &[&vault_seeds[..]],
+    );
+    token::close_account(cpi_ctx)?;
+
+    // Forward the withdrawn lamports to the owner
+    let cpi_accounts = system_program::Transfer {
+        from: ctx.accounts.authority.to_account_info(),
+        to: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+    system_program::transfer(cpi_ctx, sol_amount)
+}
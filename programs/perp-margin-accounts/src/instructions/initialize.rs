@@ -1,6 +1,11 @@
-use crate::state::MarginVault;
+use crate::errors::MarginError;
+use crate::state::{MarginVault, MARGIN_VAULT_VERSION, MAX_AUTHORITIES};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
+use perp_amm::{
+    oracle::OracleKind,
+    state::{roles, PoolState},
+};
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -23,6 +28,9 @@ pub struct Initialize<'info> {
     )]
     pub margin_usdc_vault: Account<'info, TokenAccount>,
 
+    /// Liquidity pool the market settles against
+    pub pool_state: AccountLoader<'info, PoolState>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -34,22 +42,41 @@ pub struct Initialize<'info> {
 pub fn initialize(
     ctx: Context<Initialize>,
     withdrawal_timelock: i64,
-    chainlink_program: Pubkey,
-    chainlink_feed: Pubkey,
+    oracle_kind: OracleKind,
+    oracle_program: Pubkey,
+    oracle_feed: Pubkey,
+    oracle_feed_id: [u8; 32],
+    max_price_age: u64,
+    max_confidence_bps: u16,
 ) -> Result<()> {
+    require!(max_price_age > 0, MarginError::InvalidOracleConfig);
+
     let margin_vault = &mut ctx.accounts.margin_vault;
 
     margin_vault.margin_sol_vault = ctx.accounts.margin_sol_vault.key();
     margin_vault.margin_usdc_vault = ctx.accounts.margin_usdc_vault.key();
 
-    // Initialize authorities with the signer as the first authority
+    // Initialize authorities with the signer as the first authority, holding every role
     let mut authorities = Vec::new();
     authorities.push(ctx.accounts.authority.key());
     margin_vault.authorities = authorities;
+    margin_vault.authority_roles = [0; MAX_AUTHORITIES];
+    margin_vault.authority_roles[0] = roles::ALL;
     margin_vault.withdrawal_timelock = withdrawal_timelock;
     margin_vault.bump = ctx.bumps.margin_vault;
-    margin_vault.chainlink_program = chainlink_program;
-    margin_vault.chainlink_feed = chainlink_feed;
+    margin_vault.oracle_kind = oracle_kind;
+    margin_vault.oracle_program = oracle_program;
+    margin_vault.oracle_feed = oracle_feed;
+    margin_vault.oracle_feed_id = oracle_feed_id;
+    margin_vault.max_price_age = max_price_age;
+    margin_vault.max_confidence_bps = max_confidence_bps;
+    margin_vault.version = MARGIN_VAULT_VERSION;
+    margin_vault.pool_state = ctx.accounts.pool_state.key();
+    margin_vault.owner = ctx.accounts.authority.key();
+    margin_vault.pending_owner = Pubkey::default();
+    margin_vault.paused = 0;
+    margin_vault.allowlist_root = [0; 32];
+    margin_vault.permissioned = false;
 
     Ok(())
 }
@@ -1,8 +1,13 @@
 use crate::errors::MarginError;
 use crate::state::{MarginAccount, MarginVault};
+use crate::MarginAccountLiquidated;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
-use perp_amm::{cpi::direct_deposit, program::PerpAmm, state::PoolState};
+use perp_amm::{
+    cpi::direct_deposit,
+    program::PerpAmm,
+    state::{pause_flags, roles, AssetConfig, PoolState},
+};
 
 #[derive(Accounts)]
 pub struct LiquidateMarginAccount<'info> {
@@ -16,7 +21,8 @@ pub struct LiquidateMarginAccount<'info> {
     #[account(
         seeds = [b"margin_vault"],
         bump = margin_vault.bump,
-        constraint = margin_vault.authorities.contains(&authority.key()) @ MarginError::InvalidAuthority
+        constraint = margin_vault.has_role(&authority.key(), roles::LIQUIDATOR) @ MarginError::InvalidAuthority,
+        constraint = !margin_vault.is_paused(pause_flags::LIQUIDATIONS) @ MarginError::OperationPaused
     )]
     pub margin_vault: Account<'info, MarginVault>,
 
@@ -26,27 +32,27 @@ pub struct LiquidateMarginAccount<'info> {
     )]
     pub margin_vault_token_account: Account<'info, TokenAccount>,
 
-    /// The liquidity pool's state account
-    #[account(mut)]
-    pub pool_state: Account<'info, PoolState>,
+    /// The liquidity pool's state account, which must be the pool this market is bound to
+    #[account(mut, address = margin_vault.pool_state @ MarginError::InvalidPool)]
+    pub pool_state: AccountLoader<'info, PoolState>,
 
-    /// The liquidity pool's vault account that matches the token being withdrawn
+    /// The liquidity pool's registry entry for the asset being liquidated
     #[account(
         mut,
-        constraint = pool_vault_account.key() == pool_state.sol_vault || pool_vault_account.key() == pool_state.usdc_vault
+        constraint = pool_asset.pool_state == pool_state.key(),
+        constraint = pool_asset.mint == margin_vault_token_account.mint
     )]
-    pub pool_vault_account: Account<'info, TokenAccount>,
-
-    /// CHECK: Validated in constraint against stored value in margin vault
-    #[account(address = margin_vault.chainlink_program)]
-    pub chainlink_program: AccountInfo<'info>,
+    pub pool_asset: Account<'info, AssetConfig>,
 
-    /// CHECK: Validated in constraint against stored value in margin vault
-    #[account(address = margin_vault.chainlink_feed)]
-    pub chainlink_feed: AccountInfo<'info>,
+    /// The liquidity pool's vault account that matches the token being liquidated
+    #[account(
+        mut,
+        constraint = pool_vault_account.key() == pool_asset.vault
+    )]
+    pub pool_vault_account: Account<'info, TokenAccount>,
 
     #[account(
-        constraint = margin_vault.authorities.contains(&authority.key()) @ MarginError::UnauthorizedLiquidation
+        constraint = margin_vault.has_role(&authority.key(), roles::LIQUIDATOR) @ MarginError::UnauthorizedLiquidation
     )]
     pub authority: Signer<'info>,
     pub token_program: Program<'info, Token>,
@@ -94,10 +100,9 @@ pub fn liquidate_margin_account(ctx: Context<LiquidateMarginAccount>) -> Result<
             perp_amm::cpi::accounts::DirectDeposit {
                 depositor: ctx.accounts.margin_vault.to_account_info(),
                 pool_state: ctx.accounts.pool_state.to_account_info(),
+                asset: ctx.accounts.pool_asset.to_account_info(),
                 depositor_token_account: ctx.accounts.margin_vault_token_account.to_account_info(),
                 vault_account: ctx.accounts.pool_vault_account.to_account_info(),
-                chainlink_program: ctx.accounts.chainlink_program.to_account_info(),
-                chainlink_feed: ctx.accounts.chainlink_feed.to_account_info(),
                 token_program: ctx.accounts.token_program.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
             },
@@ -107,5 +112,14 @@ pub fn liquidate_margin_account(ctx: Context<LiquidateMarginAccount>) -> Result<
         direct_deposit(cpi_ctx, current_balance)?;
     }
 
+    emit!(MarginAccountLiquidated {
+        owner: ctx.accounts.margin_account.owner,
+        margin_account: ctx.accounts.margin_account.key(),
+        authority: ctx.accounts.authority.key(),
+        mint: ctx.accounts.margin_vault_token_account.mint,
+        amount: current_balance,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
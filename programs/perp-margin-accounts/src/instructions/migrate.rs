@@ -0,0 +1,197 @@
+use crate::errors::MarginError;
+use crate::state::*;
+use anchor_lang::{prelude::*, system_program, Discriminator};
+use perp_amm::{oracle::OracleKind, state::roles};
+
+#[derive(Accounts)]
+pub struct MigrateMarginVault<'info> {
+    /// The vault's first authority, which becomes its owner; pays the extra rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Still holds the legacy layout, so it can't be deserialized by Anchor.
+    /// Owner, discriminator and authority are checked in the handler.
+    #[account(mut, seeds = [b"margin_vault"], bump)]
+    pub margin_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMarginAccount<'info> {
+    /// Pays for the extra rent; migration doesn't touch balances, so anyone may run it
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: May still hold a legacy layout, so it can't be deserialized by Anchor.
+    /// Owner and discriminator are checked in the handler.
+    #[account(mut)]
+    pub margin_account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Upgrade the margin vault from the layout it was first deployed with, growing
+// it to the current size. Fields that layout didn't have start at their
+// defaults: every authority holds all roles, nothing is paused, deposits are
+// permissionless and the vault is bound to no pool until `set_pool`. The
+// Chainlink feed is kept, with the staleness and confidence bounds it never had
+// given here. The vault's first authority, which initialized it, runs the
+// migration and becomes the owner; it can hand that on with `propose_owner`.
+pub fn migrate_margin_vault(
+    ctx: Context<MigrateMarginVault>,
+    max_price_age: u64,
+    max_confidence_bps: u16,
+) -> Result<()> {
+    require!(max_price_age > 0, MarginError::InvalidOracleConfig);
+    let vault_info = ctx.accounts.margin_vault.to_account_info();
+
+    let resized = resize_legacy_account(
+        &vault_info,
+        MarginVault::DISCRIMINATOR,
+        MarginVaultV0::MAX_LEN,
+        MarginVault::MAX_LEN,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+    )?;
+    require!(resized, MarginError::AlreadyMigrated);
+
+    // Growing the account leaves the legacy fields where they were
+    let legacy = MarginVaultV0::deserialize(&mut &vault_info.try_borrow_data()?[8..])
+        .map_err(|_| error!(MarginError::InvalidAccountLayout))?;
+    // The layout had no owner; only the authority that initialized the vault,
+    // always listed first, may claim it
+    require!(
+        legacy.authorities.first() == Some(&ctx.accounts.authority.key()),
+        MarginError::UnauthorizedExecution
+    );
+    require!(
+        legacy.authorities.len() <= MAX_AUTHORITIES,
+        MarginError::MaxAuthoritiesReached
+    );
+
+    let mut authority_roles = [0; MAX_AUTHORITIES];
+    authority_roles[..legacy.authorities.len()].fill(roles::ALL);
+
+    let margin_vault = MarginVault {
+        margin_sol_vault: legacy.margin_sol_vault,
+        margin_usdc_vault: legacy.margin_usdc_vault,
+        authorities: legacy.authorities,
+        withdrawal_timelock: legacy.withdrawal_timelock,
+        bump: legacy.bump,
+        sol_fees_accumulated: legacy.sol_fees_accumulated,
+        usdc_fees_accumulated: legacy.usdc_fees_accumulated,
+        oracle_kind: OracleKind::Chainlink,
+        oracle_program: legacy.chainlink_program,
+        oracle_feed: legacy.chainlink_feed,
+        max_price_age,
+        max_confidence_bps,
+        version: MARGIN_VAULT_VERSION,
+        pool_state: Pubkey::default(),
+        owner: ctx.accounts.authority.key(),
+        pending_owner: Pubkey::default(),
+        authority_roles,
+        paused: 0,
+        allowlist_root: [0; 32],
+        permissioned: false,
+        oracle_feed_id: [0; 32],
+        reserved: [0; 21],
+    };
+    margin_vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrated margin vault from the legacy layout to version {}",
+        MARGIN_VAULT_VERSION
+    );
+    Ok(())
+}
+
+// Upgrade a margin account to the current layout version, resizing it if it
+// predates the `unwrap_sol_withdrawal`, `version` and `reserved` fields.
+pub fn migrate_margin_account(ctx: Context<MigrateMarginAccount>) -> Result<()> {
+    let account_info = ctx.accounts.margin_account.to_account_info();
+
+    resize_legacy_account(
+        &account_info,
+        MarginAccount::DISCRIMINATOR,
+        MarginAccount::LEGACY_LEN,
+        MarginAccount::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    // The zeroed tail of a resized account reads back as version 0
+    let mut margin_account =
+        MarginAccount::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+    let (expected_pda, _) = Pubkey::find_program_address(
+        &[b"margin_account", margin_account.owner.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(
+        expected_pda,
+        account_info.key(),
+        MarginError::InvalidAccountLayout
+    );
+    require!(
+        margin_account.version < MARGIN_ACCOUNT_VERSION,
+        MarginError::AlreadyMigrated
+    );
+
+    let from_version = margin_account.version;
+    margin_account.version = MARGIN_ACCOUNT_VERSION;
+    margin_account.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrated margin account {} from version {} to {}",
+        account_info.key(),
+        from_version,
+        MARGIN_ACCOUNT_VERSION
+    );
+    Ok(())
+}
+
+// Check `info` is one of this program's accounts of the given type and, if it
+// still has its legacy size, grow it to `current_len` with `payer` topping up rent.
+// New bytes are zeroed. Returns whether the account was grown.
+fn resize_legacy_account<'info>(
+    info: &AccountInfo<'info>,
+    discriminator: [u8; 8],
+    legacy_len: usize,
+    current_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<bool> {
+    require_keys_eq!(*info.owner, crate::ID, MarginError::InvalidAccountLayout);
+
+    let data_len = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == discriminator,
+            MarginError::InvalidAccountLayout
+        );
+        data.len()
+    };
+
+    if data_len == current_len {
+        return Ok(false);
+    }
+    require!(data_len == legacy_len, MarginError::InvalidAccountLayout);
+
+    let required = Rent::get()?.minimum_balance(current_len);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+
+    info.realloc(current_len, true)?;
+    Ok(true)
+}
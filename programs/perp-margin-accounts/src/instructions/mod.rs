@@ -1,19 +1,37 @@
+pub mod accept_owner;
 pub mod add_authority;
+pub mod allowlist;
 pub mod cancel_withdrawal;
 pub mod claim_fees;
 pub mod deposit;
+pub mod deposit_sol;
 pub mod execute_withdrawal;
 pub mod initialize;
 pub mod liquidate;
+pub mod migrate;
+pub mod pause;
+pub mod propose_owner;
 pub mod remove_authority;
 pub mod request_withdrawal;
+pub mod set_authority_roles;
+pub mod set_oracle;
+pub mod set_pool;
 
+pub use accept_owner::*;
 pub use add_authority::*;
+pub use allowlist::*;
 pub use cancel_withdrawal::*;
 pub use claim_fees::*;
 pub use deposit::*;
+pub use deposit_sol::*;
 pub use execute_withdrawal::*;
 pub use initialize::*;
 pub use liquidate::*;
+pub use migrate::*;
+pub use pause::*;
+pub use propose_owner::*;
 pub use remove_authority::*;
 pub use request_withdrawal::*;
+pub use set_authority_roles::*;
+pub use set_oracle::*;
+pub use set_pool::*;
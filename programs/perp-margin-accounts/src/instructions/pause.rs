@@ -0,0 +1,54 @@
+use crate::errors::ErrorCode;
+use crate::state::{validate_pause_flags, MarginVault};
+use crate::{OperationsPaused, OperationsUnpaused};
+use anchor_lang::prelude::*;
+use perp_amm::state::roles;
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// The owner, or an authority with the pauser role
+    pub pauser: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump,
+        constraint = margin_vault.is_owner(&pauser.key()) || margin_vault.has_role(&pauser.key(), roles::PAUSER) @ ErrorCode::Unauthorized
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+}
+
+// Pause the given operations (a `pause_flags` bitmask). Operations that are
+// already paused stay paused.
+pub fn pause(ctx: Context<SetPaused>, operations: u8) -> Result<()> {
+    validate_pause_flags(operations)?;
+
+    let margin_vault = &mut ctx.accounts.margin_vault;
+    margin_vault.paused |= operations;
+
+    msg!("Paused operations {:#b}", operations);
+    emit!(OperationsPaused {
+        caller: ctx.accounts.pauser.key(),
+        operations,
+        paused: margin_vault.paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Resume the given operations (a `pause_flags` bitmask)
+pub fn unpause(ctx: Context<SetPaused>, operations: u8) -> Result<()> {
+    validate_pause_flags(operations)?;
+
+    let margin_vault = &mut ctx.accounts.margin_vault;
+    margin_vault.paused &= !operations;
+
+    msg!("Unpaused operations {:#b}", operations);
+    emit!(OperationsUnpaused {
+        caller: ctx.accounts.pauser.key(),
+        operations,
+        paused: margin_vault.paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
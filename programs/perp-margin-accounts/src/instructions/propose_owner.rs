@@ -0,0 +1,33 @@
+use crate::errors::ErrorCode;
+use crate::state::MarginVault;
+use crate::OwnerProposed;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeOwner<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump,
+        constraint = margin_vault.is_owner(&owner.key()) @ ErrorCode::Unauthorized
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+}
+
+// Nominate a new owner. Control only moves once the nominee signs
+// `accept_owner`, so a mistyped key can simply be replaced. Proposing
+// `Pubkey::default()` cancels a pending transfer.
+pub fn propose_owner(ctx: Context<ProposeOwner>, new_owner: Pubkey) -> Result<()> {
+    let margin_vault = &mut ctx.accounts.margin_vault;
+    margin_vault.pending_owner = new_owner;
+
+    msg!("Proposed new owner: {}", new_owner);
+    emit!(OwnerProposed {
+        owner: ctx.accounts.owner.key(),
+        pending_owner: new_owner,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
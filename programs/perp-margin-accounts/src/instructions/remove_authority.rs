@@ -14,9 +14,9 @@ pub struct RemoveAuthority<'info> {
 pub fn remove_authority(ctx: Context<RemoveAuthority>, authority_to_remove: Pubkey) -> Result<()> {
     let margin_vault = &mut ctx.accounts.margin_vault;
 
-    // Ensure only an existing authority can remove authorities
+    // Ensure only the owner can remove authorities
     require!(
-        margin_vault.is_authority(&ctx.accounts.authority.key()),
+        margin_vault.is_owner(&ctx.accounts.authority.key()),
         ErrorCode::Unauthorized
     );
 
@@ -32,16 +32,7 @@ pub fn remove_authority(ctx: Context<RemoveAuthority>, authority_to_remove: Pubk
     }
 
     // Find and remove the authority
-    let initial_len = margin_vault.authorities.len();
-    margin_vault
-        .authorities
-        .retain(|&a| a != authority_to_remove);
-
-    // Check if authority was found and removed
-    require!(
-        margin_vault.authorities.len() < initial_len,
-        ErrorCode::AuthorityNotFound
-    );
+    margin_vault.remove_authority(&authority_to_remove)?;
 
     msg!("Removed authority: {}", authority_to_remove);
     Ok(())
@@ -1,6 +1,8 @@
 use crate::errors::MarginError;
 use crate::state::{MarginAccount, MarginVault};
+use crate::WithdrawalRequested;
 use anchor_lang::prelude::*;
+use perp_amm::state::pause_flags;
 
 #[derive(Accounts)]
 pub struct RequestWithdrawal<'info> {
@@ -16,6 +18,7 @@ pub struct RequestWithdrawal<'info> {
     #[account(
         seeds = [b"margin_vault"],
         bump = margin_vault.bump,
+        constraint = !margin_vault.is_paused(pause_flags::WITHDRAWALS) @ MarginError::OperationPaused
     )]
     pub margin_vault: Account<'info, MarginVault>,
 
@@ -31,6 +34,21 @@ pub fn request_withdrawal(
     ctx: Context<RequestWithdrawal>,
     amount: u64,
     is_sol: bool,
+) -> Result<()> {
+    queue_withdrawal(ctx, amount, is_sol, false)
+}
+
+// Same as `request_withdrawal` for SOL, but the withdrawal is unwrapped and paid
+// to the owner's wallet as native SOL when executed.
+pub fn request_withdrawal_sol(ctx: Context<RequestWithdrawal>, amount: u64) -> Result<()> {
+    queue_withdrawal(ctx, amount, true, true)
+}
+
+fn queue_withdrawal(
+    ctx: Context<RequestWithdrawal>,
+    amount: u64,
+    is_sol: bool,
+    unwrap_sol: bool,
 ) -> Result<()> {
     let margin_account = &mut ctx.accounts.margin_account;
     let clock = Clock::get()?;
@@ -57,7 +75,17 @@ pub fn request_withdrawal(
         margin_account.pending_usdc_withdrawal = amount;
     }
 
+    margin_account.unwrap_sol_withdrawal = unwrap_sol;
     margin_account.last_withdrawal_request = clock.unix_timestamp;
 
+    emit!(WithdrawalRequested {
+        owner: margin_account.owner,
+        margin_account: margin_account.key(),
+        sol_amount: margin_account.pending_sol_withdrawal,
+        usdc_amount: margin_account.pending_usdc_withdrawal,
+        unwrap_sol,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -0,0 +1,39 @@
+use crate::errors::ErrorCode;
+use crate::state::MarginVault;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetAuthorityRoles<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+}
+
+pub fn set_authority_roles(
+    ctx: Context<SetAuthorityRoles>,
+    authority_to_update: Pubkey,
+    roles: u8,
+) -> Result<()> {
+    let margin_vault = &mut ctx.accounts.margin_vault;
+
+    // Ensure only the owner can change roles
+    require!(
+        margin_vault.is_owner(&ctx.accounts.authority.key()),
+        ErrorCode::Unauthorized
+    );
+
+    // Replace the authority's roles (rejects unknown authorities and roles)
+    margin_vault.set_authority_roles(&authority_to_update, roles)?;
+
+    msg!(
+        "Set roles of authority {} to {:#b}",
+        authority_to_update,
+        roles
+    );
+    Ok(())
+}
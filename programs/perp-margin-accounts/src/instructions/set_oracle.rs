@@ -0,0 +1,68 @@
+use crate::errors::ErrorCode;
+use crate::state::MarginVault;
+use anchor_lang::prelude::*;
+use perp_amm::oracle::{self, OracleKind};
+
+#[derive(Accounts)]
+pub struct SetOracle<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+
+    /// CHECK: Validated by reading a price from the new feed below
+    pub oracle_program: AccountInfo<'info>,
+
+    /// CHECK: Validated by reading a price from the new feed below
+    pub oracle_feed: AccountInfo<'info>,
+}
+
+pub fn set_oracle(
+    ctx: Context<SetOracle>,
+    oracle_kind: OracleKind,
+    oracle_feed_id: [u8; 32],
+    max_price_age: u64,
+    max_confidence_bps: u16,
+) -> Result<()> {
+    let margin_vault = &mut ctx.accounts.margin_vault;
+
+    // Ensure only the owner can change the oracle
+    require!(
+        margin_vault.is_owner(&ctx.accounts.authority.key()),
+        ErrorCode::Unauthorized
+    );
+    require!(max_price_age > 0, ErrorCode::InvalidOracleConfig);
+
+    // Make sure the new feed returns a usable price before switching to it
+    let price = oracle::read_price(
+        oracle_kind,
+        &ctx.accounts.oracle_program,
+        &ctx.accounts.oracle_feed,
+        &oracle_feed_id,
+    )?;
+    price
+        .validate(
+            Clock::get()?.unix_timestamp,
+            max_price_age,
+            max_confidence_bps,
+        )
+        .map_err(|rejection| error!(ErrorCode::from(rejection)))?;
+
+    margin_vault.oracle_kind = oracle_kind;
+    margin_vault.oracle_program = ctx.accounts.oracle_program.key();
+    margin_vault.oracle_feed = ctx.accounts.oracle_feed.key();
+    margin_vault.oracle_feed_id = oracle_feed_id;
+    margin_vault.max_price_age = max_price_age;
+    margin_vault.max_confidence_bps = max_confidence_bps;
+
+    msg!(
+        "Set oracle: {:?} feed {}",
+        oracle_kind,
+        ctx.accounts.oracle_feed.key()
+    );
+    Ok(())
+}
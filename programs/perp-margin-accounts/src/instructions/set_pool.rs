@@ -0,0 +1,36 @@
+use crate::errors::ErrorCode;
+use crate::state::MarginVault;
+use anchor_lang::prelude::*;
+use perp_amm::state::PoolState;
+
+#[derive(Accounts)]
+pub struct SetPool<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_vault"],
+        bump = margin_vault.bump
+    )]
+    pub margin_vault: Account<'info, MarginVault>,
+
+    /// Liquidity pool to settle against from now on
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+// Bind the market to a different liquidity pool. Pending withdrawals and
+// liquidations settle against the new pool.
+pub fn set_pool(ctx: Context<SetPool>) -> Result<()> {
+    let margin_vault = &mut ctx.accounts.margin_vault;
+
+    // Ensure only the owner can rebind the market
+    require!(
+        margin_vault.is_owner(&ctx.accounts.authority.key()),
+        ErrorCode::Unauthorized
+    );
+
+    margin_vault.pool_state = ctx.accounts.pool_state.key();
+
+    msg!("Set pool: {}", margin_vault.pool_state);
+    Ok(())
+}
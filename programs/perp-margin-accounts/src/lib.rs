@@ -6,9 +6,133 @@ pub mod state;
 pub mod util;
 
 use instructions::*;
+use perp_amm::oracle::OracleKind;
 
 declare_id!("74uHucnSnpqhv3NRpgxxRsQFhaYmw7iuU8jZFzZGBTgx");
 
+#[event]
+pub struct MarginDeposited {
+    pub owner: Pubkey,
+    pub margin_account: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalRequested {
+    pub owner: Pubkey,
+    pub margin_account: Pubkey,
+    pub sol_amount: u64,
+    pub usdc_amount: u64,
+    pub unwrap_sol: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalExecuted {
+    pub owner: Pubkey,
+    pub margin_account: Pubkey,
+    pub authority: Pubkey,
+    pub sol_amount: u64,
+    pub usdc_amount: u64,
+    pub unwrap_sol: bool,
+    pub pnl_update: i64,
+    pub sol_fees_owed: u64,
+    pub usdc_fees_owed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalCancelled {
+    pub owner: Pubkey,
+    pub margin_account: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub sol_amount: u64,
+    pub usdc_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarginAccountLiquidated {
+    pub owner: Pubkey,
+    pub margin_account: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarginFeesClaimed {
+    pub authority: Pubkey,
+    pub sol_amount: u64,
+    pub usdc_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerProposed {
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerTransferred {
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllowlistRootUpdated {
+    pub owner: Pubkey,
+    pub root: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PermissionedModeUpdated {
+    pub owner: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllowlistEntryAdded {
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllowlistEntryRemoved {
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationsPaused {
+    pub caller: Pubkey,
+    /// Operations paused by this call, see `perp_amm::state::pause_flags`
+    pub operations: u8,
+    /// Every operation paused afterwards
+    pub paused: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationsUnpaused {
+    pub caller: Pubkey,
+    /// Operations resumed by this call, see `perp_amm::state::pause_flags`
+    pub operations: u8,
+    /// Every operation still paused afterwards
+    pub paused: u8,
+    pub timestamp: i64,
+}
+
 #[program]
 pub mod perp_margin_accounts {
     use super::*;
@@ -16,19 +140,39 @@ pub mod perp_margin_accounts {
     pub fn initialize(
         ctx: Context<Initialize>,
         withdrawal_timelock: i64,
-        chainlink_program: Pubkey,
-        chainlink_feed: Pubkey,
+        oracle_kind: OracleKind,
+        oracle_program: Pubkey,
+        oracle_feed: Pubkey,
+        oracle_feed_id: [u8; 32],
+        max_price_age: u64,
+        max_confidence_bps: u16,
     ) -> Result<()> {
         instructions::initialize::initialize(
             ctx,
             withdrawal_timelock,
-            chainlink_program,
-            chainlink_feed,
+            oracle_kind,
+            oracle_program,
+            oracle_feed,
+            oracle_feed_id,
+            max_price_age,
+            max_confidence_bps,
         )
     }
 
-    pub fn deposit_margin(ctx: Context<DepositMargin>, amount: u64) -> Result<()> {
-        instructions::deposit::deposit_margin(ctx, amount)
+    pub fn deposit_margin(
+        ctx: Context<DepositMargin>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::deposit::deposit_margin(ctx, amount, proof)
+    }
+
+    pub fn deposit_margin_sol(
+        ctx: Context<DepositMarginSol>,
+        lamports: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::deposit_sol::deposit_margin_sol(ctx, lamports, proof)
     }
 
     pub fn request_withdrawal(
@@ -39,6 +183,10 @@ pub mod perp_margin_accounts {
         instructions::request_withdrawal::request_withdrawal(ctx, amount, is_sol)
     }
 
+    pub fn request_withdrawal_sol(ctx: Context<RequestWithdrawal>, amount: u64) -> Result<()> {
+        instructions::request_withdrawal::request_withdrawal_sol(ctx, amount)
+    }
+
     pub fn execute_withdrawal(
         ctx: Context<ExecuteWithdrawal>,
         pnl_update: i64,
@@ -69,8 +217,28 @@ pub mod perp_margin_accounts {
         instructions::claim_fees::claim_fees(ctx)
     }
 
-    pub fn add_authority(ctx: Context<AddAuthority>, new_authority: Pubkey) -> Result<()> {
-        instructions::add_authority::add_authority(ctx, new_authority)
+    pub fn propose_owner(ctx: Context<ProposeOwner>, new_owner: Pubkey) -> Result<()> {
+        instructions::propose_owner::propose_owner(ctx, new_owner)
+    }
+
+    pub fn accept_owner(ctx: Context<AcceptOwner>) -> Result<()> {
+        instructions::accept_owner::accept_owner(ctx)
+    }
+
+    pub fn add_authority(
+        ctx: Context<AddAuthority>,
+        new_authority: Pubkey,
+        roles: u8,
+    ) -> Result<()> {
+        instructions::add_authority::add_authority(ctx, new_authority, roles)
+    }
+
+    pub fn set_authority_roles(
+        ctx: Context<SetAuthorityRoles>,
+        authority_to_update: Pubkey,
+        roles: u8,
+    ) -> Result<()> {
+        instructions::set_authority_roles::set_authority_roles(ctx, authority_to_update, roles)
     }
 
     pub fn remove_authority(
@@ -79,4 +247,60 @@ pub mod perp_margin_accounts {
     ) -> Result<()> {
         instructions::remove_authority::remove_authority(ctx, authority_to_remove)
     }
+
+    pub fn set_allowlist_root(ctx: Context<SetAllowlist>, root: [u8; 32]) -> Result<()> {
+        instructions::allowlist::set_allowlist_root(ctx, root)
+    }
+
+    pub fn set_permissioned(ctx: Context<SetAllowlist>, enabled: bool) -> Result<()> {
+        instructions::allowlist::set_permissioned(ctx, enabled)
+    }
+
+    pub fn add_allowlist_entry(ctx: Context<AddAllowlistEntry>, wallet: Pubkey) -> Result<()> {
+        instructions::allowlist::add_allowlist_entry(ctx, wallet)
+    }
+
+    pub fn remove_allowlist_entry(ctx: Context<RemoveAllowlistEntry>) -> Result<()> {
+        instructions::allowlist::remove_allowlist_entry(ctx)
+    }
+
+    pub fn pause(ctx: Context<SetPaused>, operations: u8) -> Result<()> {
+        instructions::pause::pause(ctx, operations)
+    }
+
+    pub fn unpause(ctx: Context<SetPaused>, operations: u8) -> Result<()> {
+        instructions::pause::unpause(ctx, operations)
+    }
+
+    pub fn set_oracle(
+        ctx: Context<SetOracle>,
+        oracle_kind: OracleKind,
+        oracle_feed_id: [u8; 32],
+        max_price_age: u64,
+        max_confidence_bps: u16,
+    ) -> Result<()> {
+        instructions::set_oracle::set_oracle(
+            ctx,
+            oracle_kind,
+            oracle_feed_id,
+            max_price_age,
+            max_confidence_bps,
+        )
+    }
+
+    pub fn set_pool(ctx: Context<SetPool>) -> Result<()> {
+        instructions::set_pool::set_pool(ctx)
+    }
+
+    pub fn migrate_margin_vault(
+        ctx: Context<MigrateMarginVault>,
+        max_price_age: u64,
+        max_confidence_bps: u16,
+    ) -> Result<()> {
+        instructions::migrate::migrate_margin_vault(ctx, max_price_age, max_confidence_bps)
+    }
+
+    pub fn migrate_margin_account(ctx: Context<MigrateMarginAccount>) -> Result<()> {
+        instructions::migrate::migrate_margin_account(ctx)
+    }
 }
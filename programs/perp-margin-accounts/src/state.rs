@@ -1,4 +1,8 @@
+use crate::errors::MarginError;
 use anchor_lang::prelude::*;
+use perp_amm::oracle::{self, OracleKind};
+use perp_amm::state::{pause_flags, roles};
+use perp_amm::util::allowlist::is_allowlisted;
 
 #[account]
 #[derive(Default)]
@@ -17,6 +21,28 @@ pub struct MarginAccount {
     pub last_withdrawal_request: i64,
     /// Bump seed for PDA derivation
     pub bump: u8,
+    /// Whether the pending SOL withdrawal is paid out as native SOL rather than WSOL
+    pub unwrap_sol_withdrawal: bool,
+    /// Layout version, see MARGIN_ACCOUNT_VERSION
+    pub version: u8,
+    /// Space for future fields
+    pub reserved: [u8; 32],
+}
+
+// Current `MarginAccount` layout version
+pub const MARGIN_ACCOUNT_VERSION: u8 = 1;
+
+// Current `MarginVault` layout version
+pub const MARGIN_VAULT_VERSION: u8 = 6;
+
+/// Approves one wallet to deposit margin while the vault is permissioned, as an
+/// alternative to a merkle proof against `MarginVault::allowlist_root`.
+/// PDA seeds: [b"allowlist", wallet]
+#[account]
+#[derive(InitSpace)]
+pub struct AllowlistEntry {
+    pub wallet: Pubkey,
+    pub bump: u8,
 }
 
 // Maximum number of authorities allowed
@@ -28,7 +54,7 @@ pub struct MarginVault {
     pub margin_sol_vault: Pubkey,
     /// The token account holding USDC margin deposits
     pub margin_usdc_vault: Pubkey,
-    /// Authorities that can perform admin operations
+    /// Authorities that can perform admin operations, limited by `authority_roles`
     pub authorities: Vec<Pubkey>,
     /// Minimum time required between withdrawal request and execution (in seconds)
     pub withdrawal_timelock: i64,
@@ -38,10 +64,38 @@ pub struct MarginVault {
     pub sol_fees_accumulated: u64,
     /// Accumulated USDC fees
     pub usdc_fees_accumulated: u64,
-    /// Chainlink program ID
-    pub chainlink_program: Pubkey,
-    /// Chainlink SOL/USD price feed
-    pub chainlink_feed: Pubkey,
+    /// Oracle network used to price SOL
+    pub oracle_kind: OracleKind,
+    /// Program that owns the price feed
+    pub oracle_program: Pubkey,
+    /// SOL/USD price feed
+    pub oracle_feed: Pubkey,
+    /// Maximum age of an oracle price, in seconds, before it is rejected as stale
+    pub max_price_age: u64,
+    /// Maximum oracle confidence interval as basis points of the price (0 disables the check)
+    pub max_confidence_bps: u16,
+    /// Layout version, see MARGIN_VAULT_VERSION
+    pub version: u8,
+    /// Liquidity pool this market settles PnL and liquidations against
+    pub pool_state: Pubkey,
+    /// Manages authorities, the oracle and the pool binding.
+    /// Handed over with `propose_owner` / `accept_owner`
+    pub owner: Pubkey,
+    /// Owner proposed by `propose_owner`; `Pubkey::default()` when none is pending
+    pub pending_owner: Pubkey,
+    /// Role bitmask of each entry in `authorities`, see `perp_amm::state::roles`
+    pub authority_roles: [u8; MAX_AUTHORITIES],
+    /// Operations currently paused, see `perp_amm::state::pause_flags`
+    pub paused: u8,
+    /// Merkle root of wallets approved to deposit in permissioned mode,
+    /// see `perp_amm::util::allowlist`; all zeroes when unset
+    pub allowlist_root: [u8; 32],
+    /// Whether only allowlisted wallets may deposit
+    pub permissioned: bool,
+    /// Feed id the oracle must publish the SOL/USD price under (Pyth only)
+    pub oracle_feed_id: [u8; 32],
+    /// Space for future fields
+    pub reserved: [u8; 21],
 }
 
 impl MarginAccount {
@@ -52,7 +106,14 @@ impl MarginAccount {
         8 + // pending_sol_withdrawal
         8 + // pending_usdc_withdrawal
         8 + // last_withdrawal_request
-        1; // bump
+        1 + // bump
+        1 + // unwrap_sol_withdrawal
+        1 + // version
+        32; // reserved
+
+    // Size of the layout as first deployed, before `unwrap_sol_withdrawal`,
+    // `version` and `reserved` were added
+    pub const LEGACY_LEN: usize = Self::LEN - 1 - 1 - 32;
 }
 
 impl MarginVault {
@@ -64,8 +125,21 @@ impl MarginVault {
         1 + // bump
         8 + // sol_fees_accumulated
         8 + // usdc_fees_accumulated
-        32 + // chainlink_program
-        32; // chainlink_feed
+        1 + // oracle_kind
+        32 + // oracle_program
+        32 + // oracle_feed
+        8 + // max_price_age
+        2 + // max_confidence_bps
+        1 + // version
+        32 + // pool_state
+        32 + // owner
+        32 + // pending_owner
+        MAX_AUTHORITIES + // authority_roles
+        1 + // paused
+        32 + // allowlist_root
+        1 + // permissioned
+        32 + // oracle_feed_id
+        21; // reserved
         
     // Maximum size with max authorities allocation
     pub const MAX_LEN: usize = Self::BASE_LEN + 
@@ -73,9 +147,155 @@ impl MarginVault {
         (32 * MAX_AUTHORITIES); // pubkeys in authorities vec
 }
 
+/// Borsh layout of `MarginVault` as first deployed, before the oracle settings,
+/// versioning, the pool binding, the owner, roles, pausing and the allowlist.
+/// Only read when migrating the vault.
+#[derive(AnchorDeserialize)]
+pub struct MarginVaultV0 {
+    pub margin_sol_vault: Pubkey,
+    pub margin_usdc_vault: Pubkey,
+    pub authorities: Vec<Pubkey>,
+    pub withdrawal_timelock: i64,
+    pub bump: u8,
+    pub sol_fees_accumulated: u64,
+    pub usdc_fees_accumulated: u64,
+    pub chainlink_program: Pubkey,
+    pub chainlink_feed: Pubkey,
+}
+
+impl MarginVaultV0 {
+    // Size the vault was allocated with, room for `MAX_AUTHORITIES` included
+    pub const MAX_LEN: usize = 8 + // discriminator
+        32 + // sol_vault
+        32 + // usdc_vault
+        8 + // withdrawal_timelock
+        1 + // bump
+        8 + // sol_fees_accumulated
+        8 + // usdc_fees_accumulated
+        32 + // chainlink_program
+        32 + // chainlink_feed
+        4 + // vec discriminator
+        (32 * MAX_AUTHORITIES); // pubkeys in authorities vec
+}
+
 impl MarginVault {
     /// Check if a public key is an authorized authority
     pub fn is_authority(&self, key: &Pubkey) -> bool {
         self.authorities.iter().any(|auth| auth == key)
     }
+
+    /// Check if a public key is the owner
+    pub fn is_owner(&self, key: &Pubkey) -> bool {
+        self.owner == *key
+    }
+
+    /// Check if a public key is an authority holding every role in `role`
+    pub fn has_role(&self, key: &Pubkey, role: u8) -> bool {
+        self.authorities
+            .iter()
+            .position(|auth| auth == key)
+            .is_some_and(|index| self.authority_roles[index] & role == role)
+    }
+
+    /// Check if any of the given operations (`pause_flags`) is paused
+    pub fn is_paused(&self, operations: u8) -> bool {
+        self.paused & operations != 0
+    }
+
+    /// In permissioned mode, require `wallet` to hold an allowlist entry or
+    /// prove its place under `allowlist_root`
+    pub fn check_allowlisted(
+        &self,
+        wallet: &Pubkey,
+        proof: &[[u8; 32]],
+        has_entry: bool,
+    ) -> Result<()> {
+        require!(
+            !self.permissioned || is_allowlisted(&self.allowlist_root, wallet, proof, has_entry),
+            MarginError::NotAllowlisted
+        );
+        Ok(())
+    }
+
+    /// Register a new authority with the given roles
+    pub fn add_authority(&mut self, authority: Pubkey, roles: u8) -> Result<()> {
+        validate_roles(roles)?;
+        require!(
+            !self.is_authority(&authority),
+            MarginError::AuthorityAlreadyExists
+        );
+        require!(
+            self.authorities.len() < MAX_AUTHORITIES,
+            MarginError::MaxAuthoritiesReached
+        );
+
+        self.authority_roles[self.authorities.len()] = roles;
+        self.authorities.push(authority);
+        Ok(())
+    }
+
+    /// Replace the roles of an existing authority
+    pub fn set_authority_roles(&mut self, authority: &Pubkey, roles: u8) -> Result<()> {
+        validate_roles(roles)?;
+        let index = self
+            .authorities
+            .iter()
+            .position(|auth| auth == authority)
+            .ok_or(MarginError::AuthorityNotFound)?;
+
+        self.authority_roles[index] = roles;
+        Ok(())
+    }
+
+    /// Remove an authority, keeping `authority_roles` aligned with `authorities`
+    pub fn remove_authority(&mut self, authority: &Pubkey) -> Result<()> {
+        let index = self
+            .authorities
+            .iter()
+            .position(|auth| auth == authority)
+            .ok_or(MarginError::AuthorityNotFound)?;
+
+        self.authorities.remove(index);
+        self.authority_roles.copy_within(index + 1.., index);
+        self.authority_roles[MAX_AUTHORITIES - 1] = 0;
+        Ok(())
+    }
+
+    /// Read the SOL/USD price from the configured oracle, rejecting stale,
+    /// non-positive or low-confidence prices. Returns the price with 8 decimals.
+    pub fn read_sol_price<'info>(
+        &self,
+        oracle_program: &AccountInfo<'info>,
+        oracle_feed: &AccountInfo<'info>,
+    ) -> Result<u128> {
+        let price = oracle::read_price(
+            self.oracle_kind,
+            oracle_program,
+            oracle_feed,
+            &self.oracle_feed_id,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+
+        price
+            .validate(now, self.max_price_age, self.max_confidence_bps)
+            .map_err(|rejection| error!(MarginError::from(rejection)))
+    }
+}
+
+/// Reject an empty operation set or bits that don't name an operation
+pub fn validate_pause_flags(operations: u8) -> Result<()> {
+    require!(
+        operations != 0 && operations & !pause_flags::ALL == 0,
+        MarginError::InvalidPauseFlags
+    );
+    Ok(())
+}
+
+/// Reject an empty role set or bits that don't name a role
+pub fn validate_roles(roles: u8) -> Result<()> {
+    require!(
+        roles != 0 && roles & !roles::ALL == 0,
+        MarginError::InvalidRoles
+    );
+    Ok(())
 }
@@ -1,7 +1,6 @@
 use crate::errors::MarginError;
 use crate::instructions::ExecuteWithdrawal;
 use anchor_lang::prelude::*;
-use chainlink_solana as chainlink;
 use perp_amm::cpi::{admin_withdraw, direct_deposit};
 
 /**
@@ -12,17 +11,14 @@ use perp_amm::cpi::{admin_withdraw, direct_deposit};
  * FIXME: Uses SOL / USD price, but we're not actually updating the price feed.
  */
 pub fn process_pnl_update(ctx: &mut Context<ExecuteWithdrawal>, pnl_update: i64) -> Result<()> {
-    let pool_state = &mut ctx.accounts.pool_state;
+    // Read and validate the SOL/USD price from the configured oracle before using it
+    let sol_usd_price: u128 = ctx
+        .accounts
+        .margin_vault
+        .read_sol_price(&ctx.accounts.oracle_program, &ctx.accounts.oracle_feed)?;
 
-    // Update SOL/USD price from Chainlink before using it
-    let round = chainlink::latest_round_data(
-        ctx.accounts.chainlink_program.to_account_info(),
-        ctx.accounts.chainlink_feed.to_account_info(),
-    )?;
-    let sol_usd_price: i128 = round.answer;
-
-    // Determine which asset to use for settlement based on the provided pool_vault_account
-    let use_sol_for_settlement = ctx.accounts.pool_vault_account.key() == pool_state.sol_vault;
+    // Determine which asset to use for settlement based on the provided pool asset
+    let use_sol_for_settlement = ctx.accounts.pool_asset.mint == ctx.accounts.margin_sol_vault.mint;
 
     // Skip processing if PnL is zero
     if pnl_update == 0 {
@@ -49,7 +45,7 @@ fn process_positive_pnl(
     ctx: &mut Context<ExecuteWithdrawal>,
     pnl_total_usd: u128,
     use_sol_for_settlement: bool,
-    sol_usd_price: i128,
+    sol_usd_price: u128,
 ) -> Result<()> {
     let margin_account = &mut ctx.accounts.margin_account;
 
@@ -58,7 +54,7 @@ fn process_positive_pnl(
         let pnl_sol_native = pnl_total_usd
             .checked_mul(1_000_000_000)
             .ok_or(MarginError::ArithmeticOverflow)?
-            .checked_div(sol_usd_price as u128)
+            .checked_div(sol_usd_price)
             .ok_or(MarginError::ArithmeticOverflow)? as u64;
 
         if pnl_sol_native > 0 {
@@ -67,14 +63,16 @@ fn process_positive_pnl(
             let cpi_accounts = perp_amm::cpi::accounts::AdminWithdraw {
                 admin: ctx.accounts.authority.to_account_info(),
                 pool_state: ctx.accounts.pool_state.to_account_info(),
+                asset: ctx.accounts.pool_asset.to_account_info(),
+                outflow_limits: ctx.accounts.pool_outflow_limits.to_account_info(),
                 vault_account: ctx.accounts.pool_vault_account.to_account_info(),
                 admin_token_account: ctx.accounts.margin_sol_vault.to_account_info(),
-                chainlink_program: ctx.accounts.chainlink_program.to_account_info(),
-                chainlink_feed: ctx.accounts.chainlink_feed.to_account_info(),
                 token_program: ctx.accounts.token_program.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
             };
-            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            // The pool's AUM accounts, needed while its outflow limits are enabled
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec());
 
             admin_withdraw(cpi_ctx, pnl_sol_native)?;
 
@@ -95,14 +93,16 @@ fn process_positive_pnl(
             let cpi_accounts = perp_amm::cpi::accounts::AdminWithdraw {
                 admin: ctx.accounts.authority.to_account_info(),
                 pool_state: ctx.accounts.pool_state.to_account_info(),
+                asset: ctx.accounts.pool_asset.to_account_info(),
+                outflow_limits: ctx.accounts.pool_outflow_limits.to_account_info(),
                 vault_account: ctx.accounts.pool_vault_account.to_account_info(),
                 admin_token_account: ctx.accounts.margin_usdc_vault.to_account_info(),
-                chainlink_program: ctx.accounts.chainlink_program.to_account_info(),
-                chainlink_feed: ctx.accounts.chainlink_feed.to_account_info(),
                 token_program: ctx.accounts.token_program.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
             };
-            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            // The pool's AUM accounts, needed while its outflow limits are enabled
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec());
 
             admin_withdraw(cpi_ctx, pnl_usdc_native)?;
 
@@ -121,7 +121,7 @@ fn process_negative_pnl(
     ctx: &mut Context<ExecuteWithdrawal>,
     pnl_total_usd: u128,
     use_sol_for_settlement: bool,
-    sol_usd_price: i128,
+    sol_usd_price: u128,
 ) -> Result<()> {
     let margin_account = &mut ctx.accounts.margin_account;
 
@@ -130,7 +130,7 @@ fn process_negative_pnl(
         let pnl_sol_native = pnl_total_usd
             .checked_mul(1_000_000_000)
             .ok_or(MarginError::ArithmeticOverflow)?
-            .checked_div(sol_usd_price as u128)
+            .checked_div(sol_usd_price)
             .ok_or(MarginError::ArithmeticOverflow)? as u64;
 
         // Limit deduction to available balance
@@ -146,37 +146,36 @@ fn process_negative_pnl(
             // First, transfer tokens from margin_sol_vault to authority_token_account
             let seeds = &[b"margin_vault".as_ref(), &[ctx.accounts.margin_vault.bump]];
             let signer_seeds = &[&seeds[..]];
-            
+
             let transfer_accounts = anchor_spl::token::Transfer {
                 from: ctx.accounts.margin_sol_vault.to_account_info(),
                 to: ctx.accounts.authority_token_account.to_account_info(),
                 authority: ctx.accounts.margin_vault.to_account_info(),
             };
-            
+
             let transfer_ctx = CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 transfer_accounts,
                 signer_seeds,
             );
-            
+
             // Transfer the tokens to the authority's account first
             anchor_spl::token::transfer(transfer_ctx, deduct_sol)?;
-            
+
             // Now have the authority make the direct deposit
             let cpi_program = ctx.accounts.liquidity_pool_program.to_account_info();
             let cpi_accounts = perp_amm::cpi::accounts::DirectDeposit {
                 // Use the authority as the depositor (who is a real signer)
                 depositor: ctx.accounts.authority.to_account_info(),
                 pool_state: ctx.accounts.pool_state.to_account_info(),
+                asset: ctx.accounts.pool_asset.to_account_info(),
                 // Use the authority's token account as the source
                 depositor_token_account: ctx.accounts.authority_token_account.to_account_info(),
                 vault_account: ctx.accounts.pool_vault_account.to_account_info(),
-                chainlink_program: ctx.accounts.chainlink_program.to_account_info(),
-                chainlink_feed: ctx.accounts.chainlink_feed.to_account_info(),
                 token_program: ctx.accounts.token_program.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
             };
-            
+
             // No need for PDA signing here - authority is a real signer
             let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
             direct_deposit(cpi_ctx, deduct_sol)?;
@@ -201,37 +200,36 @@ fn process_negative_pnl(
             // First, transfer tokens from margin_usdc_vault to authority_token_account
             let seeds = &[b"margin_vault".as_ref(), &[ctx.accounts.margin_vault.bump]];
             let signer_seeds = &[&seeds[..]];
-            
+
             let transfer_accounts = anchor_spl::token::Transfer {
                 from: ctx.accounts.margin_usdc_vault.to_account_info(),
                 to: ctx.accounts.authority_token_account.to_account_info(),
                 authority: ctx.accounts.margin_vault.to_account_info(),
             };
-            
+
             let transfer_ctx = CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 transfer_accounts,
                 signer_seeds,
             );
-            
+
             // Transfer the tokens to the authority's account first
             anchor_spl::token::transfer(transfer_ctx, deduct_usdc)?;
-            
+
             // Now have the authority make the direct deposit
             let cpi_program = ctx.accounts.liquidity_pool_program.to_account_info();
             let cpi_accounts = perp_amm::cpi::accounts::DirectDeposit {
                 // Use the authority as the depositor (who is a real signer)
                 depositor: ctx.accounts.authority.to_account_info(),
                 pool_state: ctx.accounts.pool_state.to_account_info(),
+                asset: ctx.accounts.pool_asset.to_account_info(),
                 // Use the authority's token account as the source
                 depositor_token_account: ctx.accounts.authority_token_account.to_account_info(),
                 vault_account: ctx.accounts.pool_vault_account.to_account_info(),
-                chainlink_program: ctx.accounts.chainlink_program.to_account_info(),
-                chainlink_feed: ctx.accounts.chainlink_feed.to_account_info(),
                 token_program: ctx.accounts.token_program.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
             };
-            
+
             // No need for PDA signing here - authority is a real signer
             let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
             direct_deposit(cpi_ctx, deduct_usdc)?;
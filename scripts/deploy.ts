@@ -21,6 +21,36 @@ const CHAINLINK_PROGRAM_ID = new PublicKey(
 
 const WITHDRAWAL_TIMELOCK = 1; // seconds
 
+// Oracle validation
+const MAX_PRICE_AGE = 120; // seconds
+const MAX_CONFIDENCE_BPS = 0; // Chainlink doesn't publish a confidence interval
+const NO_FEED_ID = Array(32).fill(0); // Chainlink feeds have no Pyth feed id
+
+// Initial per-asset fee and target weight configuration
+const DEFAULT_FEE_BPS = 10; // 0.1%
+const DEFAULT_TARGET_WEIGHT_BPS = 5_000; // 50/50 SOL/USDC split
+
+// Outflow caps on `admin_withdraw`, as basis points of AUM per window
+const OUTFLOW_WINDOW_SECS = 60 * 60; // 1 hour
+const MAX_OUTFLOW_BPS = 1_000; // 10% of AUM per window across all callers
+const MAX_CALLER_OUTFLOW_BPS = 500; // 5% of AUM per window for any one caller
+
+// Launch deposit caps; raise them with `set_deposit_limits` as the pool grows
+const MAX_AUM_USD = new BN(1_000_000).mul(new BN(100_000_000)); // $1M, 8 decimals
+const MAX_USER_LP = new BN(0); // No per-wallet cap
+
+// USDC is valued at $1 while its oracle is within this band of $1, and
+// deposits stop while it is outside
+const PEG_BAND_BPS = 50; // 0.5%
+const DEPEG_PAUSES_DEPOSITS = true;
+
+// Authority role bits, see `perp_amm::state::roles`
+const ROLE_SETTLEMENT = 1 << 0;
+const ALL_ROLES = 0b1_1111;
+
+// Id of the pool to deploy; each id is an independent pool
+const POOL_ID = new BN(process.env.POOL_ID ?? 0);
+
 // -------------------------
 // Helper: Get or create USDC mint
 // -------------------------
@@ -43,6 +73,12 @@ const getChainlinkSolFeed = () => {
     return new PublicKey("CH31Xns5z3M1cTAbKW34jcxPPciazARpijcHj9rxtemt");
   }
 };
+// USDC/USD feed for the peg band; without one USDC is valued at exactly $1
+const getChainlinkUsdcFeed = (): PublicKey | null =>
+  process.env.CHAINLINK_USDC_FEED
+    ? new PublicKey(process.env.CHAINLINK_USDC_FEED)
+    : null;
+
 // -------------------------
 // Check if account exists
 // -------------------------
@@ -62,6 +98,7 @@ async function initializeMarginProgram(
   program: Program<PerpMarginAccounts>,
   solMint: PublicKey,
   usdcMint: PublicKey,
+  poolState: PublicKey,
   chainlinkProgram: PublicKey,
   chainlinkFeed: PublicKey,
   admin: Keypair
@@ -142,12 +179,21 @@ async function initializeMarginProgram(
 
   try {
     await program.methods
-      .initialize(new BN(WITHDRAWAL_TIMELOCK), chainlinkProgram, chainlinkFeed)
+      .initialize(
+        new BN(WITHDRAWAL_TIMELOCK),
+        { chainlink: {} },
+        chainlinkProgram,
+        chainlinkFeed,
+        NO_FEED_ID,
+        new BN(MAX_PRICE_AGE),
+        MAX_CONFIDENCE_BPS
+      )
       .accountsStrict({
         authority: admin.publicKey,
         marginVault,
         marginSolVault: solVaultAccount.address,
         marginUsdcVault: usdcVaultAccount.address,
+        poolState,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -177,53 +223,54 @@ async function initializePerpAmm(
   provider: anchor.AnchorProvider,
   program: Program<PerpAmm>,
   marginProgramId: PublicKey,
-  usdcMint: PublicKey
+  usdcMint: PublicKey,
+  chainlinkProgram: PublicKey,
+  chainlinkFeed: PublicKey,
+  usdcFeed: PublicKey | null
 ) {
   console.log("\n=== Initializing Perp AMM Program ===");
 
   // For AMM we use the wrapped SOL mint (it never changes)
   const solMint = new PublicKey("So11111111111111111111111111111111111111112");
 
-  // Derive the pool state PDA using seed "pool_state"
+  // Derive the pool state PDA from "pool_state" and the pool id
   const [poolState] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_state")],
+    [Buffer.from("pool_state"), POOL_ID.toArrayLike(Buffer, "le", 8)],
     program.programId
   );
   console.log("Pool State PDA:", poolState.toString());
 
+  // Derive the asset registry entries for SOL and USDC
+  const [solAsset] = PublicKey.findProgramAddressSync(
+    [Buffer.from("asset"), poolState.toBuffer(), solMint.toBuffer()],
+    program.programId
+  );
+  const [usdcAsset] = PublicKey.findProgramAddressSync(
+    [Buffer.from("asset"), poolState.toBuffer(), usdcMint.toBuffer()],
+    program.programId
+  );
+
   // Check if pool state account already exists
   const poolExists = await accountExists(provider.connection, poolState);
   if (poolExists) {
     console.log("✓ Perp AMM pool already exists, skipping initialization");
 
-    // Still need to get the token accounts and LP mint for the return value
-    const solVaultAccount = await getOrCreateAssociatedTokenAccount(
-      provider.connection,
-      (provider.wallet as anchor.Wallet).payer,
-      solMint,
-      poolState,
-      true
-    );
-    const usdcVaultAccount = await getOrCreateAssociatedTokenAccount(
-      provider.connection,
-      (provider.wallet as anchor.Wallet).payer,
-      usdcMint,
-      poolState,
-      true
-    );
-
-    console.log("Pool State SOL Vault:", solVaultAccount.address.toString());
-    console.log("Pool State USDC Vault:", usdcVaultAccount.address.toString());
-
-    // For LP token mint, we need to fetch it from the pool state data
+    // Still need to get the asset vaults and LP mint for the return value
     try {
       const poolData = await program.account.poolState.fetch(poolState);
+      const solVault = (await program.account.assetConfig.fetch(solAsset))
+        .vault;
+      const usdcVault = (await program.account.assetConfig.fetch(usdcAsset))
+        .vault;
+
+      console.log("Pool State SOL Vault:", solVault.toString());
+      console.log("Pool State USDC Vault:", usdcVault.toString());
       console.log("LP Token Mint:", poolData.lpTokenMint.toString());
 
       return {
         poolState,
-        solVault: solVaultAccount.address,
-        usdcVault: usdcVaultAccount.address,
+        solVault,
+        usdcVault,
         lpTokenMint: poolData.lpTokenMint,
       };
     } catch (error) {
@@ -234,13 +281,13 @@ async function initializePerpAmm(
 
   // Derive PDAs for the vaults
   const [solVaultPDA] = PublicKey.findProgramAddressSync(
-    [Buffer.from("sol_vault"), poolState.toBuffer()],
+    [Buffer.from("asset_vault"), poolState.toBuffer(), solMint.toBuffer()],
     program.programId
   );
   const solVault = solVaultPDA;
 
   const [usdcVaultPDA] = PublicKey.findProgramAddressSync(
-    [Buffer.from("usdc_vault"), poolState.toBuffer()],
+    [Buffer.from("asset_vault"), poolState.toBuffer(), usdcMint.toBuffer()],
     program.programId
   );
   const usdcVault = usdcVaultPDA;
@@ -262,13 +309,10 @@ async function initializePerpAmm(
   try {
     // Initialize Perp AMM program
     await program.methods
-      .initialize()
+      .initialize(POOL_ID)
       .accountsStrict({
         admin: provider.wallet.publicKey,
         poolState,
-        solVault: solVault,
-        usdcVault: usdcVault,
-        solMint: solMint,
         usdcMint: usdcMint,
         usdcRewardVault: usdcRewardVault,
         lpTokenMint,
@@ -282,14 +326,14 @@ async function initializePerpAmm(
       `✓ Pool state initialized successfully! ${poolState.toString()}`
     );
 
-    // Initialize SOL vault
+    // Initialize USDC reward vault
     await program.methods
-      .initializeTokenVault(Buffer.from("sol_vault"))
+      .initializeTokenVault(Buffer.from("usdc_reward_vault"))
       .accountsStrict({
         admin: provider.wallet.publicKey,
         poolState,
-        vault: solVault,
-        mint: solMint,
+        vault: usdcRewardVault,
+        mint: usdcMint,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -297,16 +341,42 @@ async function initializePerpAmm(
       .signers([provider.wallet.payer])
       .rpc();
 
-    console.log(`✓ SOL vault initialized successfully! ${solVault.toString()}`);
+    console.log(
+      `✓ USDC reward vault initialized successfully! ${usdcRewardVault.toString()}`
+    );
 
-    // Initialize USDC vault
+    // Initialize LP token mint
     await program.methods
-      .initializeTokenVault(Buffer.from("usdc_vault"))
+      .initializeLpMint()
       .accountsStrict({
         admin: provider.wallet.publicKey,
         poolState,
-        vault: usdcVault,
-        mint: usdcMint,
+        lpTokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([provider.wallet.payer, lpTokenMintKeypair])
+      .rpc();
+
+    console.log("✓ LP token mint initialized successfully!");
+
+    // Initialize the vault staked LP is held in
+    const [lpStakeVault] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("lp_stake_vault"),
+        poolState.toBuffer(),
+        lpTokenMint.toBuffer(),
+      ],
+      program.programId
+    );
+    await program.methods
+      .initializeLpStakeVault()
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        poolState,
+        lpTokenMint,
+        lpStakeVault,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -315,17 +385,29 @@ async function initializePerpAmm(
       .rpc();
 
     console.log(
-      `✓ USDC vault initialized successfully! ${usdcVault.toString()}`
+      `✓ LP stake vault initialized successfully! ${lpStakeVault.toString()}`
     );
 
-    // Initialize USDC reward vault
+    // Whitelist SOL, priced by the Chainlink SOL/USD feed
     await program.methods
-      .initializeTokenVault(Buffer.from("usdc_reward_vault"))
+      .addAsset({
+        isStable: false,
+        oracleKind: { chainlink: {} },
+        oracleFeedId: NO_FEED_ID,
+        maxPriceAge: new BN(MAX_PRICE_AGE),
+        maxConfidenceBps: MAX_CONFIDENCE_BPS,
+        depositFeeBps: DEFAULT_FEE_BPS,
+        withdrawFeeBps: DEFAULT_FEE_BPS,
+        targetWeightBps: DEFAULT_TARGET_WEIGHT_BPS,
+      })
       .accountsStrict({
         admin: provider.wallet.publicKey,
         poolState,
-        vault: usdcRewardVault,
-        mint: usdcMint,
+        mint: solMint,
+        asset: solAsset,
+        vault: solVault,
+        oracleProgram: chainlinkProgram,
+        oracleFeed: chainlinkFeed,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -333,32 +415,43 @@ async function initializePerpAmm(
       .signers([provider.wallet.payer])
       .rpc();
 
-    console.log(
-      `✓ USDC reward vault initialized successfully! ${usdcRewardVault.toString()}`
-    );
+    console.log(`✓ SOL asset added successfully! ${solAsset.toString()}`);
 
-    // Initialize LP token mint
+    // Whitelist USDC as a stable asset, valued at $1 while it holds its peg
     await program.methods
-      .initializeLpMint()
+      .addAsset({
+        isStable: true,
+        oracleKind: { chainlink: {} },
+        oracleFeedId: NO_FEED_ID,
+        maxPriceAge: new BN(usdcFeed ? MAX_PRICE_AGE : 0),
+        maxConfidenceBps: MAX_CONFIDENCE_BPS,
+        depositFeeBps: DEFAULT_FEE_BPS,
+        withdrawFeeBps: DEFAULT_FEE_BPS,
+        targetWeightBps: DEFAULT_TARGET_WEIGHT_BPS,
+      })
       .accountsStrict({
         admin: provider.wallet.publicKey,
         poolState,
-        lpTokenMint,
+        mint: usdcMint,
+        asset: usdcAsset,
+        vault: usdcVault,
+        oracleProgram: usdcFeed ? chainlinkProgram : SystemProgram.programId,
+        oracleFeed: usdcFeed ?? SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
-      .signers([provider.wallet.payer, lpTokenMintKeypair])
+      .signers([provider.wallet.payer])
       .rpc();
 
-    console.log("✓ LP token mint initialized successfully!");
+    console.log(`✓ USDC asset added successfully! ${usdcAsset.toString()}`);
 
     // Add authorities to the pool state
     console.log("Adding authorities to pool state...");
 
     // Add the perp amm program as an authority for CPI calls
     await program.methods
-      .addAuthority(program.programId)
+      .addAuthority(program.programId, ROLE_SETTLEMENT)
       .accountsStrict({
         admin: provider.wallet.publicKey,
         poolState,
@@ -370,7 +463,7 @@ async function initializePerpAmm(
 
     // Add the admin as an authority
     await program.methods
-      .addAuthority(provider.wallet.publicKey)
+      .addAuthority(provider.wallet.publicKey, ALL_ROLES)
       .accountsStrict({
         admin: provider.wallet.publicKey,
         poolState,
@@ -380,6 +473,64 @@ async function initializePerpAmm(
 
     console.log("✓ Added admin as authority");
 
+    // Cap what settlement can move out of the pool per window
+    const [outflowLimits] = PublicKey.findProgramAddressSync(
+      [Buffer.from("outflow_limits"), poolState.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .setOutflowLimits(
+        new BN(OUTFLOW_WINDOW_SECS),
+        MAX_OUTFLOW_BPS,
+        MAX_CALLER_OUTFLOW_BPS
+      )
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        poolState,
+        outflowLimits,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([provider.wallet.payer])
+      .rpc();
+
+    console.log("✓ Set outflow limits");
+
+    await program.methods
+      .setDepositLimits(MAX_AUM_USD, MAX_USER_LP)
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        poolState,
+      })
+      .signers([provider.wallet.payer])
+      .rpc();
+
+    console.log("✓ Set deposit limits");
+
+    if (usdcFeed) {
+      await program.methods
+        .setPegBand(PEG_BAND_BPS, DEPEG_PAUSES_DEPOSITS)
+        .accountsStrict({
+          admin: provider.wallet.publicKey,
+          poolState,
+        })
+        .remainingAccounts(
+          [
+            [solAsset, chainlinkFeed],
+            [usdcAsset, usdcFeed],
+          ].flatMap(([asset, feed]) => [
+            { pubkey: asset, isSigner: false, isWritable: false },
+            { pubkey: chainlinkProgram, isSigner: false, isWritable: false },
+            { pubkey: feed, isSigner: false, isWritable: false },
+          ])
+        )
+        .signers([provider.wallet.payer])
+        .rpc();
+
+      console.log("✓ Set USDC peg band");
+    } else {
+      console.log("CHAINLINK_USDC_FEED not set, USDC is valued at $1");
+    }
+
     return {
       poolState,
       solVault,
@@ -416,6 +567,7 @@ async function main() {
   const usdcMint = await getUsdcMint();
 
   const chainlinkSolFeed = getChainlinkSolFeed();
+  const chainlinkUsdcFeed = getChainlinkUsdcFeed();
 
   // Get the program interfaces from the workspace.
   const marginProgram = anchor.workspace
@@ -428,23 +580,27 @@ async function main() {
   console.log("Sol Mint:", solMint.toString());
   console.log("USDC Mint:", usdcMint.toString());
 
-  // Initialize the margin program (create the vaults, etc.)
+  // Initialize the Perp AMM program (create pool state, vaults, and LP token mint)
+  const perpAmmAccounts = await initializePerpAmm(
+    provider,
+    perpAmmProgram,
+    marginProgram.programId,
+    usdcMint,
+    CHAINLINK_PROGRAM_ID,
+    chainlinkSolFeed,
+    chainlinkUsdcFeed
+  );
+  // Initialize the margin program (create the vaults, etc.), bound to the pool
   const marginAccounts = await initializeMarginProgram(
     provider,
     marginProgram,
     solMint,
     usdcMint,
+    perpAmmAccounts.poolState,
     CHAINLINK_PROGRAM_ID,
     chainlinkSolFeed,
     admin
   );
-  // Initialize the Perp AMM program (create pool state, vaults, and LP token mint)
-  const perpAmmAccounts = await initializePerpAmm(
-    provider,
-    perpAmmProgram,
-    marginProgram.programId,
-    usdcMint
-  );
 
   // Print a deployment summary
   console.log("\n=== Deployment Summary ===");
@@ -3,7 +3,11 @@ import { Program } from "@coral-xyz/anchor";
 import { PerpMarginAccounts } from "../../target/types/perp_margin_accounts";
 import { PerpAmm } from "../../target/types/perp_amm";
 import { PublicKey, SystemProgram } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, getOrCreateAssociatedTokenAccount } from "@solana/spl-token";
+import {
+  NATIVE_MINT,
+  TOKEN_PROGRAM_ID,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
 import BN from "bn.js";
 import * as dotenv from "dotenv";
 
@@ -41,6 +45,8 @@ async function main() {
     );
     process.exit(1);
   }
+  const poolVaultAccount = (await ammProgram.account.assetConfig.fetch(poolAsset))
+    .vault;
 
   const ownerPublicKey = new PublicKey(args[0]);
   const pnlUpdate = new BN(args[1]);
@@ -61,23 +67,46 @@ async function main() {
     marginProgram.programId
   );
 
-  // Derive pool state PDA
-  const [poolState] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_state")],
-    ammProgram.programId
-  );
-
   // Fetch margin vault details to get token accounts
   const marginVaultData = await marginProgram.account.marginVault.fetch(
     marginVault
   );
+
+  // Settle against the pool the market is bound to
+  const poolState = marginVaultData.poolState;
   const marginSolVault = marginVaultData.marginSolVault;
   const marginUsdcVault = marginVaultData.marginUsdcVault;
 
-  // Fetch pool state to get vault addresses
+  // Derive the pool's asset registry entries for SOL and USDC
   const poolStateData = await ammProgram.account.poolState.fetch(poolState);
-  const solVault = poolStateData.solVault;
-  const usdcVault = poolStateData.usdcVault;
+  const [solAsset] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("asset"),
+      poolState.toBuffer(),
+      new PublicKey("So11111111111111111111111111111111111111112").toBuffer(),
+    ],
+    ammProgram.programId
+  );
+  const [usdcAsset] = PublicKey.findProgramAddressSync(
+    [Buffer.from("asset"), poolState.toBuffer(), poolStateData.usdcMint.toBuffer()],
+    ammProgram.programId
+  );
+  const [outflowLimits] = PublicKey.findProgramAddressSync(
+    [Buffer.from("outflow_limits"), poolState.toBuffer()],
+    ammProgram.programId
+  );
+
+  // Every registered asset with its oracle, in index order, so positive PnL can
+  // be valued against the pool's outflow limits
+  const assets = await ammProgram.account.assetConfig.all([
+    { memcmp: { offset: 8, bytes: poolState.toBase58() } },
+  ]);
+  assets.sort((a, b) => a.account.index - b.account.index);
+  const aumAccounts = assets.flatMap(({ publicKey, account }) => [
+    { pubkey: publicKey, isSigner: false, isWritable: false },
+    { pubkey: account.oracleProgram, isSigner: false, isWritable: false },
+    { pubkey: account.oracleFeed, isSigner: false, isWritable: false },
+  ]);
 
   // Fetch margin account to determine which vault account to use
   const marginAccountData = await marginProgram.account.marginAccount.fetch(
@@ -85,11 +114,11 @@ async function main() {
   );
 
   // Determine which vault to use based on which token has a pending withdrawal
-  let poolVaultAccount: PublicKey;
+  let poolAsset: PublicKey;
   if (marginAccountData.pendingSolWithdrawal.gt(new BN(0))) {
-    poolVaultAccount = solVault;
+    poolAsset = solAsset;
   } else if (marginAccountData.pendingUsdcWithdrawal.gt(new BN(0))) {
-    poolVaultAccount = usdcVault;
+    poolAsset = usdcAsset;
   } else {
     console.error("No pending withdrawals found for this account");
     process.exit(1);
@@ -168,13 +197,22 @@ async function main() {
         authorityTokenAccount: authorityTokenAccount,
         poolState: poolState,
         poolVaultAccount: poolVaultAccount,
-        chainlinkProgram: CHAINLINK_PROGRAM_ID,
-        chainlinkFeed: CHAINLINK_SOL_FEED,
+        poolAsset: poolAsset,
+        poolOutflowLimits: outflowLimits,
+        oracleProgram: CHAINLINK_PROGRAM_ID,
+        oracleFeed: CHAINLINK_SOL_FEED,
+        owner: marginAccountData.owner,
+        solUnwrapAccount: PublicKey.findProgramAddressSync(
+          [Buffer.from("sol_unwrap"), marginAccount.toBuffer()],
+          marginProgram.programId
+        )[0],
+        nativeMint: NATIVE_MINT,
         authority: provider.wallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         liquidityPoolProgram: ammProgram.programId,
         systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(aumAccounts)
       .signers([provider.wallet.payer])
       .rpc();
 
@@ -8,16 +8,6 @@ import * as dotenv from "dotenv";
 
 dotenv.config();
 
-// Chainlink program and feed constants
-const CHAINLINK_PROGRAM_ID = new PublicKey(
-  process.env.CHAINLINK_PROGRAM_ID ||
-    "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
-);
-
-const CHAINLINK_SOL_FEED = process.env.CHAINLINK_SOL_FEED
-  ? new PublicKey(process.env.CHAINLINK_SOL_FEED)
-  : new PublicKey("99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR");
-
 async function main() {
   // Configure the client to use the specified cluster
   const provider = anchor.AnchorProvider.env();
@@ -59,18 +49,15 @@ async function main() {
     marginProgram.programId
   );
 
-  // Derive pool state PDA
-  const [poolState] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_state")],
-    ammProgram.programId
-  );
-
   // Fetch margin vault details to get token accounts
   const marginVaultData = await marginProgram.account.marginVault.fetch(
     marginVault
   );
 
-  // Fetch pool state to get vault addresses
+  // Settle against the pool the market is bound to
+  const poolState = marginVaultData.poolState;
+
+  // Fetch pool state to get the USDC mint
   const poolStateData = await ammProgram.account.poolState.fetch(poolState);
 
   // Set the appropriate vault accounts based on token type
@@ -79,8 +66,17 @@ async function main() {
       ? marginVaultData.marginSolVault
       : marginVaultData.marginUsdcVault;
 
-  const poolVaultAccount =
-    tokenType === "sol" ? poolStateData.solVault : poolStateData.usdcVault;
+  // Derive the pool's asset registry entry for the token being liquidated
+  const mint =
+    tokenType === "sol"
+      ? new PublicKey("So11111111111111111111111111111111111111112")
+      : poolStateData.usdcMint;
+  const [poolAsset] = PublicKey.findProgramAddressSync(
+    [Buffer.from("asset"), poolState.toBuffer(), mint.toBuffer()],
+    ammProgram.programId
+  );
+  const poolVaultAccount = (await ammProgram.account.assetConfig.fetch(poolAsset))
+    .vault;
 
   console.log(
     `Liquidating ${tokenType.toUpperCase()} for account: ${marginAccount.toString()}`
@@ -96,8 +92,7 @@ async function main() {
         marginVaultTokenAccount: marginVaultTokenAccount,
         poolState: poolState,
         poolVaultAccount: poolVaultAccount,
-        chainlinkProgram: CHAINLINK_PROGRAM_ID,
-        chainlinkFeed: CHAINLINK_SOL_FEED,
+        poolAsset: poolAsset,
         authority: provider.wallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         liquidityPoolProgram: ammProgram.programId,
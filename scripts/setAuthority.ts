@@ -9,13 +9,17 @@ dotenv.config();
 
 async function main() {
   if (process.argv.length < 3) {
-    console.error("Usage: ts-node scripts/setAuthority.ts <authority_pubkey>");
+    console.error(
+      "Usage: ts-node scripts/setAuthority.ts <authority_pubkey> [roles_bitmask]"
+    );
     process.exit(1);
   }
 
   // Get new authority from command line
   const newAuthority = new PublicKey(process.argv[2]);
-  console.log(`Adding authority: ${newAuthority.toString()}`);
+  // Role bits as in `perp_amm::state::roles`; every role unless narrowed
+  const roles = process.argv[3] ? Number(process.argv[3]) : 0b1_1111;
+  console.log(`Adding authority: ${newAuthority.toString()} with roles ${roles.toString(2)}`);
 
   // Configure provider
   const provider = anchor.AnchorProvider.env();
@@ -40,8 +44,9 @@ async function main() {
   console.log(`Margin Vault: ${marginVault.toString()}`);
 
   // For perp-amm, fetch the poolState
+  const poolId = new anchor.BN(process.env.POOL_ID ?? 0);
   const [poolState] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_state")],
+    [Buffer.from("pool_state"), poolId.toArrayLike(Buffer, "le", 8)],
     perpAmmProgram.programId
   );
   console.log(`Pool State: ${poolState.toString()}`);
@@ -54,8 +59,8 @@ async function main() {
     if (marginVaultData.authorities.some((auth: PublicKey) => auth.equals(newAuthority))) {
       console.log(`✓ New authority is already in the authorities list for margin program`);
     } else {
-      // Add the new authority
-      await marginProgram.methods.addAuthority(newAuthority)
+      // Add the new authority (the wallet must be the margin vault owner)
+      await marginProgram.methods.addAuthority(newAuthority, roles)
         .accounts({
           authority: provider.wallet.publicKey,
           marginVault
@@ -67,13 +72,15 @@ async function main() {
 
     // Fetch current pool state data and add authority if needed
     const poolStateData = await perpAmmProgram.account.poolState.fetch(poolState);
-    console.log(`Current AMM program authorities: ${poolStateData.authorities.map(a => a.toString()).join(", ")}`);
+    // Authorities are a fixed-size array; only the first `authorityCount` entries are set
+    const ammAuthorities = poolStateData.authorities.slice(0, poolStateData.authorityCount);
+    console.log(`Current AMM program authorities: ${ammAuthorities.map(a => a.toString()).join(", ")}`);
     
-    if (poolStateData.authorities.some((auth: PublicKey) => auth.equals(newAuthority))) {
+    if (ammAuthorities.some((auth: PublicKey) => auth.equals(newAuthority))) {
       console.log(`✓ New authority is already in the authorities list for AMM program`);
     } else {
       // Add the new authority
-      await perpAmmProgram.methods.addAuthority(newAuthority)
+      await perpAmmProgram.methods.addAuthority(newAuthority, roles)
         .accounts({
           admin: provider.wallet.publicKey,
           poolState
@@ -0,0 +1,205 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getPoolStatePda } from "./helpers/pool-pdas";
+import { ALL_ROLES } from "./helpers/roles";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("admin transfer", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+  const newAdmin = Keypair.generate();
+
+  // A separate pool, so handing it over doesn't affect the shared test pool
+  const poolId = new anchor.BN(Date.now());
+  const poolState = getPoolStatePda(program, poolId);
+
+  let usdcMint: PublicKey;
+  let marginVault: PublicKey;
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    usdcMint = setup.usdcMint;
+    marginVault = setup.marginVault;
+
+    await program.methods
+      .initialize(poolId)
+      .accountsStrict({
+        admin: admin.publicKey,
+        poolState,
+        usdcMint,
+        usdcRewardVault: Keypair.generate().publicKey,
+        lpTokenMint: Keypair.generate().publicKey,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([admin])
+      .rpc();
+  });
+
+  const proposeAdmin = (signer: Keypair, proposed: PublicKey) =>
+    program.methods
+      .proposeAdmin(proposed)
+      .accountsStrict({ admin: signer.publicKey, poolState })
+      .signers([signer])
+      .rpc();
+
+  const acceptAdmin = (signer: Keypair) =>
+    program.methods
+      .acceptAdmin()
+      .accountsStrict({ newAdmin: signer.publicKey, poolState })
+      .signers([signer])
+      .rpc();
+
+  const proposeOwner = (signer: Keypair, proposed: PublicKey) =>
+    marginProgram.methods
+      .proposeOwner(proposed)
+      .accountsStrict({ owner: signer.publicKey, marginVault })
+      .signers([signer])
+      .rpc();
+
+  const acceptOwner = (signer: Keypair) =>
+    marginProgram.methods
+      .acceptOwner()
+      .accountsStrict({ newOwner: signer.publicKey, marginVault })
+      .signers([signer])
+      .rpc();
+
+  describe("pool admin", () => {
+    it("should not allow non-admin to propose an admin", async () => {
+      try {
+        await proposeAdmin(user1, user1.publicKey);
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    it("should keep the current admin until the proposal is accepted", async () => {
+      await proposeAdmin(admin, newAdmin.publicKey);
+
+      const pool = await program.account.poolState.fetch(poolState);
+      assert.isTrue(pool.admin.equals(admin.publicKey));
+      assert.isTrue(pool.pendingAdmin.equals(newAdmin.publicKey));
+    });
+
+    it("should only let the proposed admin accept", async () => {
+      try {
+        await acceptAdmin(user1);
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    it("should let the admin replace a mistyped proposal", async () => {
+      const typo = Keypair.generate().publicKey;
+      await proposeAdmin(admin, typo);
+      assert.isTrue(
+        (await program.account.poolState.fetch(poolState)).pendingAdmin.equals(
+          typo
+        )
+      );
+
+      await proposeAdmin(admin, newAdmin.publicKey);
+    });
+
+    it("should hand the pool over once the proposed admin accepts", async () => {
+      await acceptAdmin(newAdmin);
+
+      const pool = await program.account.poolState.fetch(poolState);
+      assert.isTrue(pool.admin.equals(newAdmin.publicKey));
+      assert.isTrue(pool.pendingAdmin.equals(PublicKey.default));
+
+      // The previous admin has lost control
+      try {
+        await proposeAdmin(admin, admin.publicKey);
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("margin vault owner", () => {
+    it("should make the initializer the owner", async () => {
+      const vault = await marginProgram.account.marginVault.fetch(marginVault);
+      assert.isTrue(vault.owner.equals(admin.publicKey));
+    });
+
+    it("should not allow non-owner to propose an owner", async () => {
+      try {
+        await proposeOwner(user1, user1.publicKey);
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    it("should not let anyone but the owner manage authorities", async () => {
+      try {
+        await marginProgram.methods
+          .addAuthority(user2.publicKey, ALL_ROLES)
+          .accountsStrict({ authority: user1.publicKey, marginVault })
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    it("should transfer ownership and back", async () => {
+      await proposeOwner(admin, newAdmin.publicKey);
+
+      try {
+        await acceptOwner(user1);
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+
+      await acceptOwner(newAdmin);
+      let vault = await marginProgram.account.marginVault.fetch(marginVault);
+      assert.isTrue(vault.owner.equals(newAdmin.publicKey));
+      assert.isTrue(vault.pendingOwner.equals(PublicKey.default));
+
+      // Other tests manage the vault as `admin`, so hand it back
+      await proposeOwner(newAdmin, admin.publicKey);
+      await acceptOwner(admin);
+      vault = await marginProgram.account.marginVault.fetch(marginVault);
+      assert.isTrue(vault.owner.equals(admin.publicKey));
+    });
+  });
+});
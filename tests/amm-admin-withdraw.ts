@@ -18,6 +18,8 @@ import * as dotenv from "dotenv";
 import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
 import { setupAmmProgram } from "./helpers/init-amm-program";
 import { wrapSol } from "./helpers/wrap-sol";
+import { getAumAccounts } from "./helpers/aum-accounts";
+import { ROLE_FEE_COLLECTOR, ROLE_SETTLEMENT } from "./helpers/roles";
 
 dotenv.config();
 
@@ -51,11 +53,14 @@ describe("perp-amm (with configuration persistence)", () => {
   let usdcMint: PublicKey;
   let solVault: PublicKey;
   let usdcVault: PublicKey;
+  let solAsset: PublicKey;
+  let usdcAsset: PublicKey;
   let lpTokenMint: PublicKey;
   let solMint: PublicKey;
 
   // Set up pool state
   let poolState: PublicKey;
+  let outflowLimits: PublicKey;
 
   // Set up token accounts
   let adminUsdcAccount: PublicKey;
@@ -84,11 +89,14 @@ describe("perp-amm (with configuration persistence)", () => {
 
     // Retrieve configuration values from the setup helper.
     poolState = setup.poolState;
+    outflowLimits = setup.outflowLimits;
     solMint = setup.solMint;
     usdcMint = setup.usdcMint;
     lpTokenMint = setup.lpTokenMint;
     solVault = setup.solVault;
     usdcVault = setup.usdcVault;
+    solAsset = setup.solAsset;
+    usdcAsset = setup.usdcAsset;
     adminSolAccount = setup.adminSolAccount;
     adminUsdcAccount = setup.adminUsdcAccount;
     user1UsdcAccount = setup.user1UsdcAccount;
@@ -126,8 +134,7 @@ describe("perp-amm (with configuration persistence)", () => {
           poolState,
           depositorTokenAccount: adminSolAccount,
           vaultAccount: solVault,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          asset: solAsset,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -135,7 +142,7 @@ describe("perp-amm (with configuration persistence)", () => {
         .rpc();
 
       // Get the pool state and admin's WSOL balance before withdrawal
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
+      const solAssetBefore = await program.account.assetConfig.fetch(solAsset);
       const adminSolBefore = await getAccount(
         provider.connection,
         adminSolAccount
@@ -151,16 +158,16 @@ describe("perp-amm (with configuration persistence)", () => {
           admin: admin.publicKey,
           poolState,
           vaultAccount: solVault,
+          asset: solAsset,
+          outflowLimits,
           adminTokenAccount: adminSolAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
         .signers([admin])
         .rpc();
 
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
+      const solAssetAfter = await program.account.assetConfig.fetch(solAsset);
       const adminSolAfter = await getAccount(
         provider.connection,
         adminSolAccount
@@ -170,8 +177,8 @@ describe("perp-amm (with configuration persistence)", () => {
       // Verify that the pool state's SOL deposited is reduced by the
       // withdrawal amount.
       assert.equal(
-        poolStateBefore.solDeposited.sub(withdrawAmount).toString(),
-        poolStateAfter.solDeposited.toString(),
+        solAssetBefore.deposited.sub(withdrawAmount).toString(),
+        solAssetAfter.deposited.toString(),
         "Pool SOL deposited should decrease by withdrawal amount"
       );
 
@@ -204,8 +211,7 @@ describe("perp-amm (with configuration persistence)", () => {
           poolState,
           depositorTokenAccount: adminUsdcAccount,
           vaultAccount: usdcVault,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          asset: usdcAsset,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -213,7 +219,7 @@ describe("perp-amm (with configuration persistence)", () => {
         .rpc();
 
       const usdcVaultBefore = await getAccount(provider.connection, usdcVault);
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
+      const usdcAssetBefore = await program.account.assetConfig.fetch(usdcAsset);
       const adminUsdcBefore = await getAccount(
         provider.connection,
         adminUsdcAccount
@@ -227,9 +233,9 @@ describe("perp-amm (with configuration persistence)", () => {
           admin: admin.publicKey,
           poolState,
           vaultAccount: usdcVault,
+          asset: usdcAsset,
+          outflowLimits,
           adminTokenAccount: adminUsdcAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -237,7 +243,7 @@ describe("perp-amm (with configuration persistence)", () => {
         .rpc();
 
       const usdcVaultAfter = await getAccount(provider.connection, usdcVault);
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
+      const usdcAssetAfter = await program.account.assetConfig.fetch(usdcAsset);
       const adminUsdcAfter = await getAccount(
         provider.connection,
         adminUsdcAccount
@@ -265,8 +271,8 @@ describe("perp-amm (with configuration persistence)", () => {
 
       // Verify that the pool state's USDC deposited is updated accordingly.
       assert.equal(
-        poolStateBefore.usdcDeposited.sub(withdrawAmount).toString(),
-        poolStateAfter.usdcDeposited.toString(),
+        usdcAssetBefore.deposited.sub(withdrawAmount).toString(),
+        usdcAssetAfter.deposited.toString(),
         "Pool USDC deposited should decrease by withdrawal amount"
       );
     });
@@ -287,9 +293,9 @@ describe("perp-amm (with configuration persistence)", () => {
             admin: user1.publicKey,
             poolState,
             vaultAccount: solVault,
+            asset: solAsset,
+            outflowLimits,
             adminTokenAccount: user1SolAccount.address,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
@@ -319,9 +325,9 @@ describe("perp-amm (with configuration persistence)", () => {
             admin: admin.publicKey,
             poolState,
             vaultAccount: usdcVault,
+            asset: usdcAsset,
+            outflowLimits,
             adminTokenAccount: adminUsdcAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
@@ -339,5 +345,160 @@ describe("perp-amm (with configuration persistence)", () => {
         );
       }
     });
+
+    it("should only let authorities with the settlement role withdraw", async () => {
+      await program.methods
+        .addAuthority(user1.publicKey, ROLE_FEE_COLLECTOR)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+
+      const withdrawAsUser1 = () =>
+        program.methods
+          .adminWithdraw(new BN(1))
+          .accountsStrict({
+            admin: user1.publicKey,
+            poolState,
+            vaultAccount: usdcVault,
+            asset: usdcAsset,
+            outflowLimits,
+            adminTokenAccount: user1UsdcAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user1])
+          .rpc();
+
+      try {
+        await withdrawAsUser1();
+        assert.fail("Expected transaction to fail without the settlement role");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+
+      await program.methods
+        .setAuthorityRoles(user1.publicKey, ROLE_SETTLEMENT)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+      await withdrawAsUser1();
+
+      // Leave the shared pool's authority list as it was
+      await program.methods
+        .removeAuthority(user1.publicKey)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("should reject roles that don't exist", async () => {
+      try {
+        await program.methods
+          .addAuthority(user2.publicKey, 1 << 7)
+          .accountsStrict({ admin: admin.publicKey, poolState })
+          .signers([admin])
+          .rpc();
+        assert.fail("Expected transaction to fail with unknown roles");
+      } catch (error: any) {
+        assert.include(error.message, "InvalidRoles");
+      }
+    });
+
+    describe("outflow limits", () => {
+      const setOutflowLimits = (
+        windowSecs: number,
+        maxOutflowBps: number,
+        maxCallerOutflowBps: number
+      ) =>
+        program.methods
+          .setOutflowLimits(
+            new BN(windowSecs),
+            maxOutflowBps,
+            maxCallerOutflowBps
+          )
+          .accountsStrict({
+            admin: admin.publicKey,
+            poolState,
+            outflowLimits,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc();
+
+      const withdrawUsdc = async (amount: BN) =>
+        program.methods
+          .adminWithdraw(amount)
+          .accountsStrict({
+            admin: admin.publicKey,
+            poolState,
+            vaultAccount: usdcVault,
+            asset: usdcAsset,
+            outflowLimits,
+            adminTokenAccount: adminUsdcAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await getAumAccounts(program, poolState))
+          .signers([admin])
+          .rpc();
+
+      // USDC amount worth `bps` of the pool's current AUM (USDC is priced at $1)
+      const usdcShareOfAum = async (bps: number) => {
+        const aum = await program.methods
+          .getAum()
+          .accountsStrict({ poolState, lpTokenMint })
+          .remainingAccounts(await getAumAccounts(program, poolState))
+          .view();
+        return aum.totalUsd.muln(bps).divn(10_000).divn(100);
+      };
+
+      after(async () => {
+        // Other tests settle PnL without AUM accounts
+        await setOutflowLimits(0, 0, 0);
+      });
+
+      it("should reject caps outside 1..=10000 bps", async () => {
+        try {
+          await setOutflowLimits(3600, 10_001, 100);
+          assert.fail("Expected transaction to fail with invalid limits");
+        } catch (error: any) {
+          assert.include(error.message, "InvalidOutflowLimits");
+        }
+      });
+
+      it("should stop a caller at its cap for the window", async () => {
+        await setOutflowLimits(3600, 200, 100);
+        const callerCap = await usdcShareOfAum(100);
+
+        await withdrawUsdc(callerCap.divn(2));
+
+        try {
+          await withdrawUsdc(callerCap);
+          assert.fail("Expected transaction to fail over the caller's cap");
+        } catch (error: any) {
+          assert.include(error.message, "CallerOutflowLimitExceeded");
+        }
+
+        const limits = await program.account.outflowLimits.fetch(outflowLimits);
+        assert.isTrue(limits.windowOutflowUsd.gtn(0));
+
+        // From now on the pool won't settle without its limits account
+        const pool = await program.account.poolState.fetch(poolState);
+        assert.equal(pool.hasOutflowLimits, 1);
+      });
+
+      it("should stop all callers at the pool's cap for the window", async () => {
+        // Half the old caller cap has already left in this window
+        await setOutflowLimits(3600, 75, 75);
+        const remaining = await usdcShareOfAum(50);
+
+        try {
+          await withdrawUsdc(remaining);
+          assert.fail("Expected transaction to fail over the pool's cap");
+        } catch (error: any) {
+          assert.include(error.message, "Error Code: OutflowLimitExceeded");
+        }
+      });
+    });
   });
 });
@@ -0,0 +1,280 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+import BN from "bn.js";
+import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import { getLiquidityAccounts } from "./helpers/aum-accounts";
+import { buildAllowlist } from "./helpers/allowlist";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("permissioned deposits", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  // user1 is in the merkle tree, user2 is not
+  const allowlist = buildAllowlist([
+    user1.publicKey,
+    Keypair.generate().publicKey,
+    Keypair.generate().publicKey,
+  ]);
+  const emptyRoot = Array(32).fill(0);
+
+  let poolState: PublicKey;
+  let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
+  let usdcAsset: PublicKey;
+  let usdcVault: PublicKey;
+  let marginVault: PublicKey;
+  let marginUsdcVault: PublicKey;
+  let user2PoolEntry: PublicKey;
+
+  const userUsdcAccounts = new Map<string, PublicKey>();
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
+    usdcAsset = setup.usdcAsset;
+    usdcVault = setup.usdcVault;
+    marginVault = setup.marginVault;
+    marginUsdcVault = setup.marginUsdcVault;
+    userUsdcAccounts.set(user1.publicKey.toBase58(), setup.user1UsdcAccount);
+    userUsdcAccounts.set(user2.publicKey.toBase58(), setup.user2UsdcAccount);
+
+    [user2PoolEntry] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("allowlist"),
+        poolState.toBuffer(),
+        user2.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+  });
+
+  after(async () => {
+    // Other suites share this pool and margin vault, so open them up again
+    await program.methods
+      .setPermissioned(false)
+      .accountsStrict({ admin: admin.publicKey, poolState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setAllowlistRoot(emptyRoot)
+      .accountsStrict({ admin: admin.publicKey, poolState })
+      .signers([admin])
+      .rpc();
+    await marginProgram.methods
+      .setPermissioned(false)
+      .accountsStrict({ owner: admin.publicKey, marginVault })
+      .signers([admin])
+      .rpc();
+    await marginProgram.methods
+      .setAllowlistRoot(emptyRoot)
+      .accountsStrict({ owner: admin.publicKey, marginVault })
+      .signers([admin])
+      .rpc();
+  });
+
+  const deposit = async (
+    user: Keypair,
+    proof: number[][],
+    allowlistEntry: PublicKey | null = null
+  ) =>
+    program.methods
+      .deposit(new BN(1_000_000), new BN(0), proof) // 1 USDC
+      .accountsStrict({
+        user: user.publicKey,
+        poolState,
+        userTokenAccount: userUsdcAccounts.get(user.publicKey.toBase58()),
+        vaultAccount: usdcVault,
+        asset: usdcAsset,
+        userState: getUserStatePda(program, poolState, user.publicKey),
+        lpTokenMint,
+        lpStakeVault,
+        allowlistEntry,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(await getLiquidityAccounts(program, poolState))
+      .signers([user])
+      .rpc();
+
+  const depositMargin = async (user: Keypair, proof: number[][]) =>
+    marginProgram.methods
+      .depositMargin(new BN(1_000_000), proof) // 1 USDC
+      .accountsStrict({
+        marginAccount: PublicKey.findProgramAddressSync(
+          [Buffer.from("margin_account"), user.publicKey.toBuffer()],
+          marginProgram.programId
+        )[0],
+        marginVault,
+        vaultTokenAccount: marginUsdcVault,
+        userTokenAccount: userUsdcAccounts.get(user.publicKey.toBase58()),
+        owner: user.publicKey,
+        allowlistEntry: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+  describe("pool", () => {
+    before(async () => {
+      await program.methods
+        .setAllowlistRoot(allowlist.root)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .setPermissioned(true)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("should let a wallet in the merkle tree deposit with its proof", async () => {
+      await deposit(user1, allowlist.proof(user1.publicKey));
+    });
+
+    it("should reject a wallet with no proof or allowlist entry", async () => {
+      try {
+        await deposit(user2, []);
+        assert.fail("Expected transaction to fail for a wallet off the allowlist");
+      } catch (error: any) {
+        assert.include(error.message, "NotAllowlisted");
+      }
+    });
+
+    it("should reject another wallet's proof", async () => {
+      try {
+        await deposit(user2, allowlist.proof(user1.publicKey));
+        assert.fail("Expected transaction to fail with a proof for another wallet");
+      } catch (error: any) {
+        assert.include(error.message, "NotAllowlisted");
+      }
+    });
+
+    it("should let a wallet with an allowlist entry deposit until it is removed", async () => {
+      await program.methods
+        .addAllowlistEntry(user2.publicKey)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          allowlistEntry: user2PoolEntry,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      await deposit(user2, [], user2PoolEntry);
+
+      await program.methods
+        .removeAllowlistEntry()
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          allowlistEntry: user2PoolEntry,
+        })
+        .signers([admin])
+        .rpc();
+
+      try {
+        await deposit(user2, []);
+        assert.fail("Expected transaction to fail once the entry is removed");
+      } catch (error: any) {
+        assert.include(error.message, "NotAllowlisted");
+      }
+    });
+
+    it("should stop accepting proofs against a rotated root", async () => {
+      const rotated = buildAllowlist([
+        user2.publicKey,
+        Keypair.generate().publicKey,
+      ]);
+      await program.methods
+        .setAllowlistRoot(rotated.root)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+
+      try {
+        await deposit(user1, allowlist.proof(user1.publicKey));
+        assert.fail("Expected transaction to fail with a proof for the old root");
+      } catch (error: any) {
+        assert.include(error.message, "NotAllowlisted");
+      }
+
+      await deposit(user2, rotated.proof(user2.publicKey));
+    });
+
+    it("should only let the admin change the allowlist", async () => {
+      try {
+        await program.methods
+          .setPermissioned(false)
+          .accountsStrict({ admin: user1.publicKey, poolState })
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected transaction to fail for a non-admin");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("margin", () => {
+    it("should gate margin deposits on the margin vault's own allowlist", async () => {
+      await marginProgram.methods
+        .setAllowlistRoot(allowlist.root)
+        .accountsStrict({ owner: admin.publicKey, marginVault })
+        .signers([admin])
+        .rpc();
+      await marginProgram.methods
+        .setPermissioned(true)
+        .accountsStrict({ owner: admin.publicKey, marginVault })
+        .signers([admin])
+        .rpc();
+
+      await depositMargin(user1, allowlist.proof(user1.publicKey));
+
+      try {
+        await depositMargin(user2, []);
+        assert.fail("Expected transaction to fail for a wallet off the allowlist");
+      } catch (error: any) {
+        assert.include(error.message, "NotAllowlisted");
+      }
+    });
+  });
+});
@@ -17,6 +17,8 @@ import BN from "bn.js";
 import * as dotenv from "dotenv";
 import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
 import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import { getLiquidityAccounts } from "./helpers/aum-accounts";
 import { wrapSol } from "./helpers/wrap-sol";
 
 dotenv.config();
@@ -51,7 +53,10 @@ describe("perp-amm (with configuration persistence)", () => {
   let usdcMint: PublicKey;
   let solVault: PublicKey;
   let usdcVault: PublicKey;
+  let solAsset: PublicKey;
+  let usdcAsset: PublicKey;
   let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
   let solMint: PublicKey;
 
   // Set up pool state
@@ -63,10 +68,6 @@ describe("perp-amm (with configuration persistence)", () => {
   let user1UsdcAccount: PublicKey;
   let user2UsdcAccount: PublicKey;
 
-  // User LP token accounts
-  let user1LpTokenAccount: PublicKey;
-  let user2LpTokenAccount: PublicKey;
-
   // User states
   let user1State: PublicKey;
   let user2State: PublicKey;
@@ -95,42 +96,20 @@ describe("perp-amm (with configuration persistence)", () => {
     solMint = setup.solMint;
     usdcMint = setup.usdcMint;
     lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
     solVault = setup.solVault;
     usdcVault = setup.usdcVault;
+    solAsset = setup.solAsset;
+    usdcAsset = setup.usdcAsset;
     adminSolAccount = setup.adminSolAccount;
     adminUsdcAccount = setup.adminUsdcAccount;
     user1UsdcAccount = setup.user1UsdcAccount;
     user2UsdcAccount = setup.user2UsdcAccount;
 
-    // Create LP token accounts for users
-    user1LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user1.publicKey
-      )
-    ).address;
-
-    user2LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user2.publicKey
-      )
-    ).address;
-
     // Derive user states
-    [user1State] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_state"), user1.publicKey.toBuffer()],
-      program.programId
-    );
+    user1State = getUserStatePda(program, poolState, user1.publicKey);
 
-    [user2State] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_state"), user2.publicKey.toBuffer()],
-      program.programId
-    );
+    user2State = getUserStatePda(program, poolState, user2.publicKey);
 
     configInitialized = true;
   });
@@ -148,20 +127,21 @@ describe("perp-amm (with configuration persistence)", () => {
       // Deposit USDC from user2 to generate some fees
       try {
         await program.methods
-          .deposit(new BN(100_000_000)) // 100 USDC
+          .deposit(new BN(100_000_000), new BN(0), []) // 100 USDC
           .accountsStrict({
             user: user2.publicKey,
             poolState,
             userTokenAccount: user2UsdcAccount,
             vaultAccount: usdcVault,
+            asset: usdcAsset,
             userState: user2State,
             lpTokenMint,
-            userLpTokenAccount: user2LpTokenAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            lpStakeVault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user2])
           .rpc();
       } catch (error) {
@@ -174,14 +154,14 @@ describe("perp-amm (with configuration persistence)", () => {
 
     it("should allow admin to claim accumulated SOL fees", async () => {
       // Get balances before claiming fees
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
+      const solAssetBefore = await program.account.assetConfig.fetch(solAsset);
       const adminSolBefore = await getAccount(
         provider.connection,
         adminSolAccount
       );
 
       // Deposit SOL to generate some fees
-      if (poolStateBefore.accumulatedSolFees.eqn(0)) {
+      if (solAssetBefore.accumulatedFees.eqn(0)) {
         // Admin deposit SOL to vault
         const depositAmount = new BN(LAMPORTS_PER_SOL);
 
@@ -200,8 +180,7 @@ describe("perp-amm (with configuration persistence)", () => {
             poolState,
             depositorTokenAccount: adminSolAccount,
             vaultAccount: solVault,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            asset: solAsset,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
@@ -231,32 +210,31 @@ describe("perp-amm (with configuration persistence)", () => {
 
         // User deposit SOL
         await program.methods
-          .deposit(new BN(LAMPORTS_PER_SOL))
+          .deposit(new BN(LAMPORTS_PER_SOL), new BN(0), [])
           .accountsStrict({
             user: user1.publicKey,
             poolState,
             userTokenAccount: user1SolAccount,
             vaultAccount: solVault,
+            asset: solAsset,
             userState: user1State,
             lpTokenMint,
-            userLpTokenAccount: user1LpTokenAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            lpStakeVault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user1])
           .rpc();
       }
 
-      // Get updated pool state
-      const poolStateWithFees = await program.account.poolState.fetch(
-        poolState
-      );
+      // Get updated asset state
+      const solAssetWithFees = await program.account.assetConfig.fetch(solAsset);
 
       // Ensure there are some accumulated SOL fees
       assert.isTrue(
-        poolStateWithFees.accumulatedSolFees.gtn(0),
+        solAssetWithFees.accumulatedFees.gtn(0),
         "There should be some accumulated SOL fees"
       );
 
@@ -266,17 +244,16 @@ describe("perp-amm (with configuration persistence)", () => {
         .accountsStrict({
           admin: admin.publicKey,
           poolState,
-          solVault,
-          usdcVault,
-          adminSolAccount,
-          adminUsdcAccount,
+          asset: solAsset,
+          vaultAccount: solVault,
+          adminTokenAccount: adminSolAccount,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([admin])
         .rpc();
 
       // Get balances after claiming fees
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
+      const solAssetAfter = await program.account.assetConfig.fetch(solAsset);
       const adminSolAfter = await getAccount(
         provider.connection,
         adminSolAccount
@@ -291,7 +268,7 @@ describe("perp-amm (with configuration persistence)", () => {
       );
 
       assert.equal(
-        poolStateAfter.accumulatedSolFees.toString(),
+        solAssetAfter.accumulatedFees.toString(),
         "0",
         "Accumulated SOL fees should be reset to zero"
       );
@@ -299,41 +276,40 @@ describe("perp-amm (with configuration persistence)", () => {
 
     it("should allow admin to claim accumulated USDC fees", async () => {
       // Get balances before claiming fees
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
+      const usdcAssetBefore = await program.account.assetConfig.fetch(usdcAsset);
       const adminUsdcBefore = await getAccount(
         provider.connection,
         adminUsdcAccount
       );
 
       // If no USDC fees, generate some by making a deposit
-      if (poolStateBefore.accumulatedUsdcFees.eqn(0)) {
+      if (usdcAssetBefore.accumulatedFees.eqn(0)) {
         await program.methods
-          .deposit(new BN(100_000_000)) // 100 USDC
+          .deposit(new BN(100_000_000), new BN(0), []) // 100 USDC
           .accountsStrict({
             user: user2.publicKey,
             poolState,
             userTokenAccount: user2UsdcAccount,
             vaultAccount: usdcVault,
+            asset: usdcAsset,
             userState: user2State,
             lpTokenMint,
-            userLpTokenAccount: user2LpTokenAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            lpStakeVault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user2])
           .rpc();
       }
 
-      // Get updated pool state
-      const poolStateWithFees = await program.account.poolState.fetch(
-        poolState
-      );
+      // Get updated asset state
+      const usdcAssetWithFees = await program.account.assetConfig.fetch(usdcAsset);
 
       // Ensure there are some accumulated USDC fees
       assert.isTrue(
-        poolStateWithFees.accumulatedUsdcFees.gtn(0),
+        usdcAssetWithFees.accumulatedFees.gtn(0),
         "There should be some accumulated USDC fees"
       );
 
@@ -343,17 +319,16 @@ describe("perp-amm (with configuration persistence)", () => {
         .accountsStrict({
           admin: admin.publicKey,
           poolState,
-          solVault,
-          usdcVault,
-          adminSolAccount,
-          adminUsdcAccount,
+          asset: usdcAsset,
+          vaultAccount: usdcVault,
+          adminTokenAccount: adminUsdcAccount,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([admin])
         .rpc();
 
       // Get balances after claiming fees
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
+      const usdcAssetAfter = await program.account.assetConfig.fetch(usdcAsset);
       const adminUsdcAfter = await getAccount(
         provider.connection,
         adminUsdcAccount
@@ -368,7 +343,7 @@ describe("perp-amm (with configuration persistence)", () => {
       );
 
       assert.equal(
-        poolStateAfter.accumulatedUsdcFees.toString(),
+        usdcAssetAfter.accumulatedFees.toString(),
         "0",
         "Accumulated USDC fees should be reset to zero"
       );
@@ -391,10 +366,9 @@ describe("perp-amm (with configuration persistence)", () => {
           .accountsStrict({
             admin: user1.publicKey,
             poolState,
-            solVault,
-            usdcVault,
-            adminSolAccount: user1SolAccount,
-            adminUsdcAccount: user1UsdcAccount,
+            asset: solAsset,
+            vaultAccount: solVault,
+            adminTokenAccount: user1SolAccount,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([user1])
@@ -17,6 +17,8 @@ import BN from "bn.js";
 import * as dotenv from "dotenv";
 import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
 import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import { getLiquidityAccounts } from "./helpers/aum-accounts";
 import { wrapSol } from "./helpers/wrap-sol";
 
 dotenv.config();
@@ -51,7 +53,10 @@ describe("perp-amm (with configuration persistence)", () => {
   let usdcMint: PublicKey;
   let solVault: PublicKey;
   let usdcVault: PublicKey;
+  let usdcAsset: PublicKey;
+  let solAsset: PublicKey;
   let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
   let solMint: PublicKey;
   let usdcRewardVault: PublicKey;
 
@@ -64,10 +69,6 @@ describe("perp-amm (with configuration persistence)", () => {
   let user1UsdcAccount: PublicKey;
   let user2UsdcAccount: PublicKey;
 
-  // User LP token accounts
-  let user1LpTokenAccount: PublicKey;
-  let user2LpTokenAccount: PublicKey;
-
   // User states
   let user1State: PublicKey;
   let user2State: PublicKey;
@@ -75,6 +76,7 @@ describe("perp-amm (with configuration persistence)", () => {
   // Test parameters
   const rewardRate = new BN(100_000); // USDC per second for rewards
   const rewardAmount = new BN(10_000_000_000); // 10,000 USDC with 6 decimals
+  const rewardDuration = new BN(604800); // One week
 
   // Global configuration state
   let configInitialized = false;
@@ -100,8 +102,11 @@ describe("perp-amm (with configuration persistence)", () => {
     solMint = setup.solMint;
     usdcMint = setup.usdcMint;
     lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
     solVault = setup.solVault;
     usdcVault = setup.usdcVault;
+    usdcAsset = setup.usdcAsset;
+    solAsset = setup.solAsset;
     adminSolAccount = setup.adminSolAccount;
     adminUsdcAccount = setup.adminUsdcAccount;
     user1UsdcAccount = setup.user1UsdcAccount;
@@ -111,35 +116,10 @@ describe("perp-amm (with configuration persistence)", () => {
     const poolStateAccount = await program.account.poolState.fetch(poolState);
     usdcRewardVault = poolStateAccount.usdcRewardVault;
 
-    // Create LP token accounts for users
-    user1LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user1.publicKey
-      )
-    ).address;
-
-    user2LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user2.publicKey
-      )
-    ).address;
-
     // Derive user states
-    [user1State] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_state"), user1.publicKey.toBuffer()],
-      program.programId
-    );
+    user1State = getUserStatePda(program, poolState, user1.publicKey);
 
-    [user2State] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_state"), user2.publicKey.toBuffer()],
-      program.programId
-    );
+    user2State = getUserStatePda(program, poolState, user2.publicKey);
 
     configInitialized = true;
   });
@@ -157,7 +137,7 @@ describe("perp-amm (with configuration persistence)", () => {
       try {
         // Start rewards with a reward rate
         await program.methods
-          .startRewards(rewardAmount)
+          .startRewards(rewardAmount, rewardDuration)
           .accountsStrict({
             admin: admin.publicKey,
             poolState,
@@ -188,20 +168,21 @@ describe("perp-amm (with configuration persistence)", () => {
 
         // User1 deposit SOL to earn rewards
         await program.methods
-          .deposit(new BN(LAMPORTS_PER_SOL))
+          .deposit(new BN(LAMPORTS_PER_SOL), new BN(0), [])
           .accountsStrict({
             user: user1.publicKey,
             poolState,
             userTokenAccount: user1SolAccount,
             vaultAccount: solVault,
+            asset: solAsset,
             userState: user1State,
             lpTokenMint,
-            userLpTokenAccount: user1LpTokenAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            lpStakeVault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user1])
           .rpc();
 
@@ -237,7 +218,6 @@ describe("perp-amm (with configuration persistence)", () => {
           userState: user1State,
           userUsdcAccount: user1UsdcAccount,
           usdcRewardVault,
-          lpTokenMint,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([user1])
@@ -306,7 +286,6 @@ describe("perp-amm (with configuration persistence)", () => {
           userState: user1State,
           userUsdcAccount: user1UsdcAccount,
           usdcRewardVault,
-          lpTokenMint,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([user1])
@@ -335,5 +314,119 @@ describe("perp-amm (with configuration persistence)", () => {
         );
       }
     });
+
+    it("should compound rewards into staked LP", async () => {
+      // Let some rewards accrue since the last claim
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      const poolStateBefore = await program.account.poolState.fetch(poolState);
+      const userStateBefore = await program.account.userState.fetch(user1State);
+      const usdcVaultBefore = await getAccount(provider.connection, usdcVault);
+
+      await program.methods
+        .compoundRewards(new BN(0), [])
+        .accountsStrict({
+          user: user1.publicKey,
+          poolState,
+          userState: user1State,
+          usdcRewardVault,
+          asset: usdcAsset,
+          vaultAccount: usdcVault,
+          lpTokenMint,
+          lpStakeVault,
+          allowlistEntry: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
+        .signers([user1])
+        .rpc();
+
+      const poolStateAfter = await program.account.poolState.fetch(poolState);
+      const userStateAfter = await program.account.userState.fetch(user1State);
+      const usdcVaultAfter = await getAccount(provider.connection, usdcVault);
+
+      const compounded = poolStateAfter.totalRewardsClaimed.sub(
+        poolStateBefore.totalRewardsClaimed
+      );
+      assert.isTrue(compounded.gtn(0), "Some rewards should be compounded");
+      assert.equal(
+        (usdcVaultAfter.amount - usdcVaultBefore.amount).toString(),
+        compounded.toString(),
+        "Compounded USDC should move from the reward vault into the pool"
+      );
+      assert.equal(userStateAfter.pendingRewards.toString(), "0");
+      assert.isTrue(
+        userStateAfter.lpTokenBalance.gt(userStateBefore.lpTokenBalance),
+        "Compounded LP should be staked for the user"
+      );
+    });
+
+    it("should fail to compound if fewer LP tokens than min_lp_out would be minted", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      try {
+        await program.methods
+          .compoundRewards(new BN("18446744073709551615"), []) // u64::MAX LP
+          .accountsStrict({
+            user: user1.publicKey,
+            poolState,
+            userState: user1State,
+            usdcRewardVault,
+            asset: usdcAsset,
+            vaultAccount: usdcVault,
+            lpTokenMint,
+            lpStakeVault,
+            allowlistEntry: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected transaction to fail with slippage exceeded");
+      } catch (error: any) {
+        assert.include(error.message, "SlippageExceeded");
+      }
+    });
+
+    it("should not compound for a wallet off the allowlist", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      // No root and no entry, so no wallet is allowlisted
+      await program.methods
+        .setPermissioned(true)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+
+      try {
+        await program.methods
+          .compoundRewards(new BN(0), [])
+          .accountsStrict({
+            user: user1.publicKey,
+            poolState,
+            userState: user1State,
+            usdcRewardVault,
+            asset: usdcAsset,
+            vaultAccount: usdcVault,
+            lpTokenMint,
+            lpStakeVault,
+            allowlistEntry: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected transaction to fail for a wallet off the allowlist");
+      } catch (error: any) {
+        assert.include(error.message, "NotAllowlisted");
+      } finally {
+        // Other suites share this pool, so leave it permissionless
+        await program.methods
+          .setPermissioned(false)
+          .accountsStrict({ admin: admin.publicKey, poolState })
+          .signers([admin])
+          .rpc();
+      }
+    });
   });
 });
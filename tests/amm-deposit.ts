@@ -19,6 +19,12 @@ import BN from "bn.js";
 import * as dotenv from "dotenv";
 import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
 import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import {
+  getAumAccounts,
+  getLiquidityAccounts,
+  getRewardStreamAccounts,
+} from "./helpers/aum-accounts";
 import { wrapSol } from "./helpers/wrap-sol";
 
 dotenv.config();
@@ -53,7 +59,10 @@ describe("perp-amm (with configuration persistence)", () => {
   let usdcMint: PublicKey;
   let solVault: PublicKey;
   let usdcVault: PublicKey;
+  let solAsset: PublicKey;
+  let usdcAsset: PublicKey;
   let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
   let solMint: PublicKey;
 
   // Set up pool state
@@ -65,10 +74,6 @@ describe("perp-amm (with configuration persistence)", () => {
   let user1UsdcAccount: PublicKey;
   let user2UsdcAccount: PublicKey;
 
-  // User LP token accounts
-  let user1LpTokenAccount: PublicKey;
-  let user2LpTokenAccount: PublicKey;
-
   // User states
   let user1State: PublicKey;
   let user2State: PublicKey;
@@ -103,42 +108,20 @@ describe("perp-amm (with configuration persistence)", () => {
     solMint = setup.solMint;
     usdcMint = setup.usdcMint;
     lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
     solVault = setup.solVault;
     usdcVault = setup.usdcVault;
+    solAsset = setup.solAsset;
+    usdcAsset = setup.usdcAsset;
     adminSolAccount = setup.adminSolAccount;
     adminUsdcAccount = setup.adminUsdcAccount;
     user1UsdcAccount = setup.user1UsdcAccount;
     user2UsdcAccount = setup.user2UsdcAccount;
 
-    // Create LP token accounts for users
-    user1LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user1.publicKey
-      )
-    ).address;
-
-    user2LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user2.publicKey
-      )
-    ).address;
-
     // Derive user states
-    [user1State] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_state"), user1.publicKey.toBuffer()],
-      program.programId
-    );
+    user1State = getUserStatePda(program, poolState, user1.publicKey);
 
-    [user2State] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_state"), user2.publicKey.toBuffer()],
-      program.programId
-    );
+    user2State = getUserStatePda(program, poolState, user2.publicKey);
 
     configInitialized = true;
   });
@@ -165,8 +148,8 @@ describe("perp-amm (with configuration persistence)", () => {
       console.log("Created WSOL ata: ", user1SolAccount);
 
       // Get pool state and LP token information before deposit
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
-      const solDepositedBefore = poolStateBefore.solDeposited;
+      const solAssetBefore = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedBefore = solAssetBefore.deposited;
       const lpTokenSupplyBefore = (
         await getMint(provider.connection, lpTokenMint)
       ).supply;
@@ -189,28 +172,29 @@ describe("perp-amm (with configuration persistence)", () => {
 
       // Deposit WSOL
       await program.methods
-        .deposit(initialSolDeposit)
+        .deposit(initialSolDeposit, new BN(0), [])
         .accountsStrict({
           user: user1.publicKey,
           poolState,
           userTokenAccount: user1SolAccount,
           vaultAccount: solVault,
+          asset: solAsset,
           userState: user1State,
           lpTokenMint,
-          userLpTokenAccount: user1LpTokenAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          lpStakeVault,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          allowlistEntry: null,
         })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
         .signers([user1])
         .rpc();
 
       console.log("Deposit successful, verifying state changes...");
 
       // Get balance after deposit
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
-      const solDepositedAfter = poolStateAfter.solDeposited;
+      const solAssetAfter = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedAfter = solAssetAfter.deposited;
       const userStateAfter = await program.account.userState.fetch(user1State);
       const lpTokenSupplyAfter = (
         await getMint(provider.connection, lpTokenMint)
@@ -219,10 +203,6 @@ describe("perp-amm (with configuration persistence)", () => {
         provider.connection,
         user1SolAccount
       );
-      const user1LpBalance = (
-        await getAccount(provider.connection, user1LpTokenAccount)
-      ).amount;
-
       console.log("Got all balances, verifying state changes...");
 
       // Calculate expected values (accounting for 0.1% fee)
@@ -264,8 +244,8 @@ describe("perp-amm (with configuration persistence)", () => {
 
     it("should deposit USDC to the pool", async () => {
       // Get balances before deposit
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
-      const usdcDepositedBefore = poolStateBefore.usdcDeposited;
+      const usdcAssetBefore = await program.account.assetConfig.fetch(usdcAsset);
+      const usdcDepositedBefore = usdcAssetBefore.deposited;
       const lpTokenSupplyBefore = (
         await getMint(provider.connection, lpTokenMint)
       ).supply;
@@ -276,26 +256,27 @@ describe("perp-amm (with configuration persistence)", () => {
 
       // Deposit USDC
       await program.methods
-        .deposit(initialUsdcDeposit)
+        .deposit(initialUsdcDeposit, new BN(0), [])
         .accountsStrict({
           user: user2.publicKey,
           poolState,
           userTokenAccount: user2UsdcAccount,
           vaultAccount: usdcVault,
+          asset: usdcAsset,
           userState: user2State,
           lpTokenMint,
-          userLpTokenAccount: user2LpTokenAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          lpStakeVault,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          allowlistEntry: null,
         })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
         .signers([user2])
         .rpc();
 
       // Get balances after deposit
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
-      const usdcDepositedAfter = poolStateAfter.usdcDeposited;
+      const usdcAssetAfter = await program.account.assetConfig.fetch(usdcAsset);
+      const usdcDepositedAfter = usdcAssetAfter.deposited;
       const userStateAfter = await program.account.userState.fetch(user2State);
       const lpTokenSupplyAfter = (
         await getMint(provider.connection, lpTokenMint)
@@ -304,10 +285,6 @@ describe("perp-amm (with configuration persistence)", () => {
         provider.connection,
         user2UsdcAccount
       );
-      const user2LpBalance = (
-        await getAccount(provider.connection, user2LpTokenAccount)
-      ).amount;
-
       // Calculate expected values (accounting for 0.1% fee)
       const feeAmount = initialUsdcDeposit.muln(1).divn(1000); // 0.1% fee
       const depositedAmount = initialUsdcDeposit.sub(feeAmount);
@@ -356,20 +333,21 @@ describe("perp-amm (with configuration persistence)", () => {
 
       try {
         await program.methods
-          .deposit(new BN(0))
+          .deposit(new BN(0), new BN(0), [])
           .accountsStrict({
             user: user1.publicKey,
             poolState,
             userTokenAccount: user1SolAccount,
             vaultAccount: solVault,
+            asset: solAsset,
             userState: user1State,
             lpTokenMint,
-            userLpTokenAccount: user1LpTokenAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            lpStakeVault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user1])
           .rpc();
 
@@ -392,20 +370,21 @@ describe("perp-amm (with configuration persistence)", () => {
 
       try {
         await program.methods
-          .deposit(excessAmount)
+          .deposit(excessAmount, new BN(0), [])
           .accountsStrict({
             user: user2.publicKey,
             poolState,
             userTokenAccount: user2UsdcAccount,
             vaultAccount: usdcVault,
+            asset: usdcAsset,
             userState: user2State,
             lpTokenMint,
-            userLpTokenAccount: user2LpTokenAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            lpStakeVault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user2])
           .rpc();
 
@@ -443,8 +422,8 @@ describe("perp-amm (with configuration persistence)", () => {
       );
 
       // Get balances before first deposit
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
-      const solDepositedBefore = poolStateBefore.solDeposited;
+      const solAssetBefore = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedBefore = solAssetBefore.deposited;
       const lpTokenSupplyBefore = (
         await getMint(provider.connection, lpTokenMint)
       ).supply;
@@ -453,26 +432,27 @@ describe("perp-amm (with configuration persistence)", () => {
 
       // Execute first deposit
       await program.methods
-        .deposit(firstDepositAmount)
+        .deposit(firstDepositAmount, new BN(0), [])
         .accountsStrict({
           user: user1.publicKey,
           poolState,
           userTokenAccount: user1SolAccount,
           vaultAccount: solVault,
+          asset: solAsset,
           userState: user1State,
           lpTokenMint,
-          userLpTokenAccount: user1LpTokenAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          lpStakeVault,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          allowlistEntry: null,
         })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
         .signers([user1])
         .rpc();
 
       // Get balances after first deposit
-      const poolStateAfterFirst = await program.account.poolState.fetch(poolState);
-      const solDepositedAfterFirst = poolStateAfterFirst.solDeposited;
+      const solAssetAfterFirst = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedAfterFirst = solAssetAfterFirst.deposited;
       const lpTokenSupplyAfterFirst = (
         await getMint(provider.connection, lpTokenMint)
       ).supply;
@@ -511,26 +491,27 @@ describe("perp-amm (with configuration persistence)", () => {
 
       // Execute second deposit
       await program.methods
-        .deposit(secondDepositAmount)
+        .deposit(secondDepositAmount, new BN(0), [])
         .accountsStrict({
           user: user1.publicKey,
           poolState,
           userTokenAccount: user1SolAccount,
           vaultAccount: solVault,
+          asset: solAsset,
           userState: user1State,
           lpTokenMint,
-          userLpTokenAccount: user1LpTokenAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          lpStakeVault,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          allowlistEntry: null,
         })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
         .signers([user1])
         .rpc();
 
       // Get balances after second deposit
-      const poolStateAfterSecond = await program.account.poolState.fetch(poolState);
-      const solDepositedAfterSecond = poolStateAfterSecond.solDeposited;
+      const solAssetAfterSecond = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedAfterSecond = solAssetAfterSecond.deposited;
       const userStateAfterSecond = await program.account.userState.fetch(user1State);
       const lpTokenSupplyAfterSecond = (
         await getMint(provider.connection, lpTokenMint)
@@ -561,5 +542,286 @@ describe("perp-amm (with configuration persistence)", () => {
         "LP token supply should increase after second deposit"
       );
     });
+
+    it("should deposit native SOL to the pool without a WSOL account", async () => {
+      const solAssetBefore = await program.account.assetConfig.fetch(solAsset);
+      const vaultBefore = await getAccount(provider.connection, solVault);
+      const lpBalanceBefore = (
+        await program.account.userState.fetch(user1State)
+      ).lpTokenBalance;
+
+      await program.methods
+        .depositSol(initialSolDeposit, new BN(0), [])
+        .accountsStrict({
+          user: user1.publicKey,
+          poolState,
+          asset: solAsset,
+          vaultAccount: solVault,
+          userState: user1State,
+          lpTokenMint,
+          lpStakeVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
+        })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
+        .signers([user1])
+        .rpc();
+
+      const solAssetAfter = await program.account.assetConfig.fetch(solAsset);
+      const vaultAfter = await getAccount(provider.connection, solVault);
+      const lpBalanceAfter = (
+        await program.account.userState.fetch(user1State)
+      ).lpTokenBalance;
+
+      const feeAmount = initialSolDeposit.muln(1).divn(1000); // 0.1% fee
+
+      assert.equal(
+        new BN(vaultAfter.amount.toString())
+          .sub(new BN(vaultBefore.amount.toString()))
+          .toString(),
+        initialSolDeposit.toString(),
+        "Vault WSOL balance should increase by the lamports deposited"
+      );
+
+      assert.equal(
+        solAssetAfter.deposited.sub(solAssetBefore.deposited).toString(),
+        initialSolDeposit.sub(feeAmount).toString(),
+        "SOL deposited should increase by deposit amount minus fee"
+      );
+
+      assert.isTrue(
+        lpBalanceAfter.gt(lpBalanceBefore),
+        "User should be credited with staked LP tokens"
+      );
+    });
+
+    it("should fail to deposit if fewer LP tokens than min_lp_out would be minted", async () => {
+      try {
+        await program.methods
+          .deposit(new BN(1_000_000), new BN("18446744073709551615"), []) // 1 USDC, u64::MAX LP
+          .accountsStrict({
+            user: user2.publicKey,
+            poolState,
+            userTokenAccount: user2UsdcAccount,
+            vaultAccount: usdcVault,
+            asset: usdcAsset,
+            userState: user2State,
+            lpTokenMint,
+            lpStakeVault,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
+          })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
+          .signers([user2])
+          .rpc();
+
+        assert.fail("Expected transaction to fail with slippage exceeded");
+      } catch (error: any) {
+        assert.include(error.message, "SlippageExceeded");
+      }
+    });
+
+    it("should fail to deposit an amount whose fee leaves nothing to mint", async () => {
+      // The fee on 1 base unit rounds up to the whole unit, so no LP can be minted
+      try {
+        await program.methods
+          .deposit(new BN(1), new BN(0), [])
+          .accountsStrict({
+            user: user2.publicKey,
+            poolState,
+            userTokenAccount: user2UsdcAccount,
+            vaultAccount: usdcVault,
+            asset: usdcAsset,
+            userState: user2State,
+            lpTokenMint,
+            lpStakeVault,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
+          })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
+          .signers([user2])
+          .rpc();
+
+        assert.fail("Expected transaction to fail with a dust deposit");
+      } catch (error: any) {
+        assert.include(error.message, "DepositTooSmall");
+      }
+    });
+
+    it("should emit a Deposited event with the LP minted and pool valuation", async () => {
+      const signature = await program.methods
+        .deposit(new BN(1_000_000), new BN(0), []) // 1 USDC
+        .accountsStrict({
+          user: user2.publicKey,
+          poolState,
+          userTokenAccount: user2UsdcAccount,
+          vaultAccount: usdcVault,
+          asset: usdcAsset,
+          userState: user2State,
+          lpTokenMint,
+          lpStakeVault,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          allowlistEntry: null,
+        })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
+        .signers([user2])
+        .rpc({ commitment: "confirmed" });
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const events = [...parser.parseLogs(tx.meta.logMessages)];
+      const deposited = events.find((event) => event.name === "deposited");
+
+      assert.isDefined(deposited, "Deposited event should be emitted");
+      assert.isTrue(deposited.data.user.equals(user2.publicKey));
+      assert.isTrue(deposited.data.pool.equals(poolState));
+      assert.isTrue(deposited.data.mint.equals(usdcMint));
+      assert.equal(deposited.data.tokenAmount.toString(), "1000000");
+      assert.isTrue(deposited.data.lpMinted.gtn(0));
+      assert.isTrue(deposited.data.aumUsd.gtn(0));
+    });
+  });
+
+  describe("deposit limits", () => {
+    const depositUsdc = async (amount: BN) =>
+      program.methods
+        .deposit(amount, new BN(0), [])
+        .accountsStrict({
+          user: user2.publicKey,
+          poolState,
+          userTokenAccount: user2UsdcAccount,
+          vaultAccount: usdcVault,
+          asset: usdcAsset,
+          userState: user2State,
+          lpTokenMint,
+          lpStakeVault,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          allowlistEntry: null,
+        })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
+        .signers([user2])
+        .rpc();
+
+    const setDepositLimits = async (maxAumUsd: BN, maxUserLp: BN) =>
+      program.methods
+        .setDepositLimits(maxAumUsd, maxUserLp)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+
+    after(async () => {
+      // Other suites share this pool, so leave it uncapped
+      await setDepositLimits(new BN(0), new BN(0));
+    });
+
+    it("should reject a deposit that takes the pool over its AUM cap", async () => {
+      // Cap the pool at $1 above its current AUM
+      const aum = await program.methods
+        .getAum()
+        .accountsStrict({ poolState, lpTokenMint })
+        .remainingAccounts(await getAumAccounts(program, poolState))
+        .view();
+      await setDepositLimits(aum.totalUsd.addn(100_000_000), new BN(0));
+
+      try {
+        await depositUsdc(new BN(2_000_000)); // 2 USDC
+        assert.fail("Expected transaction to fail with the AUM cap reached");
+      } catch (error: any) {
+        assert.include(error.message, "AumCapExceeded");
+      }
+
+      // Staying under the cap is fine
+      await depositUsdc(new BN(500_000)); // 0.5 USDC
+    });
+
+    it("should reject a deposit that takes a wallet over its LP cap", async () => {
+      const staked = (await program.account.userState.fetch(user2State))
+        .lpTokenBalance;
+      await setDepositLimits(new BN(0), staked.addn(1));
+
+      try {
+        await depositUsdc(new BN(1_000_000)); // 1 USDC
+        assert.fail("Expected transaction to fail with the LP cap reached");
+      } catch (error: any) {
+        assert.include(error.message, "UserLpCapExceeded");
+      }
+    });
+
+    it("should not let a wallet stake past its LP cap", async () => {
+      const user2LpAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        user2,
+        lpTokenMint,
+        user2.publicKey
+      );
+      const stakeAccounts = {
+        user: user2.publicKey,
+        poolState,
+        userState: user2State,
+        lpTokenMint,
+        userLpTokenAccount: user2LpAccount.address,
+        lpStakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      // Unstake some LP while uncapped, then cap the wallet just above its stake
+      await setDepositLimits(new BN(0), new BN(0));
+      await program.methods
+        .unstakeLp(new BN(2))
+        .accountsStrict(stakeAccounts)
+        .remainingAccounts(await getRewardStreamAccounts(program, poolState))
+        .signers([user2])
+        .rpc();
+      const staked = (await program.account.userState.fetch(user2State))
+        .lpTokenBalance;
+      await setDepositLimits(new BN(0), staked.addn(1));
+
+      try {
+        await program.methods
+          .stakeLp(new BN(2))
+          .accountsStrict({
+            ...stakeAccounts,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(await getRewardStreamAccounts(program, poolState))
+          .signers([user2])
+          .rpc();
+        assert.fail("Expected transaction to fail with the LP cap reached");
+      } catch (error: any) {
+        assert.include(error.message, "UserLpCapExceeded");
+      }
+
+      // Staking up to the cap is fine
+      await program.methods
+        .stakeLp(new BN(1))
+        .accountsStrict({
+          ...stakeAccounts,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await getRewardStreamAccounts(program, poolState))
+        .signers([user2])
+        .rpc();
+    });
+
+    it("should only let the admin set deposit limits", async () => {
+      try {
+        await program.methods
+          .setDepositLimits(new BN(1), new BN(1))
+          .accountsStrict({ admin: user1.publicKey, poolState })
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected transaction to fail for a non-admin");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
   });
 });
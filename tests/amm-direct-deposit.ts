@@ -51,6 +51,8 @@ describe("perp-amm (with configuration persistence)", () => {
   let usdcMint: PublicKey;
   let solVault: PublicKey;
   let usdcVault: PublicKey;
+  let solAsset: PublicKey;
+  let usdcAsset: PublicKey;
   let usdcRewardVault: PublicKey;
   let lpTokenMint: PublicKey;
   let solMint: PublicKey;
@@ -92,6 +94,8 @@ describe("perp-amm (with configuration persistence)", () => {
     lpTokenMint = setup.lpTokenMint;
     solVault = setup.solVault;
     usdcVault = setup.usdcVault;
+    solAsset = setup.solAsset;
+    usdcAsset = setup.usdcAsset;
     usdcRewardVault = setup.usdcRewardVault;
     adminSolAccount = setup.adminSolAccount;
     adminUsdcAccount = setup.adminUsdcAccount;
@@ -124,8 +128,8 @@ describe("perp-amm (with configuration persistence)", () => {
       );
 
       // Get pool state before deposit
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
-      const solDepositedBefore = poolStateBefore.solDeposited;
+      const solAssetBefore = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedBefore = solAssetBefore.deposited;
 
       // Check admin's WSOL balance before deposit
       const adminSolBefore = await getAccount(
@@ -145,9 +149,8 @@ describe("perp-amm (with configuration persistence)", () => {
           depositor: admin.publicKey,
           poolState,
           depositorTokenAccount: adminSolAccount,
-          vaultAccount: poolStateAccount.solVault,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          vaultAccount: solVault,
+          asset: solAsset,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -155,8 +158,8 @@ describe("perp-amm (with configuration persistence)", () => {
         .rpc();
 
       // Get pool state after admin deposit for verification
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
-      const solDepositedAfter = poolStateAfter.solDeposited;
+      const solAssetAfter = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedAfter = solAssetAfter.deposited;
 
       // Check admin's WSOL balance after deposit
       const adminSolAfter = await getAccount(
@@ -188,8 +191,8 @@ describe("perp-amm (with configuration persistence)", () => {
       const poolStateAccount = await program.account.poolState.fetch(poolState);
 
       // Get balances before admin deposit
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
-      const usdcDepositedBefore = poolStateBefore.usdcDeposited;
+      const usdcAssetBefore = await program.account.assetConfig.fetch(usdcAsset);
+      const usdcDepositedBefore = usdcAssetBefore.deposited;
 
       const adminUsdcBefore = await getAccount(
         provider.connection,
@@ -214,9 +217,8 @@ describe("perp-amm (with configuration persistence)", () => {
           depositor: admin.publicKey,
           poolState,
           depositorTokenAccount: adminUsdcAccount,
-          vaultAccount: poolStateAccount.usdcVault,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          vaultAccount: usdcVault,
+          asset: usdcAsset,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -224,8 +226,8 @@ describe("perp-amm (with configuration persistence)", () => {
         .rpc();
 
       // Get balances after admin deposit
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
-      const usdcDepositedAfter = poolStateAfter.usdcDeposited;
+      const usdcAssetAfter = await program.account.assetConfig.fetch(usdcAsset);
+      const usdcDepositedAfter = usdcAssetAfter.deposited;
       const adminUsdcAfter = await getAccount(
         provider.connection,
         adminUsdcAccount
@@ -0,0 +1,294 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  NATIVE_MINT,
+  getAccount,
+  getMint,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import BN from "bn.js";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import {
+  setupAmmProgram,
+  DEFAULT_FEE_BPS,
+  DEFAULT_TARGET_WEIGHT_BPS,
+  MAX_CONFIDENCE_BPS,
+  MAX_PRICE_AGE,
+  NO_FEED_ID,
+} from "./helpers/init-amm-program";
+import { getAssetPda } from "./helpers/aum-accounts";
+import { ALL_ROLES } from "./helpers/roles";
+import {
+  getLpStakeVaultPda,
+  getOutflowLimitsPda,
+  getPoolStatePda,
+  getUserStatePda,
+} from "./helpers/pool-pdas";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+// Current layout versions
+const POOL_STATE_VERSION = 12;
+const USER_STATE_VERSION = 2;
+
+// The legacy pool loaded from tests/fixtures/legacy-pool (see Anchor.toml)
+const LEGACY_LP_SUPPLY = new BN(10_000_000_000); // 10 LP
+const LEGACY_SOL_DEPOSITED = new BN(1_000_000_000); // 1 SOL
+const LEGACY_SOL_FEES = new BN(1_000_000);
+const LEGACY_USDC_DEPOSITED = new BN(100_000_000); // 100 USDC
+const LEGACY_USDC_FEES = new BN(100_000);
+const LEGACY_USER_LP = new BN(2_000_000_000); // 2 LP
+const LEGACY_USER_REWARDS = new BN(1_000_000); // 1 USDC earned on 2 LP
+
+describe("legacy pool migration", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  // Use a fixed keypair for admin; it is also the legacy pool's admin
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const legacyUser = Keypair.fromSeed(Uint8Array.from(Array(32).fill(2)));
+  const legacyAuthority = Keypair.fromSeed(
+    Uint8Array.from(Array(32).fill(5))
+  ).publicKey;
+  const usdcMint = Keypair.fromSeed(Uint8Array.from(Array(32).fill(3)))
+    .publicKey;
+  const lpTokenMint = Keypair.fromSeed(Uint8Array.from(Array(32).fill(4)))
+    .publicKey;
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  const [legacyPoolState] = PublicKey.findProgramAddressSync(
+    [Buffer.from("pool_state")],
+    program.programId
+  );
+  const [legacyUserState] = PublicKey.findProgramAddressSync(
+    [Buffer.from("user_state"), legacyUser.publicKey.toBuffer()],
+    program.programId
+  );
+  const [solVault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("sol_vault"), legacyPoolState.toBuffer()],
+    program.programId
+  );
+  const [usdcVault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("usdc_vault"), legacyPoolState.toBuffer()],
+    program.programId
+  );
+  const [usdcRewardVault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("usdc_reward_vault"), legacyPoolState.toBuffer()],
+    program.programId
+  );
+
+  // The pool the legacy pool moves to
+  const poolId = new BN(Date.now());
+  const poolState = getPoolStatePda(program, poolId);
+  const solAsset = getAssetPda(program, poolState, NATIVE_MINT);
+  const usdcAsset = getAssetPda(program, poolState, usdcMint);
+  const userState = getUserStatePda(program, poolState, legacyUser.publicKey);
+
+  let testPoolState: PublicKey;
+
+  const solParams = {
+    isStable: false,
+    oracleKind: { chainlink: {} },
+    oracleFeedId: NO_FEED_ID,
+    maxPriceAge: new BN(MAX_PRICE_AGE),
+    maxConfidenceBps: MAX_CONFIDENCE_BPS,
+    depositFeeBps: DEFAULT_FEE_BPS,
+    withdrawFeeBps: DEFAULT_FEE_BPS,
+    targetWeightBps: DEFAULT_TARGET_WEIGHT_BPS,
+  };
+  const usdcParams = {
+    isStable: true,
+    oracleKind: { chainlink: {} },
+    oracleFeedId: NO_FEED_ID,
+    maxPriceAge: new BN(0),
+    maxConfidenceBps: 0,
+    depositFeeBps: DEFAULT_FEE_BPS,
+    withdrawFeeBps: DEFAULT_FEE_BPS,
+    targetWeightBps: DEFAULT_TARGET_WEIGHT_BPS,
+  };
+
+  const migrateLegacyPool = (signer: Keypair) =>
+    program.methods
+      .migrateLegacyPool(poolId, solParams, usdcParams)
+      .accountsStrict({
+        admin: signer.publicKey,
+        legacyPoolState,
+        poolState,
+        lpTokenMint,
+        lpStakeVault: getLpStakeVaultPda(program, poolState, lpTokenMint),
+        usdcRewardVault,
+        solMint: NATIVE_MINT,
+        solVault,
+        solAsset,
+        solOracleProgram: chainlinkProgram,
+        solOracleFeed: chainlinkFeed,
+        usdcMint,
+        usdcVault,
+        usdcAsset,
+        usdcOracleProgram: SystemProgram.programId,
+        usdcOracleFeed: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([signer])
+      .rpc();
+
+  const migrateLegacyUserState = (pool: PublicKey) =>
+    program.methods
+      .migrateLegacyUserState()
+      .accountsStrict({
+        payer: admin.publicKey,
+        poolState: pool,
+        user: legacyUser.publicKey,
+        legacyUserState,
+        userState: getUserStatePda(program, pool, legacyUser.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    testPoolState = setup.poolState;
+  });
+
+  it("should not allow non-admin to migrate the legacy pool", async () => {
+    try {
+      await migrateLegacyPool(user1);
+      assert.fail("Expected error but transaction succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "Unauthorized");
+    }
+  });
+
+  it("should move the legacy pool to a pool id", async () => {
+    await migrateLegacyPool(admin);
+
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(pool.version, POOL_STATE_VERSION);
+    assert.isTrue(pool.poolId.eq(poolId));
+    assert.equal(pool.fromLegacyPool, 1);
+    assert.isTrue(pool.admin.equals(admin.publicKey));
+    assert.isTrue(pool.lpTokenMint.equals(lpTokenMint));
+    assert.isTrue(pool.usdcRewardVault.equals(usdcRewardVault));
+
+    // Legacy authorities keep every role
+    assert.equal(pool.authorityCount, 1);
+    assert.isTrue(pool.authorities[0].equals(legacyAuthority));
+    assert.equal(pool.authorityRoles[0], ALL_ROLES);
+
+    // The whole LP supply counts as staked until users are migrated
+    assert.isTrue(pool.totalStakedLp.eq(LEGACY_LP_SUPPLY));
+    assert.equal(pool.assetCount, 2);
+
+    // The legacy vaults and books carry over to the assets
+    const sol = await program.account.assetConfig.fetch(solAsset);
+    assert.isTrue(sol.vault.equals(solVault));
+    assert.isTrue(sol.deposited.eq(LEGACY_SOL_DEPOSITED));
+    assert.isTrue(sol.accumulatedFees.eq(LEGACY_SOL_FEES));
+
+    const usdc = await program.account.assetConfig.fetch(usdcAsset);
+    assert.isTrue(usdc.vault.equals(usdcVault));
+    assert.isTrue(usdc.deposited.eq(LEGACY_USDC_DEPOSITED));
+    assert.isTrue(usdc.accumulatedFees.eq(LEGACY_USDC_FEES));
+
+    // The new pool state now controls the LP mint and every vault
+    const mint = await getMint(provider.connection, lpTokenMint);
+    assert.isTrue(mint.mintAuthority.equals(poolState));
+    assert.isTrue(mint.freezeAuthority.equals(poolState));
+    for (const vault of [solVault, usdcVault, usdcRewardVault]) {
+      const account = await getAccount(provider.connection, vault);
+      assert.isTrue(account.owner.equals(poolState));
+    }
+
+    // The legacy pool state is closed
+    assert.isNull(await provider.connection.getAccountInfo(legacyPoolState));
+  });
+
+  it("should only move legacy user states into the migrated pool", async () => {
+    try {
+      await migrateLegacyUserState(testPoolState);
+      assert.fail("Expected error but transaction succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "NotLegacyPool");
+    }
+  });
+
+  it("should move a legacy user state and settle its rewards", async () => {
+    await migrateLegacyUserState(poolState);
+
+    const user = await program.account.userState.fetch(userState);
+    assert.equal(user.version, USER_STATE_VERSION);
+    assert.isTrue(user.owner.equals(legacyUser.publicKey));
+    assert.equal(user.lpTokenBalance.toString(), "0");
+    assert.equal(
+      user.pendingRewards.toString(),
+      LEGACY_USER_REWARDS.toString()
+    );
+
+    // The legacy position no longer counts as staked
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.isTrue(pool.totalStakedLp.eq(LEGACY_LP_SUPPLY.sub(LEGACY_USER_LP)));
+
+    assert.isNull(await provider.connection.getAccountInfo(legacyUserState));
+  });
+
+  it("should not limit admin_withdraw before set_outflow_limits", async () => {
+    const outflowLimits = getOutflowLimitsPda(program, poolState);
+    assert.isNull(await provider.connection.getAccountInfo(outflowLimits));
+
+    const adminUsdcAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      admin,
+      usdcMint,
+      admin.publicKey
+    );
+    const withdrawAmount = new BN(1_000_000); // 1 USDC
+
+    await program.methods
+      .adminWithdraw(withdrawAmount)
+      .accountsStrict({
+        admin: admin.publicKey,
+        poolState,
+        asset: usdcAsset,
+        outflowLimits,
+        vaultAccount: usdcVault,
+        adminTokenAccount: adminUsdcAccount.address,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const usdc = await program.account.assetConfig.fetch(usdcAsset);
+    assert.isTrue(usdc.deposited.eq(LEGACY_USDC_DEPOSITED.sub(withdrawAmount)));
+  });
+});
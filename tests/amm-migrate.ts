@@ -0,0 +1,149 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
+import * as dotenv from "dotenv";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import BN from "bn.js";
+import { getOutflowLimitsPda } from "./helpers/pool-pdas";
+import {
+  setupAmmProgram,
+  MAX_PRICE_AGE,
+  MAX_CONFIDENCE_BPS,
+} from "./helpers/init-amm-program";
+
+dotenv.config();
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+// Current layout versions
+const POOL_STATE_VERSION = 12;
+const MARGIN_VAULT_VERSION = 6;
+
+describe("account layout migrations", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+
+  // Required for initialization
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  // Use a fixed keypair for admin
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  let poolState: PublicKey;
+  let marginVault: PublicKey;
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    marginVault = setup.marginVault;
+  });
+
+  describe("migrate_pool_state", () => {
+    it("should store the current version, size and a fixed-size authority list", async () => {
+      const poolStateAccount = await program.account.poolState.fetch(poolState);
+      assert.equal(poolStateAccount.version, POOL_STATE_VERSION);
+
+      // Version 11 grew the account past its original 640 bytes
+      const info = await provider.connection.getAccountInfo(poolState);
+      assert.equal(info.data.length, 8 + 768);
+
+      // Only the first `authorityCount` entries are set
+      const authorities = poolStateAccount.authorities.slice(
+        0,
+        poolStateAccount.authorityCount
+      );
+      assert.isTrue(authorities.some((a) => a.equals(admin.publicKey)));
+      assert.isTrue(
+        poolStateAccount.authorities
+          .slice(poolStateAccount.authorityCount)
+          .every((a) => a.equals(PublicKey.default))
+      );
+    });
+
+    it("should reject migrating a pool already on the current version", async () => {
+      try {
+        await program.methods
+          .migratePoolState()
+          .accountsStrict({
+            admin: admin.publicKey,
+            poolState,
+            outflowLimits: getOutflowLimitsPda(program, poolState),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "AlreadyMigrated");
+      }
+    });
+
+    it("should not allow non-admin to migrate the pool", async () => {
+      try {
+        await program.methods
+          .migratePoolState()
+          .accountsStrict({
+            admin: user1.publicKey,
+            poolState,
+            outflowLimits: getOutflowLimitsPda(program, poolState),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("migrate_margin_vault", () => {
+    it("should reject migrating a vault already on the current version", async () => {
+      const marginVaultAccount = await marginProgram.account.marginVault.fetch(
+        marginVault
+      );
+      assert.equal(marginVaultAccount.version, MARGIN_VAULT_VERSION);
+
+      try {
+        await marginProgram.methods
+          .migrateMarginVault(new BN(MAX_PRICE_AGE), MAX_CONFIDENCE_BPS)
+          .accountsStrict({
+            authority: admin.publicKey,
+            marginVault,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "AlreadyMigrated");
+      }
+    });
+  });
+});
@@ -0,0 +1,452 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import BN from "bn.js";
+import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import {
+  getLiquidityAccounts,
+  getRewardStreamAccounts,
+} from "./helpers/aum-accounts";
+import { ROLE_PAUSER } from "./helpers/roles";
+import {
+  PAUSE_DEPOSITS,
+  PAUSE_SETTLEMENTS,
+  PAUSE_WITHDRAWALS,
+} from "./helpers/pause-flags";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("pausing", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+  const pauser = Keypair.generate();
+
+  let poolState: PublicKey;
+  let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
+  let usdcAsset: PublicKey;
+  let usdcVault: PublicKey;
+  let user1UsdcAccount: PublicKey;
+  let user1State: PublicKey;
+  let user1LpAccount: PublicKey;
+  let adminUsdcAccount: PublicKey;
+  let marginVault: PublicKey;
+  let marginSolVault: PublicKey;
+  let marginUsdcVault: PublicKey;
+  let adminSolAccount: PublicKey;
+  let user1MarginAccount: PublicKey;
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
+    usdcAsset = setup.usdcAsset;
+    usdcVault = setup.usdcVault;
+    user1UsdcAccount = setup.user1UsdcAccount;
+    user1State = getUserStatePda(program, poolState, user1.publicKey);
+    adminUsdcAccount = setup.adminUsdcAccount;
+    marginVault = setup.marginVault;
+    marginSolVault = setup.marginSolVault;
+    marginUsdcVault = setup.marginUsdcVault;
+    adminSolAccount = setup.adminSolAccount;
+    user1LpAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        user1,
+        lpTokenMint,
+        user1.publicKey
+      )
+    ).address;
+    [user1MarginAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("margin_account"), user1.publicKey.toBuffer()],
+      marginProgram.programId
+    );
+
+    // A dedicated pauser authority in both programs
+    await program.methods
+      .addAuthority(pauser.publicKey, ROLE_PAUSER)
+      .accountsStrict({ admin: admin.publicKey, poolState })
+      .signers([admin])
+      .rpc();
+    await marginProgram.methods
+      .addAuthority(pauser.publicKey, ROLE_PAUSER)
+      .accountsStrict({ authority: admin.publicKey, marginVault })
+      .signers([admin])
+      .rpc();
+  });
+
+  after(async () => {
+    // Leave both programs unpaused and without the test pauser
+    const pool = await program.account.poolState.fetch(poolState);
+    if (pool.paused !== 0) {
+      await program.methods
+        .unpause(pool.paused)
+        .accountsStrict({ pauser: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+    }
+    const vault = await marginProgram.account.marginVault.fetch(marginVault);
+    if (vault.paused !== 0) {
+      await marginProgram.methods
+        .unpause(vault.paused)
+        .accountsStrict({ pauser: admin.publicKey, marginVault })
+        .signers([admin])
+        .rpc();
+    }
+
+    await program.methods
+      .removeAuthority(pauser.publicKey)
+      .accountsStrict({ admin: admin.publicKey, poolState })
+      .signers([admin])
+      .rpc();
+    await marginProgram.methods
+      .removeAuthority(pauser.publicKey)
+      .accountsStrict({ authority: admin.publicKey, marginVault })
+      .signers([admin])
+      .rpc();
+  });
+
+  const depositUsdc = async (amount: BN) =>
+    program.methods
+      .deposit(amount, new BN(0), [])
+      .accountsStrict({
+        user: user1.publicKey,
+        poolState,
+        userTokenAccount: user1UsdcAccount,
+        vaultAccount: usdcVault,
+        asset: usdcAsset,
+        userState: user1State,
+        lpTokenMint,
+        lpStakeVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        allowlistEntry: null,
+      })
+      .remainingAccounts(await getLiquidityAccounts(program, poolState))
+      .signers([user1])
+      .rpc();
+
+  const unstakeLp = async (amount: BN) =>
+    program.methods
+      .unstakeLp(amount)
+      .accountsStrict({
+        user: user1.publicKey,
+        poolState,
+        userState: user1State,
+        lpTokenMint,
+        userLpTokenAccount: user1LpAccount,
+        lpStakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(await getRewardStreamAccounts(program, poolState))
+      .signers([user1])
+      .rpc();
+
+  const stakeLp = async (amount: BN) =>
+    program.methods
+      .stakeLp(amount)
+      .accountsStrict({
+        user: user1.publicKey,
+        poolState,
+        userState: user1State,
+        lpTokenMint,
+        userLpTokenAccount: user1LpAccount,
+        lpStakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(await getRewardStreamAccounts(program, poolState))
+      .signers([user1])
+      .rpc();
+
+  const directDepositUsdc = async (amount: BN) =>
+    program.methods
+      .directDeposit(amount)
+      .accountsStrict({
+        depositor: admin.publicKey,
+        poolState,
+        depositorTokenAccount: adminUsdcAccount,
+        vaultAccount: usdcVault,
+        asset: usdcAsset,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+  const cancelMarginWithdrawal = async () =>
+    marginProgram.methods
+      .cancelWithdrawal()
+      .accountsStrict({
+        marginAccount: user1MarginAccount,
+        marginVault,
+        authority: user1.publicKey,
+      })
+      .signers([user1])
+      .rpc();
+
+  const claimMarginFees = async () =>
+    marginProgram.methods
+      .claimFees()
+      .accountsStrict({
+        marginVault,
+        marginSolVault,
+        marginUsdcVault,
+        adminSolAccount,
+        adminUsdcAccount,
+        authority: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+  const depositMarginUsdc = async (amount: BN) =>
+    marginProgram.methods
+      .depositMargin(amount, [])
+      .accountsStrict({
+        marginAccount: user1MarginAccount,
+        marginVault,
+        vaultTokenAccount: marginUsdcVault,
+        userTokenAccount: user1UsdcAccount,
+        owner: user1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        allowlistEntry: null,
+      })
+      .signers([user1])
+      .rpc();
+
+  describe("pool", () => {
+    it("should stop deposits while they are paused", async () => {
+      await program.methods
+        .pause(PAUSE_DEPOSITS | PAUSE_WITHDRAWALS)
+        .accountsStrict({ pauser: pauser.publicKey, poolState })
+        .signers([pauser])
+        .rpc();
+
+      const pool = await program.account.poolState.fetch(poolState);
+      assert.equal(pool.paused, PAUSE_DEPOSITS | PAUSE_WITHDRAWALS);
+
+      try {
+        await depositUsdc(new BN(1_000_000));
+        assert.fail("Expected deposit to fail while paused");
+      } catch (error: any) {
+        assert.include(error.message, "OperationPaused");
+      }
+    });
+
+    it("should resume only the unpaused operations", async () => {
+      await program.methods
+        .unpause(PAUSE_DEPOSITS)
+        .accountsStrict({ pauser: pauser.publicKey, poolState })
+        .signers([pauser])
+        .rpc();
+
+      const pool = await program.account.poolState.fetch(poolState);
+      assert.equal(pool.paused, PAUSE_WITHDRAWALS);
+
+      await depositUsdc(new BN(1_000_000));
+    });
+
+    it("should stop unstaking while withdrawals are paused", async () => {
+      try {
+        await unstakeLp(new BN(1));
+        assert.fail("Expected unstake to fail while paused");
+      } catch (error: any) {
+        assert.include(error.message, "OperationPaused");
+      }
+    });
+
+    it("should stop staking and direct deposits while they are paused", async () => {
+      await program.methods
+        .unpause(PAUSE_WITHDRAWALS)
+        .accountsStrict({ pauser: pauser.publicKey, poolState })
+        .signers([pauser])
+        .rpc();
+      await unstakeLp(new BN(1));
+
+      await program.methods
+        .pause(PAUSE_DEPOSITS)
+        .accountsStrict({ pauser: pauser.publicKey, poolState })
+        .signers([pauser])
+        .rpc();
+
+      try {
+        await stakeLp(new BN(1));
+        assert.fail("Expected stake to fail while deposits are paused");
+      } catch (error: any) {
+        assert.include(error.message, "OperationPaused");
+      }
+
+      // Direct deposits settle PnL, so pausing deposits alone doesn't stop them
+      await directDepositUsdc(new BN(1_000_000));
+
+      await program.methods
+        .unpause(PAUSE_DEPOSITS)
+        .accountsStrict({ pauser: pauser.publicKey, poolState })
+        .signers([pauser])
+        .rpc();
+      await program.methods
+        .pause(PAUSE_SETTLEMENTS)
+        .accountsStrict({ pauser: pauser.publicKey, poolState })
+        .signers([pauser])
+        .rpc();
+
+      try {
+        await directDepositUsdc(new BN(1_000_000));
+        assert.fail("Expected direct deposit to fail while settlements are paused");
+      } catch (error: any) {
+        assert.include(error.message, "OperationPaused");
+      }
+
+      await program.methods
+        .unpause(PAUSE_SETTLEMENTS)
+        .accountsStrict({ pauser: pauser.publicKey, poolState })
+        .signers([pauser])
+        .rpc();
+      await stakeLp(new BN(1));
+    });
+
+    it("should not let an authority without the pauser role pause", async () => {
+      try {
+        await program.methods
+          .pause(PAUSE_DEPOSITS)
+          .accountsStrict({ pauser: user2.publicKey, poolState })
+          .signers([user2])
+          .rpc();
+        assert.fail("Expected pause to fail for a non-pauser");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    it("should reject unknown pause flags", async () => {
+      try {
+        await program.methods
+          .pause(1 << 7)
+          .accountsStrict({ pauser: admin.publicKey, poolState })
+          .signers([admin])
+          .rpc();
+        assert.fail("Expected pause to fail with unknown flags");
+      } catch (error: any) {
+        assert.include(error.message, "InvalidPauseFlags");
+      }
+    });
+  });
+
+  describe("margin", () => {
+    it("should stop margin deposits while they are paused", async () => {
+      await marginProgram.methods
+        .pause(PAUSE_DEPOSITS)
+        .accountsStrict({ pauser: pauser.publicKey, marginVault })
+        .signers([pauser])
+        .rpc();
+
+      try {
+        await depositMarginUsdc(new BN(1_000_000));
+        assert.fail("Expected margin deposit to fail while paused");
+      } catch (error: any) {
+        assert.include(error.message, "OperationPaused");
+      }
+
+      await marginProgram.methods
+        .unpause(PAUSE_DEPOSITS)
+        .accountsStrict({ pauser: pauser.publicKey, marginVault })
+        .signers([pauser])
+        .rpc();
+
+      await depositMarginUsdc(new BN(1_000_000));
+      const vault = await marginProgram.account.marginVault.fetch(marginVault);
+      assert.equal(vault.paused, 0);
+    });
+
+    it("should stop cancelling withdrawals while withdrawals are paused", async () => {
+      await marginProgram.methods
+        .requestWithdrawal(new BN(1_000_000), false)
+        .accountsStrict({
+          marginAccount: user1MarginAccount,
+          marginVault,
+          owner: user1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc();
+
+      await marginProgram.methods
+        .pause(PAUSE_WITHDRAWALS)
+        .accountsStrict({ pauser: pauser.publicKey, marginVault })
+        .signers([pauser])
+        .rpc();
+
+      try {
+        await cancelMarginWithdrawal();
+        assert.fail("Expected cancel to fail while paused");
+      } catch (error: any) {
+        assert.include(error.message, "OperationPaused");
+      }
+
+      await marginProgram.methods
+        .unpause(PAUSE_WITHDRAWALS)
+        .accountsStrict({ pauser: pauser.publicKey, marginVault })
+        .signers([pauser])
+        .rpc();
+      await cancelMarginWithdrawal();
+    });
+
+    it("should stop fee claims while settlements are paused", async () => {
+      await marginProgram.methods
+        .pause(PAUSE_SETTLEMENTS)
+        .accountsStrict({ pauser: pauser.publicKey, marginVault })
+        .signers([pauser])
+        .rpc();
+
+      try {
+        await claimMarginFees();
+        assert.fail("Expected fee claim to fail while paused");
+      } catch (error: any) {
+        assert.include(error.message, "OperationPaused");
+      }
+
+      await marginProgram.methods
+        .unpause(PAUSE_SETTLEMENTS)
+        .accountsStrict({ pauser: pauser.publicKey, marginVault })
+        .signers([pauser])
+        .rpc();
+    });
+  });
+});
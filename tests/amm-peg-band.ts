@@ -0,0 +1,160 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { assert } from "chai";
+import BN from "bn.js";
+import {
+  setupAmmProgram,
+  MAX_PRICE_AGE,
+  MAX_CONFIDENCE_BPS,
+  NO_FEED_ID,
+} from "./helpers/init-amm-program";
+import { getAumAccounts } from "./helpers/aum-accounts";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+const ONE_USD = new BN(100_000_000); // 8 decimals
+const PEG_BAND_BPS = 50; // 0.5%
+
+describe("perp-amm peg band", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  let poolState: PublicKey;
+  let lpTokenMint: PublicKey;
+  let usdcAsset: PublicKey;
+  let usdcIndex: number;
+
+  const setPegBand = async (pegBandBps: number, pausesDeposits: boolean) =>
+    program.methods
+      .setPegBand(pegBandBps, pausesDeposits)
+      .accountsStrict({ admin: admin.publicKey, poolState })
+      .remainingAccounts(await getAumAccounts(program, poolState))
+      .signers([admin])
+      .rpc();
+
+  const getAum = async () =>
+    program.methods
+      .getAum()
+      .accountsStrict({ poolState, lpTokenMint })
+      .remainingAccounts(await getAumAccounts(program, poolState))
+      .view();
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    lpTokenMint = setup.lpTokenMint;
+    usdcAsset = setup.usdcAsset;
+    usdcIndex = (await program.account.assetConfig.fetch(usdcAsset)).index;
+
+    // Localnet has no USDC/USD feed, so price USDC off the SOL/USD feed to
+    // simulate a stablecoin far outside its peg
+    await program.methods
+      .setOracle({ chainlink: {} }, NO_FEED_ID, new BN(MAX_PRICE_AGE), MAX_CONFIDENCE_BPS)
+      .accountsStrict({
+        admin: admin.publicKey,
+        poolState,
+        asset: usdcAsset,
+        oracleProgram: chainlinkProgram,
+        oracleFeed: chainlinkFeed,
+      })
+      .signers([admin])
+      .rpc();
+  });
+
+  after(async () => {
+    // With no band the USDC feed is never read, so it can stay pointed at SOL/USD
+    await setPegBand(0, false);
+  });
+
+  it("should value stablecoins at exactly 1 USD while there is no band", async () => {
+    const aum = await getAum();
+    assert.equal(aum.prices[usdcIndex].toString(), ONE_USD.toString());
+    assert.isFalse(aum.depegged);
+  });
+
+  it("should value a stablecoin outside the band at its oracle price", async () => {
+    await setPegBand(PEG_BAND_BPS, false);
+
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(pool.pegBandBps, PEG_BAND_BPS);
+    assert.equal(pool.depegPausesDeposits, 0);
+
+    const aum = await getAum();
+    assert.notEqual(aum.prices[usdcIndex].toString(), ONE_USD.toString());
+    assert.isTrue(aum.depegged);
+  });
+
+  it("should reject deposits while depegged when configured to", async () => {
+    await setPegBand(PEG_BAND_BPS, true);
+
+    try {
+      await program.methods
+        .previewDeposit(new BN(1_000_000), []) // 1 USDC
+        .accountsStrict({
+          poolState,
+          asset: usdcAsset,
+          lpTokenMint,
+          user: user1.publicKey,
+          userState: null,
+          allowlistEntry: null,
+        })
+        .remainingAccounts(await getAumAccounts(program, poolState))
+        .view();
+      assert.fail("Expected error but preview succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "StablecoinDepegged");
+    }
+  });
+
+  it("should reject a band wider than the maximum", async () => {
+    try {
+      await setPegBand(1_001, false);
+      assert.fail("Expected error but transaction succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "InvalidPegBand");
+    }
+  });
+
+  it("should only let the admin set the peg band", async () => {
+    try {
+      await program.methods
+        .setPegBand(PEG_BAND_BPS, false)
+        .accountsStrict({ admin: user1.publicKey, poolState })
+        .remainingAccounts(await getAumAccounts(program, poolState))
+        .signers([user1])
+        .rpc();
+      assert.fail("Expected error but transaction succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "Unauthorized");
+    }
+  });
+});
@@ -0,0 +1,117 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
+import * as dotenv from "dotenv";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getPoolStatePda, POOL_ID } from "./helpers/pool-pdas";
+
+dotenv.config();
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("perp-amm (multiple pools)", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+
+  // Required for initialization
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  // Use a fixed keypair for admin
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  // A second, isolated pool alongside the one set up for every test
+  const secondPoolId = new anchor.BN(Date.now());
+  const secondPoolState = getPoolStatePda(program, secondPoolId);
+
+  let poolState: PublicKey;
+  let usdcMint: PublicKey;
+  let marginVault: PublicKey;
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    usdcMint = setup.usdcMint;
+    marginVault = setup.marginVault;
+  });
+
+  it("should create a second pool with its own state", async () => {
+    const lpTokenMint = Keypair.generate().publicKey;
+    const usdcRewardVault = Keypair.generate().publicKey;
+
+    await program.methods
+      .initialize(secondPoolId)
+      .accountsStrict({
+        admin: admin.publicKey,
+        poolState: secondPoolState,
+        usdcMint,
+        usdcRewardVault,
+        lpTokenMint,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([admin])
+      .rpc();
+
+    const secondPool = await program.account.poolState.fetch(secondPoolState);
+    assert.isTrue(secondPool.poolId.eq(secondPoolId));
+    assert.isTrue(secondPool.lpTokenMint.equals(lpTokenMint));
+    assert.equal(secondPool.assetCount, 0);
+
+    // The original pool is untouched
+    const firstPool = await program.account.poolState.fetch(poolState);
+    assert.isTrue(firstPool.poolId.eq(POOL_ID));
+    assert.isFalse(firstPool.lpTokenMint.equals(lpTokenMint));
+    assert.isAbove(firstPool.assetCount, 0);
+  });
+
+  it("should bind the margin market to the pool it was initialized with", async () => {
+    const marginVaultAccount = await marginProgram.account.marginVault.fetch(
+      marginVault
+    );
+    assert.isTrue(marginVaultAccount.poolState.equals(poolState));
+  });
+
+  it("should not allow non-authority to rebind the margin market", async () => {
+    try {
+      await marginProgram.methods
+        .setPool()
+        .accountsStrict({
+          authority: user1.publicKey,
+          marginVault,
+          poolState: secondPoolState,
+        })
+        .signers([user1])
+        .rpc();
+      assert.fail("Expected error but transaction succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "Unauthorized");
+    }
+  });
+});
@@ -0,0 +1,138 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { transfer } from "@solana/spl-token";
+import { assert } from "chai";
+import BN from "bn.js";
+import { setupAmmProgram } from "./helpers/init-amm-program";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("perp-amm reconcile", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  let poolState: PublicKey;
+  let usdcAsset: PublicKey;
+  let usdcVault: PublicKey;
+  let adminUsdcAccount: PublicKey;
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    usdcAsset = setup.usdcAsset;
+    usdcVault = setup.usdcVault;
+    adminUsdcAccount = setup.adminUsdcAccount;
+  });
+
+  const reconcile = (caller: Keypair, action: any) =>
+    program.methods
+      .reconcile(action)
+      .accountsStrict({
+        caller: caller.publicKey,
+        poolState,
+        asset: usdcAsset,
+        vaultAccount: usdcVault,
+      })
+      .signers([caller]);
+
+  // Report without sending a transaction
+  const report = async () => {
+    const { events } = await reconcile(admin, { report: {} }).simulate();
+    const reconciled = events.find((event) => event.name === "assetReconciled");
+    assert.isDefined(reconciled, "AssetReconciled event should be emitted");
+    return reconciled.data;
+  };
+
+  it("should report tokens sent straight to the vault as surplus", async () => {
+    const before = await report();
+
+    await transfer(
+      provider.connection,
+      admin,
+      adminUsdcAccount,
+      usdcVault,
+      admin,
+      BigInt(2_000_000) // 2 USDC
+    );
+
+    const after = await report();
+    assert.equal(
+      after.vaultBalance.sub(before.vaultBalance).toString(),
+      "2000000"
+    );
+    assert.equal(after.booked.toString(), before.booked.toString());
+    assert.isTrue(after.surplus.gte(new BN(2_000_000)));
+    assert.equal(after.deficit.toString(), "0");
+  });
+
+  it("should not let a non-admin sweep the surplus", async () => {
+    try {
+      await reconcile(user1, { sweepSurplus: {} }).rpc();
+      assert.fail("Expected transaction to fail for a non-admin");
+    } catch (error: any) {
+      assert.include(error.message, "Unauthorized");
+    }
+  });
+
+  it("should sweep the surplus into the asset's deposits", async () => {
+    const { surplus } = await report();
+    const assetBefore = await program.account.assetConfig.fetch(usdcAsset);
+
+    await reconcile(admin, { sweepSurplus: {} }).rpc();
+
+    const assetAfter = await program.account.assetConfig.fetch(usdcAsset);
+    assert.equal(
+      assetAfter.deposited.sub(assetBefore.deposited).toString(),
+      surplus.toString()
+    );
+
+    const after = await report();
+    assert.equal(after.surplus.toString(), "0");
+    assert.equal(after.deficit.toString(), "0");
+  });
+
+  it("should reject sweeping or flagging a vault that is in balance", async () => {
+    try {
+      await reconcile(admin, { sweepSurplus: {} }).rpc();
+      assert.fail("Expected transaction to fail with nothing to sweep");
+    } catch (error: any) {
+      assert.include(error.message, "NoSurplus");
+    }
+
+    try {
+      await reconcile(admin, { flagDeficit: {} }).rpc();
+      assert.fail("Expected transaction to fail with no deficit");
+    } catch (error: any) {
+      assert.include(error.message, "NoDeficit");
+    }
+  });
+});
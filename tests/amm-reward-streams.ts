@@ -0,0 +1,268 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import BN from "bn.js";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import {
+  getAumAccounts,
+  getLiquidityAccounts,
+  getRewardStreamPda,
+  getRewardStreamVaultPda,
+} from "./helpers/aum-accounts";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("perp-amm reward streams", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  let poolState: PublicKey;
+  let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
+  let usdcAsset: PublicKey;
+  let usdcVault: PublicKey;
+  let user1UsdcAccount: PublicKey;
+  let user1State: PublicKey;
+
+  // Partner reward token
+  let rewardMint: PublicKey;
+  let rewardStream: PublicKey;
+  let rewardVault: PublicKey;
+  let adminRewardAccount: PublicKey;
+  let user1RewardAccount: PublicKey;
+
+  const rewardAmount = new BN(1_000_000_000_000); // 1,000,000 tokens with 6 decimals
+  const rewardDuration = new BN(604800); // One week
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
+    usdcAsset = setup.usdcAsset;
+    usdcVault = setup.usdcVault;
+    user1UsdcAccount = setup.user1UsdcAccount;
+    user1State = getUserStatePda(program, poolState, user1.publicKey);
+
+    rewardMint = await createMint(
+      provider.connection,
+      admin,
+      admin.publicKey,
+      null,
+      6
+    );
+    rewardStream = getRewardStreamPda(program, poolState, rewardMint);
+    rewardVault = getRewardStreamVaultPda(program, poolState, rewardMint);
+
+    adminRewardAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        admin,
+        rewardMint,
+        admin.publicKey
+      )
+    ).address;
+    user1RewardAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        admin,
+        rewardMint,
+        user1.publicKey
+      )
+    ).address;
+
+    await mintTo(
+      provider.connection,
+      admin,
+      rewardMint,
+      adminRewardAccount,
+      admin,
+      BigInt(rewardAmount.toString())
+    );
+  });
+
+  const depositUsdc = (amount: BN) =>
+    program.methods.deposit(amount, new BN(0), []).accountsStrict({
+      user: user1.publicKey,
+      poolState,
+      userTokenAccount: user1UsdcAccount,
+      vaultAccount: usdcVault,
+      asset: usdcAsset,
+      userState: user1State,
+      lpTokenMint,
+      lpStakeVault,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      allowlistEntry: null,
+    });
+
+  it("should fail if non-admin tries to add a reward stream", async () => {
+    try {
+      await program.methods
+        .addRewardStream()
+        .accountsStrict({
+          admin: user1.publicKey,
+          poolState,
+          mint: rewardMint,
+          rewardStream,
+          vault: rewardVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user1])
+        .rpc();
+
+      assert.fail("Expected transaction to fail with unauthorized admin");
+    } catch (error: any) {
+      assert.include(error.message, "Unauthorized");
+    }
+  });
+
+  it("should allow admin to add a reward stream", async () => {
+    const poolBefore = await program.account.poolState.fetch(poolState);
+
+    await program.methods
+      .addRewardStream()
+      .accountsStrict({
+        admin: admin.publicKey,
+        poolState,
+        mint: rewardMint,
+        rewardStream,
+        vault: rewardVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([admin])
+      .rpc();
+
+    const poolAfter = await program.account.poolState.fetch(poolState);
+    const stream = await program.account.rewardStream.fetch(rewardStream);
+
+    assert.equal(poolAfter.rewardStreamCount, poolBefore.rewardStreamCount + 1);
+    assert.equal(stream.index, poolBefore.rewardStreamCount);
+    assert.isTrue(stream.mint.equals(rewardMint));
+    assert.isTrue(stream.vault.equals(rewardVault));
+  });
+
+  it("should reject a deposit that leaves out the reward streams", async () => {
+    try {
+      await depositUsdc(new BN(1_000_000))
+        .remainingAccounts(await getAumAccounts(program, poolState))
+        .signers([user1])
+        .rpc();
+
+      assert.fail("Expected transaction to fail without reward streams");
+    } catch (error: any) {
+      assert.include(error.message, "InvalidRewardStreamAccounts");
+    }
+  });
+
+  it("should allow admin to fund a reward stream", async () => {
+    // Give user1 an LP position before emissions start
+    await depositUsdc(new BN(5_000_000))
+      .remainingAccounts(await getLiquidityAccounts(program, poolState))
+      .signers([user1])
+      .rpc();
+
+    await program.methods
+      .fundRewardStream(rewardAmount, rewardDuration)
+      .accountsStrict({
+        admin: admin.publicKey,
+        poolState,
+        rewardStream,
+        adminTokenAccount: adminRewardAccount,
+        vault: rewardVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+    const stream = await program.account.rewardStream.fetch(rewardStream);
+    const vault = await getAccount(provider.connection, rewardVault);
+
+    assert.equal(vault.amount.toString(), rewardAmount.toString());
+    assert.equal(
+      stream.tokensPerInterval.toString(),
+      rewardAmount.div(rewardDuration).toString()
+    );
+    assert.equal(
+      stream.rewardEndTime.sub(stream.rewardStartTime).toString(),
+      rewardDuration.toString()
+    );
+  });
+
+  it("should let an LP claim from a reward stream", async () => {
+    // Let some rewards accrue
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
+    const balanceBefore = (
+      await getAccount(provider.connection, user1RewardAccount)
+    ).amount;
+
+    await program.methods
+      .claimStreamRewards()
+      .accountsStrict({
+        user: user1.publicKey,
+        poolState,
+        userState: user1State,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: rewardStream, isSigner: false, isWritable: true },
+        { pubkey: rewardVault, isSigner: false, isWritable: true },
+        { pubkey: user1RewardAccount, isSigner: false, isWritable: true },
+      ])
+      .signers([user1])
+      .rpc();
+
+    const balanceAfter = (
+      await getAccount(provider.connection, user1RewardAccount)
+    ).amount;
+    const stream = await program.account.rewardStream.fetch(rewardStream);
+
+    assert.isTrue(balanceAfter > balanceBefore, "LP should receive rewards");
+    assert.equal(
+      stream.totalRewardsClaimed.toString(),
+      (balanceAfter - balanceBefore).toString()
+    );
+  });
+});
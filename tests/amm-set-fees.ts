@@ -0,0 +1,232 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { assert } from "chai";
+import * as dotenv from "dotenv";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import {
+  setupAmmProgram,
+  DEFAULT_FEE_BPS,
+  DEFAULT_TARGET_WEIGHT_BPS,
+} from "./helpers/init-amm-program";
+
+dotenv.config();
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("perp-amm (with configuration persistence)", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+
+  // Required for initialization
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  // Use a fixed keypair for admin
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  // Set up pool state and asset registry entries
+  let poolState: PublicKey;
+  let solAsset: PublicKey;
+  let usdcAsset: PublicKey;
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    solAsset = setup.solAsset;
+    usdcAsset = setup.usdcAsset;
+  });
+
+  describe("set_fees", () => {
+    it("should allow admin to update an asset's fee rates", async () => {
+      await program.methods
+        .setFees(5, 20)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          asset: solAsset,
+        })
+        .signers([admin])
+        .rpc();
+
+      const solAssetAccount = await program.account.assetConfig.fetch(solAsset);
+      assert.equal(solAssetAccount.depositFeeBps, 5);
+      assert.equal(solAssetAccount.withdrawFeeBps, 20);
+
+      // Other assets keep their own rates
+      const usdcAssetAccount = await program.account.assetConfig.fetch(
+        usdcAsset
+      );
+      assert.equal(usdcAssetAccount.depositFeeBps, DEFAULT_FEE_BPS);
+      assert.equal(usdcAssetAccount.withdrawFeeBps, DEFAULT_FEE_BPS);
+    });
+
+    it("should reject fee rates above the maximum", async () => {
+      try {
+        await program.methods
+          .setFees(10, 101)
+          .accountsStrict({
+            admin: admin.publicKey,
+            poolState,
+            asset: usdcAsset,
+          })
+          .signers([admin])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "FeeTooHigh");
+      }
+    });
+
+    it("should not allow non-admin to update fee rates", async () => {
+      try {
+        await program.methods
+          .setFees(0, 0)
+          .accountsStrict({
+            admin: user1.publicKey,
+            poolState,
+            asset: solAsset,
+          })
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    after(async () => {
+      // Restore the default 0.1% fees so other suites see the expected amounts
+      await program.methods
+        .setFees(DEFAULT_FEE_BPS, DEFAULT_FEE_BPS)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          asset: solAsset,
+        })
+        .signers([admin])
+        .rpc();
+    });
+  });
+
+  describe("set_target_weight", () => {
+    it("should allow admin to set an asset's target weight", async () => {
+      await program.methods
+        .setTargetWeight(3_000)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          asset: solAsset,
+        })
+        .signers([admin])
+        .rpc();
+
+      const solAssetAccount = await program.account.assetConfig.fetch(solAsset);
+      assert.equal(solAssetAccount.targetWeightBps, 3_000);
+
+      const poolStateAccount = await program.account.poolState.fetch(poolState);
+      assert.equal(
+        poolStateAccount.totalTargetWeightBps,
+        3_000 + DEFAULT_TARGET_WEIGHT_BPS
+      );
+    });
+
+    it("should not allow non-admin to set target weights", async () => {
+      try {
+        await program.methods
+          .setTargetWeight(5_000)
+          .accountsStrict({
+            admin: user1.publicKey,
+            poolState,
+            asset: solAsset,
+          })
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    after(async () => {
+      // Restore the even split
+      await program.methods
+        .setTargetWeight(DEFAULT_TARGET_WEIGHT_BPS)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          asset: solAsset,
+        })
+        .signers([admin])
+        .rpc();
+    });
+  });
+
+  describe("set_dynamic_fee_tax", () => {
+    it("should allow admin to set the dynamic fee tax", async () => {
+      await program.methods
+        .setDynamicFeeTax(50)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+        })
+        .signers([admin])
+        .rpc();
+
+      const poolStateAccount = await program.account.poolState.fetch(poolState);
+      assert.equal(poolStateAccount.dynamicFeeTaxBps, 50);
+    });
+
+    it("should reject a tax above the maximum", async () => {
+      try {
+        await program.methods
+          .setDynamicFeeTax(101)
+          .accountsStrict({
+            admin: admin.publicKey,
+            poolState,
+          })
+          .signers([admin])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "FeeTooHigh");
+      }
+    });
+
+    after(async () => {
+      // Disable dynamic fees again
+      await program.methods
+        .setDynamicFeeTax(0)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+        })
+        .signers([admin])
+        .rpc();
+    });
+  });
+});
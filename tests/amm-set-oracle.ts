@@ -0,0 +1,179 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { assert } from "chai";
+import BN from "bn.js";
+import * as dotenv from "dotenv";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import {
+  setupAmmProgram,
+  MAX_PRICE_AGE,
+  MAX_CONFIDENCE_BPS,
+  NO_FEED_ID,
+} from "./helpers/init-amm-program";
+
+dotenv.config();
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("perp-amm (with configuration persistence)", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+
+  // Required for initialization
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  // Use a fixed keypair for admin
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  // Set up pool state and the SOL registry entry
+  let poolState: PublicKey;
+  let solAsset: PublicKey;
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    solAsset = setup.solAsset;
+  });
+
+  describe("set_oracle", () => {
+    it("should allow admin to set an asset's oracle feed", async () => {
+      await program.methods
+        .setOracle({ chainlink: {} }, NO_FEED_ID, new BN(MAX_PRICE_AGE), MAX_CONFIDENCE_BPS)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          asset: solAsset,
+          oracleProgram: chainlinkProgram,
+          oracleFeed: chainlinkFeed,
+        })
+        .signers([admin])
+        .rpc();
+
+      const solAssetAccount = await program.account.assetConfig.fetch(solAsset);
+      assert.deepEqual(solAssetAccount.oracleKind, { chainlink: {} });
+      assert.equal(
+        solAssetAccount.oracleProgram.toString(),
+        chainlinkProgram.toString()
+      );
+      assert.equal(
+        solAssetAccount.oracleFeed.toString(),
+        chainlinkFeed.toString()
+      );
+      assert.equal(
+        solAssetAccount.maxPriceAge.toString(),
+        MAX_PRICE_AGE.toString()
+      );
+    });
+
+    it("should emit an OracleUpdated event for the pool", async () => {
+      const signature = await program.methods
+        .setOracle({ chainlink: {} }, NO_FEED_ID, new BN(MAX_PRICE_AGE), MAX_CONFIDENCE_BPS)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          asset: solAsset,
+          oracleProgram: chainlinkProgram,
+          oracleFeed: chainlinkFeed,
+        })
+        .signers([admin])
+        .rpc({ commitment: "confirmed" });
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const events = [...parser.parseLogs(tx.meta.logMessages)];
+      const updated = events.find((event) => event.name === "oracleUpdated");
+
+      assert.isDefined(updated, "OracleUpdated event should be emitted");
+      assert.isTrue(updated.data.pool.equals(poolState));
+      assert.isTrue(updated.data.oracleFeed.equals(chainlinkFeed));
+      assert.deepEqual(updated.data.oracleKind, { chainlink: {} });
+    });
+
+    it("should reject a feed whose price is too old", async () => {
+      try {
+        // The cloned feed is never updated on localnet, so a 1 second window always fails
+        await program.methods
+          .setOracle({ chainlink: {} }, NO_FEED_ID, new BN(1), MAX_CONFIDENCE_BPS)
+          .accountsStrict({
+            admin: admin.publicKey,
+            poolState,
+            asset: solAsset,
+            oracleProgram: chainlinkProgram,
+            oracleFeed: chainlinkFeed,
+          })
+          .signers([admin])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "StaleOraclePrice");
+      }
+    });
+
+    it("should reject a zero max price age", async () => {
+      try {
+        await program.methods
+          .setOracle({ chainlink: {} }, NO_FEED_ID, new BN(0), MAX_CONFIDENCE_BPS)
+          .accountsStrict({
+            admin: admin.publicKey,
+            poolState,
+            asset: solAsset,
+            oracleProgram: chainlinkProgram,
+            oracleFeed: chainlinkFeed,
+          })
+          .signers([admin])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "InvalidOracleConfig");
+      }
+    });
+
+    it("should not allow non-admin to set the oracle feed", async () => {
+      try {
+        await program.methods
+          .setOracle({ chainlink: {} }, NO_FEED_ID, new BN(MAX_PRICE_AGE), MAX_CONFIDENCE_BPS)
+          .accountsStrict({
+            admin: user1.publicKey,
+            poolState,
+            asset: solAsset,
+            oracleProgram: chainlinkProgram,
+            oracleFeed: chainlinkFeed,
+          })
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected error but transaction succeeded");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+});
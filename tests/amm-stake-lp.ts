@@ -0,0 +1,255 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  transfer,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import BN from "bn.js";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import {
+  getLiquidityAccounts,
+  getRewardStreamAccounts,
+} from "./helpers/aum-accounts";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("perp-amm LP staking", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  let poolState: PublicKey;
+  let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
+  let usdcAsset: PublicKey;
+  let usdcVault: PublicKey;
+  let user1UsdcAccount: PublicKey;
+  let user1LpTokenAccount: PublicKey;
+  let user2LpTokenAccount: PublicKey;
+  let user1State: PublicKey;
+  let user2State: PublicKey;
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
+    usdcAsset = setup.usdcAsset;
+    usdcVault = setup.usdcVault;
+    user1UsdcAccount = setup.user1UsdcAccount;
+    user1State = getUserStatePda(program, poolState, user1.publicKey);
+    user2State = getUserStatePda(program, poolState, user2.publicKey);
+
+    user1LpTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        admin,
+        lpTokenMint,
+        user1.publicKey
+      )
+    ).address;
+    user2LpTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        admin,
+        lpTokenMint,
+        user2.publicKey
+      )
+    ).address;
+
+    await program.methods
+      .deposit(new BN(10_000_000), new BN(0), []) // 10 USDC
+      .accountsStrict({
+        user: user1.publicKey,
+        poolState,
+        userTokenAccount: user1UsdcAccount,
+        vaultAccount: usdcVault,
+        asset: usdcAsset,
+        userState: user1State,
+        lpTokenMint,
+        lpStakeVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        allowlistEntry: null,
+      })
+      .remainingAccounts(await getLiquidityAccounts(program, poolState))
+      .signers([user1])
+      .rpc();
+  });
+
+  const unstakeLp = async (
+    user: Keypair,
+    userState: PublicKey,
+    userLpTokenAccount: PublicKey,
+    amount: BN
+  ) =>
+    program.methods
+      .unstakeLp(amount)
+      .accountsStrict({
+        user: user.publicKey,
+        poolState,
+        userState,
+        lpTokenMint,
+        userLpTokenAccount,
+        lpStakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(await getRewardStreamAccounts(program, poolState))
+      .signers([user])
+      .rpc();
+
+  const stakeLp = async (
+    user: Keypair,
+    userState: PublicKey,
+    userLpTokenAccount: PublicKey,
+    amount: BN
+  ) =>
+    program.methods
+      .stakeLp(amount)
+      .accountsStrict({
+        user: user.publicKey,
+        poolState,
+        userState,
+        lpTokenMint,
+        userLpTokenAccount,
+        lpStakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(await getRewardStreamAccounts(program, poolState))
+      .signers([user])
+      .rpc();
+
+  it("should stake the LP minted by a deposit", async () => {
+    const userState = await program.account.userState.fetch(user1State);
+    const pool = await program.account.poolState.fetch(poolState);
+    const vault = await getAccount(provider.connection, lpStakeVault);
+
+    assert.isTrue(userState.lpTokenBalance.gtn(0));
+    assert.equal(
+      pool.totalStakedLp.toString(),
+      vault.amount.toString(),
+      "Staked total should match the stake vault"
+    );
+    assert.equal(
+      (await getAccount(provider.connection, user1LpTokenAccount)).amount,
+      BigInt(0),
+      "Deposits should not mint LP to the wallet"
+    );
+  });
+
+  it("should unstake LP to the user's wallet", async () => {
+    const staked = (await program.account.userState.fetch(user1State))
+      .lpTokenBalance;
+    const amount = staked.divn(2);
+    const poolBefore = await program.account.poolState.fetch(poolState);
+
+    await unstakeLp(user1, user1State, user1LpTokenAccount, amount);
+
+    const userState = await program.account.userState.fetch(user1State);
+    const poolAfter = await program.account.poolState.fetch(poolState);
+    assert.equal(
+      userState.lpTokenBalance.toString(),
+      staked.sub(amount).toString()
+    );
+    assert.equal(
+      poolBefore.totalStakedLp.sub(poolAfter.totalStakedLp).toString(),
+      amount.toString()
+    );
+    assert.equal(
+      (await getAccount(provider.connection, user1LpTokenAccount)).amount.toString(),
+      amount.toString()
+    );
+  });
+
+  it("should fail to unstake more LP than is staked", async () => {
+    const staked = (await program.account.userState.fetch(user1State))
+      .lpTokenBalance;
+
+    try {
+      await unstakeLp(user1, user1State, user1LpTokenAccount, staked.addn(1));
+      assert.fail("Expected transaction to fail with insufficient LP balance");
+    } catch (error: any) {
+      assert.include(error.message, "InsufficientLpBalance");
+    }
+  });
+
+  it("should only credit transferred LP once the new holder stakes it", async () => {
+    const amount = new BN(
+      (await getAccount(provider.connection, user1LpTokenAccount)).amount.toString()
+    );
+    await transfer(
+      provider.connection,
+      user1,
+      user1LpTokenAccount,
+      user2LpTokenAccount,
+      user1,
+      BigInt(amount.toString())
+    );
+
+    // Holding LP alone doesn't create a stake
+    assert.isNull(await provider.connection.getAccountInfo(user2State));
+
+    const poolBefore = await program.account.poolState.fetch(poolState);
+    await stakeLp(user2, user2State, user2LpTokenAccount, amount);
+
+    const userState = await program.account.userState.fetch(user2State);
+    const poolAfter = await program.account.poolState.fetch(poolState);
+    assert.equal(userState.lpTokenBalance.toString(), amount.toString());
+    assert.isTrue(userState.owner.equals(user2.publicKey));
+    assert.equal(
+      poolAfter.totalStakedLp.sub(poolBefore.totalStakedLp).toString(),
+      amount.toString()
+    );
+  });
+
+  it("should not let a user close their state with LP still staked", async () => {
+    try {
+      await program.methods
+        .closeUserState()
+        .accountsStrict({
+          user: user2.publicKey,
+          poolState,
+          userState: user2State,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user2])
+        .rpc();
+      assert.fail("Expected transaction to fail with LP still staked");
+    } catch (error: any) {
+      assert.include(error.message, "StakedLpRemaining");
+    }
+  });
+});
@@ -66,6 +66,7 @@ describe("perp-amm (with configuration persistence)", () => {
   // Test parameters
   const rewardRate = new BN(100_000); // USDC per second for rewards
   const rewardAmount = new BN(10_000_000_000); // 10,000 USDC with 6 decimals
+  const rewardDuration = new BN(604800); // One week
 
   // Global configuration state
   let configInitialized = false;
@@ -127,7 +128,7 @@ describe("perp-amm (with configuration persistence)", () => {
 
       // Start rewards
       await program.methods
-        .startRewards(rewardAmount)
+        .startRewards(rewardAmount, rewardDuration)
         .accountsStrict({
           admin: admin.publicKey,
           poolState,
@@ -167,15 +168,22 @@ describe("perp-amm (with configuration persistence)", () => {
       );
 
       assert.equal(
-        poolStateAfter.totalRewardsDeposited.toString(),
+        poolStateAfter.totalRewardsDeposited
+          .sub(poolStateBefore.totalRewardsDeposited)
+          .toString(),
         rewardAmount.toString(),
-        "Total rewards deposited should change to the amount deposited"
+        "Total rewards deposited should increase by the amount deposited"
+      );
+
+      assert.isTrue(
+        poolStateAfter.tokensPerInterval.gte(rewardAmount.div(rewardDuration)),
+        "Tokens per interval should cover the deposit over the duration"
       );
 
       assert.equal(
-        poolStateAfter.tokensPerInterval.toString(),
-        rewardAmount.div(new BN(604800)).toString(),
-        "Tokens per interval should be set correctly"
+        poolStateAfter.rewardEndTime.sub(poolStateAfter.rewardStartTime).toString(),
+        rewardDuration.toString(),
+        "Reward period should last the requested duration"
       );
 
       assert.isTrue(
@@ -187,7 +195,7 @@ describe("perp-amm (with configuration persistence)", () => {
     it("should fail if non-admin tries to start rewards", async () => {
       try {
         await program.methods
-          .startRewards(new BN(1000000))
+          .startRewards(new BN(1000000), rewardDuration)
           .accountsStrict({
             admin: user1.publicKey,
             poolState,
@@ -208,16 +216,16 @@ describe("perp-amm (with configuration persistence)", () => {
       }
     });
 
-    it("should allow admin to update reward rate", async () => {
-      // Get pool state before updating rewards
+    it("should roll unemitted rewards into a top-up", async () => {
+      // Get pool state before topping up rewards
       const poolStateBefore = await program.account.poolState.fetch(poolState);
 
       // 10 USDC of rewards
       const rewardsToDeposit = new BN(10_000_000);
 
-      // Update rewards without adding more USDC
+      // Top up mid-period
       await program.methods
-        .startRewards(rewardsToDeposit)
+        .startRewards(rewardsToDeposit, rewardDuration)
         .accountsStrict({
           admin: admin.publicKey,
           poolState,
@@ -228,29 +236,89 @@ describe("perp-amm (with configuration persistence)", () => {
         .signers([admin])
         .rpc();
 
-      // Get pool state after updating rewards
+      // Get pool state after topping up rewards
       const poolStateAfter = await program.account.poolState.fetch(poolState);
 
-      const expectedTokensPerInterval = rewardsToDeposit.div(new BN(604800));
-
-      // Verify state changes
-      assert.equal(
-        poolStateAfter.tokensPerInterval.toString(),
-        expectedTokensPerInterval.toString(),
-        "Tokens per interval should be updated"
+      // The running period's remaining emissions carry over, so the new rate
+      // is well above what the top-up alone would fund
+      assert.isTrue(
+        poolStateAfter.tokensPerInterval.gt(
+          rewardsToDeposit.div(rewardDuration)
+        ),
+        "Tokens per interval should include rolled-over rewards"
       );
 
       assert.equal(
-        poolStateAfter.totalRewardsDeposited.toString(),
+        poolStateAfter.totalRewardsDeposited
+          .sub(poolStateBefore.totalRewardsDeposited)
+          .toString(),
         rewardsToDeposit.toString(),
-        "Total rewards deposited should change to the amount deposited"
+        "Total rewards deposited should accumulate"
       );
 
       assert.isTrue(
-        poolStateAfter.lastDistributionTime.toNumber() >=
-          poolStateBefore.lastDistributionTime.toNumber(),
-        "Last distribution time should be updated"
+        poolStateAfter.rewardEndTime.gte(poolStateBefore.rewardEndTime),
+        "Reward period should be extended"
       );
     });
+
+    it("should carry the rate's rounding remainder into the next top-up", async () => {
+      const poolStateBefore = await program.account.poolState.fetch(poolState);
+
+      // 7 USDC and change over a week doesn't divide evenly
+      const rewardsToDeposit = new BN(7_123_457);
+
+      await program.methods
+        .startRewards(rewardsToDeposit, rewardDuration)
+        .accountsStrict({
+          admin: admin.publicKey,
+          poolState,
+          adminUsdcAccount,
+          usdcRewardVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+      const poolStateAfter = await program.account.poolState.fetch(poolState);
+      const remainder = new BN(poolStateAfter.rewardRemainder);
+
+      assert.isTrue(
+        remainder.lt(rewardDuration),
+        "Remainder should be below the duration"
+      );
+
+      // The new period emits everything it was funded with: the top-up, plus
+      // what was rolled over, which includes the previous remainder
+      const funded = poolStateAfter.tokensPerInterval
+        .mul(rewardDuration)
+        .add(remainder);
+      assert.isTrue(
+        funded
+          .sub(rewardsToDeposit)
+          .gte(new BN(poolStateBefore.rewardRemainder)),
+        "Previous remainder should roll into the new period"
+      );
+    });
+
+    it("should fail to start rewards with a zero duration", async () => {
+      try {
+        await program.methods
+          .startRewards(new BN(1_000_000), new BN(0))
+          .accountsStrict({
+            admin: admin.publicKey,
+            poolState,
+            adminUsdcAccount,
+            usdcRewardVault,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([admin])
+          .rpc();
+
+        assert.fail("Expected transaction to fail with an invalid duration");
+      } catch (error: any) {
+        assert.include(error.message, "InvalidRewardDuration");
+      }
+    });
   });
 });
@@ -0,0 +1,286 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PerpAmm } from "../target/types/perp_amm";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import BN from "bn.js";
+import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
+import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import { PAUSE_DEPOSITS, PAUSE_WITHDRAWALS } from "./helpers/pause-flags";
+import {
+  getAumAccounts,
+  getLiquidityAccounts,
+} from "./helpers/aum-accounts";
+
+// Get the deployed chainlink_mock program
+const chainlinkProgram = new PublicKey(
+  "HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny"
+);
+
+// Devnet SOL/USD Price Feed
+const chainlinkFeed = new PublicKey(
+  "99B2bTijsU6f1GCT73HmdR7HCFFjGMBcPZY6jZ96ynrR"
+);
+
+describe("perp-amm views", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.PerpAmm as Program<PerpAmm>;
+  const marginProgram = anchor.workspace
+    .PerpMarginAccounts as Program<PerpMarginAccounts>;
+
+  const admin = Keypair.fromSeed(Uint8Array.from(Array(32).fill(1)));
+  const user1 = Keypair.generate();
+  const user2 = Keypair.generate();
+
+  let poolState: PublicKey;
+  let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
+  let usdcAsset: PublicKey;
+  let usdcVault: PublicKey;
+  let user1UsdcAccount: PublicKey;
+
+  const usdcAmount = new BN(5_000_000); // 5 USDC
+
+  before(async () => {
+    const setup = await setupAmmProgram(
+      provider,
+      program,
+      marginProgram,
+      chainlinkProgram,
+      chainlinkFeed,
+      admin,
+      user1,
+      user2
+    );
+
+    poolState = setup.poolState;
+    lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
+    usdcAsset = setup.usdcAsset;
+    usdcVault = setup.usdcVault;
+    user1UsdcAccount = setup.user1UsdcAccount;
+  });
+
+  it("should return AUM as the sum of every asset's value", async () => {
+    const aum = await program.methods
+      .getAum()
+      .accountsStrict({ poolState, lpTokenMint })
+      .remainingAccounts(await getAumAccounts(program, poolState))
+      .view();
+
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(aum.prices.length, pool.assetCount);
+    assert.equal(aum.assetUsd.length, pool.assetCount);
+
+    const sum = aum.assetUsd.reduce(
+      (acc: BN, value: BN) => acc.add(value),
+      new BN(0)
+    );
+    assert.equal(aum.totalUsd.toString(), sum.toString());
+  });
+
+  it("should return a positive LP price", async () => {
+    const price = await program.methods
+      .lpPrice()
+      .accountsStrict({ poolState, lpTokenMint })
+      .remainingAccounts(await getAumAccounts(program, poolState))
+      .view();
+
+    assert.isTrue(price.gtn(0), "LP price should be positive");
+  });
+
+  // Preview a USDC deposit by `user`, passing their user state only if it exists
+  const previewDeposit = async (user: PublicKey, amount: BN) => {
+    const userState = getUserStatePda(program, poolState, user);
+    const hasUserState =
+      (await provider.connection.getAccountInfo(userState)) !== null;
+
+    return program.methods
+      .previewDeposit(amount, [])
+      .accountsStrict({
+        poolState,
+        asset: usdcAsset,
+        lpTokenMint,
+        user,
+        userState: hasUserState ? userState : null,
+        allowlistEntry: null,
+      })
+      .remainingAccounts(await getAumAccounts(program, poolState))
+      .view();
+  };
+
+  it("should preview exactly what a deposit mints", async () => {
+    const quote = await previewDeposit(user1.publicKey, usdcAmount);
+
+    assert.equal(
+      quote.depositAmount.add(quote.feeAmount).toString(),
+      usdcAmount.toString()
+    );
+
+    // Deposits stake the LP they mint
+    const lpBefore = (await getAccount(provider.connection, lpStakeVault))
+      .amount;
+
+    await program.methods
+      .deposit(usdcAmount, quote.lpToMint, [])
+      .accountsStrict({
+        user: user1.publicKey,
+        poolState,
+        userTokenAccount: user1UsdcAccount,
+        vaultAccount: usdcVault,
+        asset: usdcAsset,
+        userState: getUserStatePda(program, poolState, user1.publicKey),
+        lpTokenMint,
+        lpStakeVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        allowlistEntry: null,
+      })
+      .remainingAccounts(await getLiquidityAccounts(program, poolState))
+      .signers([user1])
+      .rpc();
+
+    const lpAfter = (await getAccount(provider.connection, lpStakeVault))
+      .amount;
+    assert.equal(
+      (lpAfter - lpBefore).toString(),
+      quote.lpToMint.toString(),
+      "Minted LP should match the preview"
+    );
+  });
+
+  it("should reject a preview while deposits are paused", async () => {
+    await program.methods
+      .pause(PAUSE_DEPOSITS)
+      .accountsStrict({ pauser: admin.publicKey, poolState })
+      .signers([admin])
+      .rpc();
+
+    try {
+      await previewDeposit(user1.publicKey, usdcAmount);
+      assert.fail("Expected error but preview succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "OperationPaused");
+    } finally {
+      await program.methods
+        .unpause(PAUSE_DEPOSITS)
+        .accountsStrict({ pauser: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+    }
+  });
+
+  it("should reject a preview over the per-wallet cap", async () => {
+    const setDepositLimits = (maxAumUsd: BN, maxUserLp: BN) =>
+      program.methods
+        .setDepositLimits(maxAumUsd, maxUserLp)
+        .accountsStrict({ admin: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+
+    // user1 already holds more than 1 LP unit from the deposit above
+    await setDepositLimits(new BN(0), new BN(1));
+
+    try {
+      await previewDeposit(user1.publicKey, usdcAmount);
+      assert.fail("Expected error but preview succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "UserLpCapExceeded");
+    } finally {
+      // Other suites share this pool, so leave it uncapped
+      await setDepositLimits(new BN(0), new BN(0));
+    }
+  });
+
+  // Preview a USDC withdrawal by `user`, passing their user state only if it exists
+  const previewWithdraw = async (user: PublicKey, lpAmount: BN) => {
+    const userState = getUserStatePda(program, poolState, user);
+    const hasUserState =
+      (await provider.connection.getAccountInfo(userState)) !== null;
+
+    return program.methods
+      .previewWithdraw(lpAmount)
+      .accountsStrict({
+        poolState,
+        asset: usdcAsset,
+        lpTokenMint,
+        user,
+        userState: hasUserState ? userState : null,
+      })
+      .remainingAccounts(await getAumAccounts(program, poolState))
+      .view();
+  };
+
+  it("should preview a withdrawal net of fees", async () => {
+    const lpBalance = (
+      await program.account.userState.fetch(
+        getUserStatePda(program, poolState, user1.publicKey)
+      )
+    ).lpTokenBalance;
+
+    const quote = await previewWithdraw(
+      user1.publicKey,
+      new BN(lpBalance.toString())
+    );
+
+    assert.equal(
+      quote.withdrawalAmount.add(quote.feeAmount).toString(),
+      quote.tokenAmount.toString()
+    );
+    assert.isTrue(quote.withdrawalAmount.gtn(0));
+  });
+
+  it("should reject a withdrawal preview for more LP than the user has staked", async () => {
+    const lpBalance = (
+      await program.account.userState.fetch(
+        getUserStatePda(program, poolState, user1.publicKey)
+      )
+    ).lpTokenBalance;
+
+    try {
+      await previewWithdraw(
+        user1.publicKey,
+        new BN(lpBalance.toString()).addn(1)
+      );
+      assert.fail("Expected error but preview succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "InsufficientLpBalance");
+    }
+
+    // A wallet without a user state has nothing staked
+    try {
+      await previewWithdraw(Keypair.generate().publicKey, new BN(1));
+      assert.fail("Expected error but preview succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "InsufficientLpBalance");
+    }
+  });
+
+  it("should reject a withdrawal preview while withdrawals are paused", async () => {
+    await program.methods
+      .pause(PAUSE_WITHDRAWALS)
+      .accountsStrict({ pauser: admin.publicKey, poolState })
+      .signers([admin])
+      .rpc();
+
+    try {
+      await previewWithdraw(user1.publicKey, new BN(1));
+      assert.fail("Expected error but preview succeeded");
+    } catch (error: any) {
+      assert.include(error.message, "OperationPaused");
+    } finally {
+      await program.methods
+        .unpause(PAUSE_WITHDRAWALS)
+        .accountsStrict({ pauser: admin.publicKey, poolState })
+        .signers([admin])
+        .rpc();
+    }
+  });
+});
@@ -8,6 +8,7 @@ import {
   SystemProgram,
 } from "@solana/web3.js";
 import {
+  NATIVE_MINT,
   TOKEN_PROGRAM_ID,
   getAccount,
   getOrCreateAssociatedTokenAccount,
@@ -19,6 +20,8 @@ import BN from "bn.js";
 import * as dotenv from "dotenv";
 import { PerpMarginAccounts } from "../target/types/perp_margin_accounts";
 import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getUserStatePda } from "./helpers/pool-pdas";
+import { getLiquidityAccounts } from "./helpers/aum-accounts";
 import { wrapSol } from "./helpers/wrap-sol";
 
 dotenv.config();
@@ -53,7 +56,10 @@ describe("perp-amm (with configuration persistence)", () => {
   let usdcMint: PublicKey;
   let solVault: PublicKey;
   let usdcVault: PublicKey;
+  let solAsset: PublicKey;
+  let usdcAsset: PublicKey;
   let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
   let solMint: PublicKey;
 
   // Set up pool state
@@ -65,10 +71,6 @@ describe("perp-amm (with configuration persistence)", () => {
   let user1UsdcAccount: PublicKey;
   let user2UsdcAccount: PublicKey;
 
-  // User LP token accounts
-  let user1LpTokenAccount: PublicKey;
-  let user2LpTokenAccount: PublicKey;
-
   // User states
   let user1State: PublicKey;
   let user2State: PublicKey;
@@ -101,42 +103,20 @@ describe("perp-amm (with configuration persistence)", () => {
     solMint = setup.solMint;
     usdcMint = setup.usdcMint;
     lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
     solVault = setup.solVault;
     usdcVault = setup.usdcVault;
+    solAsset = setup.solAsset;
+    usdcAsset = setup.usdcAsset;
     adminSolAccount = setup.adminSolAccount;
     adminUsdcAccount = setup.adminUsdcAccount;
     user1UsdcAccount = setup.user1UsdcAccount;
     user2UsdcAccount = setup.user2UsdcAccount;
 
-    // Create LP token accounts for users
-    user1LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user1.publicKey
-      )
-    ).address;
-
-    user2LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user2.publicKey
-      )
-    ).address;
-
     // Derive user states
-    [user1State] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_state"), user1.publicKey.toBuffer()],
-      program.programId
-    );
+    user1State = getUserStatePda(program, poolState, user1.publicKey);
 
-    [user2State] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_state"), user2.publicKey.toBuffer()],
-      program.programId
-    );
+    user2State = getUserStatePda(program, poolState, user2.publicKey);
 
     configInitialized = true;
   });
@@ -172,20 +152,21 @@ describe("perp-amm (with configuration persistence)", () => {
 
         // User1 deposit SOL to earn LP tokens
         await program.methods
-          .deposit(initialSolDeposit)
+          .deposit(initialSolDeposit, new BN(0), [])
           .accountsStrict({
             user: user1.publicKey,
             poolState,
             userTokenAccount: user1SolAccount,
             vaultAccount: solVault,
+            asset: solAsset,
             userState: user1State,
             lpTokenMint,
-            userLpTokenAccount: user1LpTokenAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            lpStakeVault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user1])
           .rpc();
 
@@ -201,20 +182,21 @@ describe("perp-amm (with configuration persistence)", () => {
 
         // User2 deposit USDC to earn LP tokens
         await program.methods
-          .deposit(initialUsdcDeposit)
+          .deposit(initialUsdcDeposit, new BN(0), [])
           .accountsStrict({
             user: user2.publicKey,
             poolState,
             userTokenAccount: user2UsdcAccount,
             vaultAccount: usdcVault,
+            asset: usdcAsset,
             userState: user2State,
             lpTokenMint,
-            userLpTokenAccount: user2LpTokenAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            lpStakeVault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            allowlistEntry: null,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user2])
           .rpc();
       } catch (error) {
@@ -234,8 +216,8 @@ describe("perp-amm (with configuration persistence)", () => {
       ).address;
 
       // Get balances before withdrawal
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
-      const solDepositedBefore = poolStateBefore.solDeposited;
+      const solAssetBefore = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedBefore = solAssetBefore.deposited;
       const userStateBefore = await program.account.userState.fetch(user1State);
       const user1SolBefore = await getAccount(
         provider.connection,
@@ -245,8 +227,8 @@ describe("perp-amm (with configuration persistence)", () => {
         await getMint(provider.connection, lpTokenMint)
       ).supply;
       const user1LpBalanceBefore = (
-        await getAccount(provider.connection, user1LpTokenAccount)
-      ).amount;
+        await program.account.userState.fetch(user1State)
+      ).lpTokenBalance;
 
       // Skip test if user doesn't have LP tokens
       if (new BN(user1LpBalanceBefore.toString()).eqn(0)) {
@@ -259,26 +241,26 @@ describe("perp-amm (with configuration persistence)", () => {
 
       // Withdraw WSOL
       await program.methods
-        .withdraw(withdrawLpAmount)
+        .withdraw(withdrawLpAmount, new BN(0))
         .accountsStrict({
           user: user1.publicKey,
           poolState,
           userState: user1State,
           lpTokenMint,
-          userLpTokenAccount: user1LpTokenAccount,
+          lpStakeVault,
           vaultAccount: solVault,
+          asset: solAsset,
           userTokenAccount: user1SolAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
         .signers([user1])
         .rpc();
 
       // Get balances after withdrawal
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
-      const solDepositedAfter = poolStateAfter.solDeposited;
+      const solAssetAfter = await program.account.assetConfig.fetch(solAsset);
+      const solDepositedAfter = solAssetAfter.deposited;
       const userStateAfter = await program.account.userState.fetch(user1State);
       const user1SolAfter = await getAccount(
         provider.connection,
@@ -288,8 +270,8 @@ describe("perp-amm (with configuration persistence)", () => {
         await getMint(provider.connection, lpTokenMint)
       ).supply;
       const user1LpBalanceAfter = (
-        await getAccount(provider.connection, user1LpTokenAccount)
-      ).amount;
+        await program.account.userState.fetch(user1State)
+      ).lpTokenBalance;
 
       // Verify state changes
       assert.isTrue(
@@ -307,7 +289,7 @@ describe("perp-amm (with configuration persistence)", () => {
       );
 
       assert.isTrue(
-        poolStateBefore.solDeposited.gt(poolStateAfter.solDeposited),
+        solAssetBefore.deposited.gt(solAssetAfter.deposited),
         "Pool SOL deposited should decrease"
       );
 
@@ -337,8 +319,8 @@ describe("perp-amm (with configuration persistence)", () => {
 
     it("should withdraw USDC from the pool", async () => {
       // Get balances before withdrawal
-      const poolStateBefore = await program.account.poolState.fetch(poolState);
-      const usdcDepositedBefore = poolStateBefore.usdcDeposited;
+      const usdcAssetBefore = await program.account.assetConfig.fetch(usdcAsset);
+      const usdcDepositedBefore = usdcAssetBefore.deposited;
       const userStateBefore = await program.account.userState.fetch(user2State);
       const user2UsdcBefore = await getAccount(
         provider.connection,
@@ -348,8 +330,8 @@ describe("perp-amm (with configuration persistence)", () => {
         await getMint(provider.connection, lpTokenMint)
       ).supply;
       const user2LpBalanceBefore = (
-        await getAccount(provider.connection, user2LpTokenAccount)
-      ).amount;
+        await program.account.userState.fetch(user2State)
+      ).lpTokenBalance;
 
       // Skip test if user doesn't have LP tokens
       if (new BN(user2LpBalanceBefore.toString()).eqn(0)) {
@@ -362,26 +344,26 @@ describe("perp-amm (with configuration persistence)", () => {
 
       // Withdraw USDC
       await program.methods
-        .withdraw(withdrawLpAmount)
+        .withdraw(withdrawLpAmount, new BN(0))
         .accountsStrict({
           user: user2.publicKey,
           poolState,
           userState: user2State,
           lpTokenMint,
-          userLpTokenAccount: user2LpTokenAccount,
+          lpStakeVault,
           vaultAccount: usdcVault,
+          asset: usdcAsset,
           userTokenAccount: user2UsdcAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
         .signers([user2])
         .rpc();
 
       // Get balances after withdrawal
-      const poolStateAfter = await program.account.poolState.fetch(poolState);
-      const usdcDepositedAfter = poolStateAfter.usdcDeposited;
+      const usdcAssetAfter = await program.account.assetConfig.fetch(usdcAsset);
+      const usdcDepositedAfter = usdcAssetAfter.deposited;
       const userStateAfter = await program.account.userState.fetch(user2State);
       const user2UsdcAfter = await getAccount(
         provider.connection,
@@ -391,8 +373,8 @@ describe("perp-amm (with configuration persistence)", () => {
         await getMint(provider.connection, lpTokenMint)
       ).supply;
       const user2LpBalanceAfter = (
-        await getAccount(provider.connection, user2LpTokenAccount)
-      ).amount;
+        await program.account.userState.fetch(user2State)
+      ).lpTokenBalance;
 
       // Verify state changes
       assert.isTrue(
@@ -410,7 +392,7 @@ describe("perp-amm (with configuration persistence)", () => {
       );
 
       assert.isTrue(
-        poolStateBefore.usdcDeposited.gt(poolStateAfter.usdcDeposited),
+        usdcAssetBefore.deposited.gt(usdcAssetAfter.deposited),
         "Pool USDC deposited should decrease"
       );
 
@@ -451,20 +433,20 @@ describe("perp-amm (with configuration persistence)", () => {
 
       try {
         await program.methods
-          .withdraw(new BN(0))
+          .withdraw(new BN(0), new BN(0))
           .accountsStrict({
             user: user1.publicKey,
             poolState,
             userState: user1State,
             lpTokenMint,
-            userLpTokenAccount: user1LpTokenAccount,
+            lpStakeVault,
             vaultAccount: solVault,
+            asset: solAsset,
             userTokenAccount: user1SolAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user1])
           .rpc();
 
@@ -489,28 +471,27 @@ describe("perp-amm (with configuration persistence)", () => {
         )
       ).address;
 
-      const user1LpBalance = await getAccount(
-        provider.connection,
-        user1LpTokenAccount
-      );
-      const excessAmount = new BN(user1LpBalance.amount.toString()).addn(1); // Balance + 1
+      const user1LpBalance = (
+        await program.account.userState.fetch(user1State)
+      ).lpTokenBalance;
+      const excessAmount = new BN(user1LpBalance.toString()).addn(1); // Balance + 1
 
       try {
         await program.methods
-          .withdraw(excessAmount)
+          .withdraw(excessAmount, new BN(0))
           .accountsStrict({
             user: user1.publicKey,
             poolState,
             userState: user1State,
             lpTokenMint,
-            userLpTokenAccount: user1LpTokenAccount,
+            lpStakeVault,
             vaultAccount: solVault,
+            asset: solAsset,
             userTokenAccount: user1SolAccount,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
           .signers([user1])
           .rpc();
 
@@ -523,5 +504,96 @@ describe("perp-amm (with configuration persistence)", () => {
         );
       }
     });
+
+    it("should withdraw native SOL from the pool without a WSOL account", async () => {
+      const [unwrapAccount] = PublicKey.findProgramAddressSync(
+        [Buffer.from("sol_unwrap"), user1.publicKey.toBuffer()],
+        program.programId
+      );
+
+      const lpBalance = (
+        await program.account.userState.fetch(user1State)
+      ).lpTokenBalance;
+      const withdrawAmount = new BN(lpBalance.toString()).divn(4);
+
+      const solAssetBefore = await program.account.assetConfig.fetch(solAsset);
+      const lamportsBefore = await provider.connection.getBalance(
+        user1.publicKey
+      );
+
+      await program.methods
+        .withdrawSol(withdrawAmount, new BN(0))
+        .accountsStrict({
+          user: user1.publicKey,
+          poolState,
+          userState: user1State,
+          lpTokenMint,
+          lpStakeVault,
+          asset: solAsset,
+          vaultAccount: solVault,
+          nativeMint: NATIVE_MINT,
+          unwrapAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await getLiquidityAccounts(program, poolState))
+        .signers([user1])
+        .rpc();
+
+      const solAssetAfter = await program.account.assetConfig.fetch(solAsset);
+      const lamportsAfter = await provider.connection.getBalance(
+        user1.publicKey
+      );
+
+      assert.isTrue(
+        lamportsAfter > lamportsBefore,
+        "User should receive native SOL"
+      );
+
+      assert.isTrue(
+        solAssetAfter.deposited.lt(solAssetBefore.deposited),
+        "SOL deposited should decrease"
+      );
+
+      assert.isNull(
+        await provider.connection.getAccountInfo(unwrapAccount),
+        "Temporary unwrap account should be closed"
+      );
+    });
+
+    it("should fail to withdraw if fewer tokens than min_tokens_out would be paid", async () => {
+      const user1SolAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          admin,
+          solMint,
+          user1.publicKey
+        )
+      ).address;
+
+      try {
+        await program.methods
+          .withdraw(new BN(1_000), new BN("18446744073709551615")) // u64::MAX lamports
+          .accountsStrict({
+            user: user1.publicKey,
+            poolState,
+            userState: user1State,
+            lpTokenMint,
+            lpStakeVault,
+            vaultAccount: solVault,
+            asset: solAsset,
+            userTokenAccount: user1SolAccount,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .remainingAccounts(await getLiquidityAccounts(program, poolState))
+          .signers([user1])
+          .rpc();
+
+        assert.fail("Expected transaction to fail with slippage exceeded");
+      } catch (error: any) {
+        assert.include(error.message, "SlippageExceeded");
+      }
+    });
   });
 });
@@ -0,0 +1,46 @@
+import { PublicKey } from "@solana/web3.js";
+import { keccak_256 } from "@noble/hashes/sha3";
+
+// Merkle tree over wallets, matching `perp_amm::util::allowlist`: leaves are
+// keccak256 of the wallet and pairs are hashed in sorted order. An odd node
+// out is carried up to the next level unchanged.
+export function buildAllowlist(wallets: PublicKey[]) {
+  const leaves = wallets.map((wallet) =>
+    Buffer.from(keccak_256(wallet.toBytes()))
+  );
+  const levels: Buffer[][] = [leaves];
+
+  while (levels[levels.length - 1].length > 1) {
+    const level = levels[levels.length - 1];
+    const next: Buffer[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      if (i + 1 === level.length) {
+        next.push(level[i]);
+        continue;
+      }
+      const [a, b] = [level[i], level[i + 1]].sort(Buffer.compare);
+      next.push(Buffer.from(keccak_256(Buffer.concat([a, b]))));
+    }
+    levels.push(next);
+  }
+
+  const root = [...levels[levels.length - 1][0]];
+
+  // Sibling hashes from the wallet's leaf up to the root
+  const proof = (wallet: PublicKey): number[][] => {
+    let index = wallets.findIndex((w) => w.equals(wallet));
+    if (index < 0) {
+      throw new Error(`${wallet.toBase58()} is not in the allowlist`);
+    }
+
+    const siblings: number[][] = [];
+    for (const level of levels.slice(0, -1)) {
+      const sibling = index ^ 1;
+      if (sibling < level.length) siblings.push([...level[sibling]]);
+      index >>= 1;
+    }
+    return siblings;
+  };
+
+  return { root, proof };
+}
@@ -0,0 +1,98 @@
+import { Program } from "@coral-xyz/anchor";
+import { AccountMeta, PublicKey } from "@solana/web3.js";
+import { PerpAmm } from "../../target/types/perp_amm";
+
+// Derive the registry entry (AssetConfig PDA) for a whitelisted mint
+export function getAssetPda(
+  program: Program<PerpAmm>,
+  poolState: PublicKey,
+  mint: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("asset"), poolState.toBuffer(), mint.toBuffer()],
+    program.programId
+  )[0];
+}
+
+// Derive the vault holding the pool's balance of a whitelisted mint
+export function getAssetVaultPda(
+  program: Program<PerpAmm>,
+  poolState: PublicKey,
+  mint: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("asset_vault"), poolState.toBuffer(), mint.toBuffer()],
+    program.programId
+  )[0];
+}
+
+// Remaining accounts for instructions that value the pool (deposit, withdraw):
+// [asset, oracleProgram, oracleFeed] for every registered asset, in index order
+export async function getAumAccounts(
+  program: Program<PerpAmm>,
+  poolState: PublicKey
+): Promise<AccountMeta[]> {
+  const assets = await program.account.assetConfig.all([
+    { memcmp: { offset: 8, bytes: poolState.toBase58() } },
+  ]);
+  assets.sort((a, b) => a.account.index - b.account.index);
+
+  return assets.flatMap(({ publicKey, account }) => [
+    { pubkey: publicKey, isSigner: false, isWritable: false },
+    { pubkey: account.oracleProgram, isSigner: false, isWritable: false },
+    { pubkey: account.oracleFeed, isSigner: false, isWritable: false },
+  ]);
+}
+
+// Derive the registry entry (RewardStream PDA) for a reward token
+export function getRewardStreamPda(
+  program: Program<PerpAmm>,
+  poolState: PublicKey,
+  mint: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("reward_stream"), poolState.toBuffer(), mint.toBuffer()],
+    program.programId
+  )[0];
+}
+
+// Derive the vault holding a reward stream's undistributed rewards
+export function getRewardStreamVaultPda(
+  program: Program<PerpAmm>,
+  poolState: PublicKey,
+  mint: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("reward_stream_vault"), poolState.toBuffer(), mint.toBuffer()],
+    program.programId
+  )[0];
+}
+
+// Every reward stream registered to the pool, writable and in index order
+export async function getRewardStreamAccounts(
+  program: Program<PerpAmm>,
+  poolState: PublicKey
+): Promise<AccountMeta[]> {
+  const streams = await program.account.rewardStream.all([
+    { memcmp: { offset: 8, bytes: poolState.toBase58() } },
+  ]);
+  streams.sort((a, b) => a.account.index - b.account.index);
+
+  return streams.map(({ publicKey }) => ({
+    pubkey: publicKey,
+    isSigner: false,
+    isWritable: true,
+  }));
+}
+
+// Remaining accounts for deposit and withdraw: the AUM accounts followed by
+// every reward stream, so the user's stream checkpoints can be updated
+export async function getLiquidityAccounts(
+  program: Program<PerpAmm>,
+  poolState: PublicKey
+): Promise<AccountMeta[]> {
+  return [
+    ...(await getAumAccounts(program, poolState)),
+    ...(await getRewardStreamAccounts(program, poolState)),
+  ];
+}
@@ -18,8 +18,23 @@ import {
   getMint,
 } from "@solana/spl-token";
 import { initializeMarginProgram } from "./init-margin-program";
+import { getAssetPda, getAssetVaultPda } from "./aum-accounts";
+import { ALL_ROLES, ROLE_SETTLEMENT } from "./roles";
+import {
+  getLpStakeVaultPda,
+  getOutflowLimitsPda,
+  getPoolStatePda,
+  POOL_ID,
+} from "./pool-pdas";
 import BN from "bn.js";
 
+// The cloned devnet feed is never updated on localnet, so allow very old prices
+export const MAX_PRICE_AGE = 60 * 60 * 24 * 365 * 10; // 10 years
+export const MAX_CONFIDENCE_BPS = 0; // Chainlink doesn't publish a confidence interval
+export const NO_FEED_ID = Array(32).fill(0); // Chainlink feeds have no Pyth feed id
+export const DEFAULT_FEE_BPS = 10; // 0.1%
+export const DEFAULT_TARGET_WEIGHT_BPS = 5_000; // Even SOL/USDC split
+
 // Initialize AMM program for testing
 export async function setupAmmProgram(
   provider: anchor.AnchorProvider,
@@ -34,10 +49,7 @@ export async function setupAmmProgram(
   console.log("=== Starting AMM program setup ===");
 
   // Derive PDA for pool state
-  const [poolState] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_state")],
-    program.programId
-  );
+  const poolState = getPoolStatePda(program, POOL_ID);
 
   console.log("Pool State PDA:", poolState.toString());
 
@@ -47,7 +59,10 @@ export async function setupAmmProgram(
   let usdcVault: PublicKey;
   let usdcRewardVault: PublicKey;
   let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
   let solMint: PublicKey;
+  let solAsset: PublicKey;
+  let usdcAsset: PublicKey;
   let lpTokenMintKeypair: Keypair;
 
   // Set up token accounts
@@ -71,17 +86,21 @@ export async function setupAmmProgram(
 
     // Set all the configuration from the pool state
     lpTokenMint = poolStateAccount.lpTokenMint;
+    lpStakeVault = getLpStakeVaultPda(program, poolState, lpTokenMint);
     solMint = new PublicKey("So11111111111111111111111111111111111111112"); // Wrapped SOL is always this address
 
-    // Get vault PDAs from the pool state
-    solVault = poolStateAccount.solVault;
-    usdcVault = poolStateAccount.usdcVault;
     usdcRewardVault = poolStateAccount.usdcRewardVault;
 
     // We need to get the USDC mint from the pool state directly
     // since vaults are now PDAs, not token accounts
     usdcMint = poolStateAccount.usdcMint;
 
+    // Get asset registry entries and their vaults
+    solAsset = getAssetPda(program, poolState, solMint);
+    usdcAsset = getAssetPda(program, poolState, usdcMint);
+    solVault = (await program.account.assetConfig.fetch(solAsset)).vault;
+    usdcVault = (await program.account.assetConfig.fetch(usdcAsset)).vault;
+
     // Get margin vault from the pool state
     marginVault = PublicKey.findProgramAddressSync(
       [Buffer.from("margin_vault")],
@@ -346,18 +365,11 @@ export async function setupAmmProgram(
     const wrapTx = new anchor.web3.Transaction().add(wrapIx);
     await provider.sendAndConfirm(wrapTx, [admin]);
 
-    // Derive PDAs for the vaults
-    const [solVaultPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("sol_vault"), poolState.toBuffer()],
-      program.programId
-    );
-    solVault = solVaultPDA;
-
-    const [usdcVaultPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("usdc_vault"), poolState.toBuffer()],
-      program.programId
-    );
-    usdcVault = usdcVaultPDA;
+    // Derive PDAs for the asset registry entries and their vaults
+    solAsset = getAssetPda(program, poolState, solMint);
+    usdcAsset = getAssetPda(program, poolState, usdcMint);
+    solVault = getAssetVaultPda(program, poolState, solMint);
+    usdcVault = getAssetVaultPda(program, poolState, usdcMint);
 
     const [usdcRewardVaultPDA] = PublicKey.findProgramAddressSync(
       [Buffer.from("usdc_reward_vault"), poolState.toBuffer()],
@@ -369,12 +381,36 @@ export async function setupAmmProgram(
     console.log("USDC vault:", usdcVault.toString());
     console.log("USDC reward vault:", usdcRewardVault.toString());
 
-    // Initialize margin program - pass mint addresses instead of vault addresses
+    // Create a keypair for the LP token mint
+    lpTokenMintKeypair = Keypair.generate();
+    lpTokenMint = lpTokenMintKeypair.publicKey;
+
+    // Initialize Perp AMM program
+    await program.methods
+      .initialize(POOL_ID)
+      .accountsStrict({
+        admin: admin.publicKey,
+        poolState,
+        usdcMint: usdcMint,
+        usdcRewardVault: usdcRewardVault,
+        lpTokenMint,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([admin])
+      .rpc();
+
+    console.log(
+      `✓ Pool state initialized successfully! ${poolState.toString()}`
+    );
+
+    // Initialize margin program bound to the pool - pass mint addresses instead of vault addresses
     let marginInit = (await initializeMarginProgram(
       provider,
       marginProgram,
       solMint,
       usdcMint,
+      poolState,
       chainlinkProgram,
       chainlinkFeed,
       admin
@@ -384,22 +420,15 @@ export async function setupAmmProgram(
     marginUsdcVault = marginInit.marginUsdcVault;
     marginVault = marginInit.marginVault;
 
-    // Create a keypair for the LP token mint
-    lpTokenMintKeypair = Keypair.generate();
-    lpTokenMint = lpTokenMintKeypair.publicKey;
-
-    // Initialize Perp AMM program
+    // Initialize USDC reward vault
     await program.methods
-      .initialize()
+      .initializeTokenVault(Buffer.from("usdc_reward_vault"))
       .accountsStrict({
         admin: admin.publicKey,
         poolState,
-        solVault: solVault,
-        usdcVault: usdcVault,
-        solMint: solMint,
-        usdcMint: usdcMint,
-        usdcRewardVault: usdcRewardVault,
-        lpTokenMint,
+        vault: usdcRewardVault,
+        mint: usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
@@ -407,34 +436,34 @@ export async function setupAmmProgram(
       .rpc();
 
     console.log(
-      `✓ Pool state initialized successfully! ${poolState.toString()}`
+      `✓ USDC reward vault initialized successfully! ${usdcRewardVault.toString()}`
     );
 
-    // Initialize SOL vault
+    // Initialize LP token mint
     await program.methods
-      .initializeTokenVault(Buffer.from("sol_vault"))
+      .initializeLpMint()
       .accountsStrict({
         admin: admin.publicKey,
         poolState,
-        vault: solVault,
-        mint: solMint,
+        lpTokenMint,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
-      .signers([admin])
+      .signers([admin, lpTokenMintKeypair])
       .rpc();
 
-    console.log(`✓ SOL vault initialized successfully! ${solVault.toString()}`);
+    console.log("✓ LP token mint initialized successfully!");
 
-    // Initialize USDC vault
+    // Initialize the vault staked LP is held in
+    lpStakeVault = getLpStakeVaultPda(program, poolState, lpTokenMint);
     await program.methods
-      .initializeTokenVault(Buffer.from("usdc_vault"))
+      .initializeLpStakeVault()
       .accountsStrict({
         admin: admin.publicKey,
         poolState,
-        vault: usdcVault,
-        mint: usdcMint,
+        lpTokenMint,
+        lpStakeVault,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -442,18 +471,28 @@ export async function setupAmmProgram(
       .signers([admin])
       .rpc();
 
-    console.log(
-      `✓ USDC vault initialized successfully! ${usdcVault.toString()}`
-    );
+    console.log(`✓ LP stake vault initialized successfully! ${lpStakeVault.toString()}`);
 
-    // Initialize USDC reward vault
+    // Whitelist SOL, priced by Chainlink
     await program.methods
-      .initializeTokenVault(Buffer.from("usdc_reward_vault"))
+      .addAsset({
+        isStable: false,
+        oracleKind: { chainlink: {} },
+        oracleFeedId: NO_FEED_ID,
+        maxPriceAge: new BN(MAX_PRICE_AGE),
+        maxConfidenceBps: MAX_CONFIDENCE_BPS,
+        depositFeeBps: DEFAULT_FEE_BPS,
+        withdrawFeeBps: DEFAULT_FEE_BPS,
+        targetWeightBps: DEFAULT_TARGET_WEIGHT_BPS,
+      })
       .accountsStrict({
         admin: admin.publicKey,
         poolState,
-        vault: usdcRewardVault,
-        mint: usdcMint,
+        mint: solMint,
+        asset: solAsset,
+        vault: solVault,
+        oracleProgram: chainlinkProgram,
+        oracleFeed: chainlinkFeed,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -461,32 +500,43 @@ export async function setupAmmProgram(
       .signers([admin])
       .rpc();
 
-    console.log(
-      `✓ USDC reward vault initialized successfully! ${usdcRewardVault.toString()}`
-    );
+    console.log(`✓ SOL asset added successfully! ${solAsset.toString()}`);
 
-    // Initialize LP token mint
+    // Whitelist USDC as a stable asset (valued at $1, no oracle)
     await program.methods
-      .initializeLpMint()
+      .addAsset({
+        isStable: true,
+        oracleKind: { chainlink: {} },
+        oracleFeedId: NO_FEED_ID,
+        maxPriceAge: new BN(0),
+        maxConfidenceBps: 0,
+        depositFeeBps: DEFAULT_FEE_BPS,
+        withdrawFeeBps: DEFAULT_FEE_BPS,
+        targetWeightBps: DEFAULT_TARGET_WEIGHT_BPS,
+      })
       .accountsStrict({
         admin: admin.publicKey,
         poolState,
-        lpTokenMint,
+        mint: usdcMint,
+        asset: usdcAsset,
+        vault: usdcVault,
+        oracleProgram: SystemProgram.programId,
+        oracleFeed: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
-      .signers([admin, lpTokenMintKeypair])
+      .signers([admin])
       .rpc();
 
-    console.log("✓ LP token mint initialized successfully!");
+    console.log(`✓ USDC asset added successfully! ${usdcAsset.toString()}`);
 
     // Add authorities to the pool state
     console.log("Adding authorities to pool state...");
 
     // Add the perp amm program as an authority for CPI calls
     await program.methods
-      .addAuthority(program.programId)
+      .addAuthority(program.programId, ROLE_SETTLEMENT)
       .accountsStrict({
         admin: admin.publicKey,
         poolState,
@@ -498,7 +548,7 @@ export async function setupAmmProgram(
 
     // Add the admin as an authority
     await program.methods
-      .addAuthority(admin.publicKey)
+      .addAuthority(admin.publicKey, ALL_ROLES)
       .accountsStrict({
         admin: admin.publicKey,
         poolState,
@@ -507,15 +557,33 @@ export async function setupAmmProgram(
       .rpc();
 
     console.log("✓ Added admin as authority");
+
+    // Create the outflow limits with the caps off; tests that need them turn them on
+    await program.methods
+      .setOutflowLimits(new anchor.BN(0), 0, 0)
+      .accountsStrict({
+        admin: admin.publicKey,
+        poolState,
+        outflowLimits: getOutflowLimitsPda(program, poolState),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    console.log("✓ Created outflow limits");
   }
 
   return {
     poolState,
+    outflowLimits: getOutflowLimitsPda(program, poolState),
     solMint,
     usdcMint,
     lpTokenMint,
+    lpStakeVault,
     solVault,
     usdcVault,
+    solAsset,
+    usdcAsset,
     usdcRewardVault,
     marginSolVault,
     marginUsdcVault,
@@ -9,11 +9,17 @@ import {
 
 const WITHDRAWAL_TIMELOCK = 1; // 1 seconds
 
+// The cloned devnet feed is never updated on localnet, so allow very old prices
+const MAX_PRICE_AGE = 60 * 60 * 24 * 365 * 10; // 10 years
+const MAX_CONFIDENCE_BPS = 0; // Chainlink doesn't publish a confidence interval
+const NO_FEED_ID = Array(32).fill(0); // Chainlink feeds have no Pyth feed id
+
 export async function initializeMarginProgram(
   provider: anchor.AnchorProvider,
   program: Program<PerpMarginAccounts>,
   solMint: PublicKey,
   usdcMint: PublicKey,
+  poolState: PublicKey,
   chainlinkProgram: PublicKey,
   chainlinkFeed: PublicKey,
   admin: anchor.web3.Keypair
@@ -80,14 +86,19 @@ export async function initializeMarginProgram(
     await program.methods
       .initialize(
         new anchor.BN(WITHDRAWAL_TIMELOCK),
+        { chainlink: {} },
         chainlinkProgram,
-        chainlinkFeed
+        chainlinkFeed,
+        NO_FEED_ID,
+        new anchor.BN(MAX_PRICE_AGE),
+        MAX_CONFIDENCE_BPS
       )
       .accountsStrict({
         authority: admin.publicKey,
         marginVault,
         marginSolVault: solVaultAccount.address,
         marginUsdcVault: usdcVaultAccount.address,
+        poolState,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -0,0 +1,14 @@
+// Pausable operation bits, mirroring `perp_amm::state::pause_flags`.
+// The pool and the margin vault each keep their own set.
+export const PAUSE_DEPOSITS = 1 << 0;
+export const PAUSE_WITHDRAWALS = 1 << 1;
+export const PAUSE_SETTLEMENTS = 1 << 2;
+export const PAUSE_LIQUIDATIONS = 1 << 3;
+export const PAUSE_REWARD_CLAIMS = 1 << 4;
+
+export const PAUSE_ALL =
+  PAUSE_DEPOSITS |
+  PAUSE_WITHDRAWALS |
+  PAUSE_SETTLEMENTS |
+  PAUSE_LIQUIDATIONS |
+  PAUSE_REWARD_CLAIMS;
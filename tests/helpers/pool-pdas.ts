@@ -0,0 +1,57 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { PerpAmm } from "../../target/types/perp_amm";
+
+// Id of the pool the tests run against; other ids are independent pools
+export const POOL_ID = new anchor.BN(0);
+
+// Derive the pool state PDA for a pool id
+export function getPoolStatePda(
+  program: Program<PerpAmm>,
+  poolId: anchor.BN = POOL_ID
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("pool_state"), poolId.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  )[0];
+}
+
+// Derive a user's state in a pool
+export function getUserStatePda(
+  program: Program<PerpAmm>,
+  poolState: PublicKey,
+  user: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("user_state"), poolState.toBuffer(), user.toBuffer()],
+    program.programId
+  )[0];
+}
+
+// Derive a pool's outflow limits for `admin_withdraw`
+export function getOutflowLimitsPda(
+  program: Program<PerpAmm>,
+  poolState: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("outflow_limits"), poolState.toBuffer()],
+    program.programId
+  )[0];
+}
+
+// Derive the vault holding a pool's staked LP tokens
+export function getLpStakeVaultPda(
+  program: Program<PerpAmm>,
+  poolState: PublicKey,
+  lpTokenMint: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("lp_stake_vault"),
+      poolState.toBuffer(),
+      lpTokenMint.toBuffer(),
+    ],
+    program.programId
+  )[0];
+}
@@ -0,0 +1,13 @@
+// Authority role bits, mirroring `perp_amm::state::roles`. Both programs use them.
+export const ROLE_SETTLEMENT = 1 << 0;
+export const ROLE_LIQUIDATOR = 1 << 1;
+export const ROLE_FEE_COLLECTOR = 1 << 2;
+export const ROLE_REWARDS_MANAGER = 1 << 3;
+export const ROLE_PAUSER = 1 << 4;
+
+export const ALL_ROLES =
+  ROLE_SETTLEMENT |
+  ROLE_LIQUIDATOR |
+  ROLE_FEE_COLLECTOR |
+  ROLE_REWARDS_MANAGER |
+  ROLE_PAUSER;
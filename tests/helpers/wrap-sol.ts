@@ -29,3 +29,14 @@ export async function wrapSol(
 
   await provider.sendAndConfirm(wrapTx, [signer]);
 }
+
+// Temporary account the margin program uses to unwrap native SOL withdrawals
+export function getSolUnwrapPda(
+  programId: PublicKey,
+  marginAccount: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("sol_unwrap"), marginAccount.toBuffer()],
+    programId
+  )[0];
+}
@@ -17,8 +17,9 @@ import {
 import { assert } from "chai";
 import BN from "bn.js";
 import * as dotenv from "dotenv";
-import { wrapSol } from "./helpers/wrap-sol";
+import { wrapSol, getSolUnwrapPda } from "./helpers/wrap-sol";
 import { setupAmmProgram } from "./helpers/init-amm-program";
+import { ROLE_LIQUIDATOR } from "./helpers/roles";
 import { PerpAmm } from "../target/types/perp_amm";
 
 dotenv.config();
@@ -58,6 +59,7 @@ describe("perp-margin-accounts", () => {
 
   // Set up pool state
   let poolState: PublicKey;
+  let outflowLimits: PublicKey;
 
   // Set up token accounts
   let adminSolAccount: PublicKey;
@@ -105,6 +107,7 @@ describe("perp-margin-accounts", () => {
 
     // Retrieve configuration values from the setup helper.
     poolState = setup.poolState;
+    outflowLimits = setup.outflowLimits;
     solMint = setup.solMint;
     usdcMint = setup.usdcMint;
     lpTokenMint = setup.lpTokenMint;
@@ -222,7 +225,7 @@ describe("perp-margin-accounts", () => {
       try {
         // User1 deposit SOL
         await marginProgram.methods
-          .depositMargin(new BN(LAMPORTS_PER_SOL)) // 1 SOL
+          .depositMargin(new BN(LAMPORTS_PER_SOL), []) // 1 SOL
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -231,13 +234,14 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
 
         // User2 deposit USDC
         await marginProgram.methods
-          .depositMargin(new BN(10_000_000)) // 10 USDC
+          .depositMargin(new BN(10_000_000), []) // 10 USDC
           .accountsStrict({
             marginAccount: user2MarginAccount,
             marginVault: marginVault,
@@ -246,6 +250,7 @@ describe("perp-margin-accounts", () => {
             owner: user2.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user2])
           .rpc();
@@ -293,8 +298,16 @@ describe("perp-margin-accounts", () => {
               userUsdcAccount: user1UsdcAccount,
               poolState: poolStatePda,
               poolVaultAccount: user1SolAccount, // Mock account
-              chainlinkProgram: chainlinkProgram,
-              chainlinkFeed: chainlinkFeed,
+              poolAsset: user1SolAccount, // Mock account
+              poolOutflowLimits: outflowLimits,
+              oracleProgram: chainlinkProgram,
+              oracleFeed: chainlinkFeed,
+              owner: user1.publicKey,
+              solUnwrapAccount: getSolUnwrapPda(
+                marginProgram.programId,
+                user1MarginAccount
+              ),
+              nativeMint: NATIVE_MINT,
               authority: admin.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               liquidityPoolProgram: mockAmmProgramId,
@@ -351,8 +364,16 @@ describe("perp-margin-accounts", () => {
               userUsdcAccount: user2UsdcAccount,
               poolState: poolStatePda,
               poolVaultAccount: user2UsdcAccount, // Mock account
-              chainlinkProgram: chainlinkProgram,
-              chainlinkFeed: chainlinkFeed,
+              poolAsset: user2UsdcAccount, // Mock account
+              poolOutflowLimits: outflowLimits,
+              oracleProgram: chainlinkProgram,
+              oracleFeed: chainlinkFeed,
+              owner: user2.publicKey,
+              solUnwrapAccount: getSolUnwrapPda(
+                marginProgram.programId,
+                user2MarginAccount
+              ),
+              nativeMint: NATIVE_MINT,
               authority: admin.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               liquidityPoolProgram: mockAmmProgramId,
@@ -385,7 +406,7 @@ describe("perp-margin-accounts", () => {
         try {
           // Instead of using setTestFees, we'll do more deposits to generate fees
           await marginProgram.methods
-            .depositMargin(new BN(2 * LAMPORTS_PER_SOL)) // 2 SOL
+            .depositMargin(new BN(2 * LAMPORTS_PER_SOL), []) // 2 SOL
             .accountsStrict({
               marginAccount: user1MarginAccount,
               marginVault: marginVault,
@@ -394,12 +415,13 @@ describe("perp-margin-accounts", () => {
               owner: user1.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user1])
             .rpc();
 
           await marginProgram.methods
-            .depositMargin(new BN(20_000_000)) // 20 USDC
+            .depositMargin(new BN(20_000_000), []) // 20 USDC
             .accountsStrict({
               marginAccount: user2MarginAccount,
               marginVault: marginVault,
@@ -408,6 +430,7 @@ describe("perp-margin-accounts", () => {
               owner: user2.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user2])
             .rpc();
@@ -580,5 +603,47 @@ describe("perp-margin-accounts", () => {
         );
       }
     });
+
+    it("should not let an authority without the fee collector role claim fees", async () => {
+      await marginProgram.methods
+        .addAuthority(user1.publicKey, ROLE_LIQUIDATOR)
+        .accountsStrict({ authority: admin.publicKey, marginVault })
+        .signers([admin])
+        .rpc();
+
+      const user1SolAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          user1,
+          solMint,
+          user1.publicKey
+        )
+      ).address;
+
+      try {
+        await marginProgram.methods
+          .claimFees()
+          .accountsStrict({
+            marginVault,
+            marginSolVault,
+            marginUsdcVault,
+            adminSolAccount: user1SolAccount,
+            adminUsdcAccount: user1UsdcAccount,
+            authority: user1.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user1])
+          .rpc();
+        assert.fail("Expected transaction to fail without the fee collector role");
+      } catch (error: any) {
+        assert.include(error.message, "UnauthorizedExecution");
+      } finally {
+        await marginProgram.methods
+          .removeAuthority(user1.publicKey)
+          .accountsStrict({ authority: admin.publicKey, marginVault })
+          .signers([admin])
+          .rpc();
+      }
+    });
   });
 });
@@ -240,7 +240,7 @@ describe("perp-margin-accounts", () => {
 
       // Deposit WSOL
       await marginProgram.methods
-        .depositMargin(solDepositAmount)
+        .depositMargin(solDepositAmount, [])
         .accountsStrict({
           marginAccount: user1MarginAccount,
           marginVault: marginVault,
@@ -249,6 +249,7 @@ describe("perp-margin-accounts", () => {
           owner: user1.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
         })
         .signers([user1])
         .rpc();
@@ -304,7 +305,7 @@ describe("perp-margin-accounts", () => {
 
       // Deposit USDC
       await marginProgram.methods
-        .depositMargin(usdcDepositAmount)
+        .depositMargin(usdcDepositAmount, [])
         .accountsStrict({
           marginAccount: user2MarginAccount,
           marginVault: marginVault,
@@ -313,6 +314,7 @@ describe("perp-margin-accounts", () => {
           owner: user2.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
         })
         .signers([user2])
         .rpc();
@@ -380,7 +382,7 @@ describe("perp-margin-accounts", () => {
         await marginProgram.account.marginAccount.fetch(user1MarginAccount);
 
       await marginProgram.methods
-        .depositMargin(additionalSolDeposit)
+        .depositMargin(additionalSolDeposit, [])
         .accountsStrict({
           marginAccount: user1MarginAccount,
           marginVault: marginVault,
@@ -389,6 +391,7 @@ describe("perp-margin-accounts", () => {
           owner: user1.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
         })
         .signers([user1])
         .rpc();
@@ -448,7 +451,7 @@ describe("perp-margin-accounts", () => {
       const additionalUsdcDeposit = new BN(5_000_000); // 5 USDC
 
       await marginProgram.methods
-        .depositMargin(additionalUsdcDeposit)
+        .depositMargin(additionalUsdcDeposit, [])
         .accountsStrict({
           marginAccount: user2MarginAccount,
           marginVault: marginVault,
@@ -457,6 +460,7 @@ describe("perp-margin-accounts", () => {
           owner: user2.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
         })
         .signers([user2])
         .rpc();
@@ -502,7 +506,7 @@ describe("perp-margin-accounts", () => {
     it("should fail to deposit if amount is zero", async () => {
       try {
         await marginProgram.methods
-          .depositMargin(new BN(0))
+          .depositMargin(new BN(0), [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -511,6 +515,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -529,7 +534,7 @@ describe("perp-margin-accounts", () => {
       try {
         // Try to deposit to user2's account using user1's signature
         await marginProgram.methods
-          .depositMargin(new BN(1_000_000))
+          .depositMargin(new BN(1_000_000), [])
           .accountsStrict({
             marginAccount: user2MarginAccount,
             marginVault: marginVault,
@@ -538,6 +543,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey, // This should be user2
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -569,7 +575,7 @@ describe("perp-margin-accounts", () => {
 
       try {
         await marginProgram.methods
-          .depositMargin(excessAmount)
+          .depositMargin(excessAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -578,6 +584,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -591,5 +598,51 @@ describe("perp-margin-accounts", () => {
         );
       }
     });
+
+    it("should deposit native SOL without a WSOL account", async () => {
+      const marginSolVaultBefore = await getAccount(
+        provider.connection,
+        marginSolVault
+      );
+      const marginAccountBefore =
+        await marginProgram.account.marginAccount.fetch(user1MarginAccount);
+
+      await marginProgram.methods
+        .depositMarginSol(solDepositAmount, [])
+        .accountsStrict({
+          marginAccount: user1MarginAccount,
+          marginVault: marginVault,
+          marginSolVault: marginSolVault,
+          owner: user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
+        })
+        .signers([user1])
+        .rpc();
+
+      const marginSolVaultAfter = await getAccount(
+        provider.connection,
+        marginSolVault
+      );
+      const marginAccountAfter =
+        await marginProgram.account.marginAccount.fetch(user1MarginAccount);
+
+      assert.equal(
+        new BN(marginSolVaultAfter.amount.toString())
+          .sub(new BN(marginSolVaultBefore.amount.toString()))
+          .toString(),
+        solDepositAmount.toString(),
+        "WSOL vault balance should increase by the lamports deposited"
+      );
+
+      assert.equal(
+        marginAccountAfter.solBalance
+          .sub(marginAccountBefore.solBalance)
+          .toString(),
+        solDepositAmount.toString(),
+        "Margin account SOL balance should increase by deposit amount"
+      );
+    });
   });
 });
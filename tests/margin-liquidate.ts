@@ -52,6 +52,8 @@ describe("perp-margin-accounts", () => {
   let marginVault: PublicKey;
   let solVault: PublicKey;
   let usdcVault: PublicKey;
+  let solAsset: PublicKey;
+  let usdcAsset: PublicKey;
   let lpTokenMint: PublicKey;
 
   // Set up pool state
@@ -118,6 +120,8 @@ describe("perp-margin-accounts", () => {
     lpTokenMint = setup.lpTokenMint;
     solVault = setup.solVault;
     usdcVault = setup.usdcVault;
+    solAsset = setup.solAsset;
+    usdcAsset = setup.usdcAsset;
     adminSolAccount = setup.adminSolAccount;
     adminUsdcAccount = setup.adminUsdcAccount;
     user1UsdcAccount = setup.user1UsdcAccount;
@@ -227,7 +231,7 @@ describe("perp-margin-accounts", () => {
 
       // First, deposit SOL into user1's margin account.
       await marginProgram.methods
-        .depositMargin(initialSolDeposit)
+        .depositMargin(initialSolDeposit, [])
         .accountsStrict({
           marginAccount: user1MarginAccount,
           marginVault: marginVault,
@@ -236,6 +240,7 @@ describe("perp-margin-accounts", () => {
           owner: user1.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
         })
         .signers([user1])
         .rpc();
@@ -263,8 +268,7 @@ describe("perp-margin-accounts", () => {
           marginVaultTokenAccount: marginSolVault,
           poolState: poolState,
           poolVaultAccount: solVault,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          poolAsset: solAsset,
           authority: admin.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           liquidityPoolProgram: ammProgram.programId,
@@ -298,7 +302,7 @@ describe("perp-margin-accounts", () => {
     it("should liquidate a margin account's USDC balance", async () => {
       // Deposit USDC into user2's margin account.
       await marginProgram.methods
-        .depositMargin(initialUsdcDeposit)
+        .depositMargin(initialUsdcDeposit, [])
         .accountsStrict({
           marginAccount: user2MarginAccount,
           marginVault: marginVault,
@@ -307,6 +311,7 @@ describe("perp-margin-accounts", () => {
           owner: user2.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
         })
         .signers([user2])
         .rpc();
@@ -334,8 +339,7 @@ describe("perp-margin-accounts", () => {
           marginVaultTokenAccount: marginUsdcVault,
           poolState: poolState,
           poolVaultAccount: usdcVault,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          poolAsset: usdcAsset,
           authority: admin.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           liquidityPoolProgram: ammProgram.programId,
@@ -377,8 +381,7 @@ describe("perp-margin-accounts", () => {
             marginVaultTokenAccount: marginSolVault,
             poolState: poolState,
             poolVaultAccount: solVault,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            poolAsset: solAsset,
             authority: admin.publicKey, // Unauthorized!
             tokenProgram: TOKEN_PROGRAM_ID,
             liquidityPoolProgram: ammProgram.programId,
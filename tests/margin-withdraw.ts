@@ -18,8 +18,9 @@ import { assert } from "chai";
 import BN from "bn.js";
 import * as dotenv from "dotenv";
 import { initializeMarginProgram } from "./helpers/init-margin-program";
-import { wrapSol } from "./helpers/wrap-sol";
+import { wrapSol, getSolUnwrapPda } from "./helpers/wrap-sol";
 import { setupAmmProgram } from "./helpers/init-amm-program";
+import { getLiquidityAccounts } from "./helpers/aum-accounts";
 import { PerpAmm } from "../target/types/perp_amm";
 
 dotenv.config();
@@ -55,10 +56,13 @@ describe("perp-margin-accounts", () => {
   let marginVault: PublicKey;
   let solVault: PublicKey;
   let usdcVault: PublicKey;
+  let solAsset: PublicKey;
   let lpTokenMint: PublicKey;
+  let lpStakeVault: PublicKey;
 
   // Set up pool state
   let poolState: PublicKey;
+  let outflowLimits: PublicKey;
 
   // Set up token accounts
   let adminSolAccount: PublicKey;
@@ -72,10 +76,6 @@ describe("perp-margin-accounts", () => {
   let user1State: PublicKey;
   let user2State: PublicKey;
 
-  // User LP token accounts
-  let user1LpTokenAccount: PublicKey;
-  let user2LpTokenAccount: PublicKey;
-
   // User margin accounts
   let user1MarginAccount: PublicKey;
   let user2MarginAccount: PublicKey;
@@ -113,11 +113,14 @@ describe("perp-margin-accounts", () => {
 
     // Retrieve configuration values from the setup helper.
     poolState = setup.poolState;
+    outflowLimits = setup.outflowLimits;
     solMint = setup.solMint;
     usdcMint = setup.usdcMint;
     lpTokenMint = setup.lpTokenMint;
+    lpStakeVault = setup.lpStakeVault;
     solVault = setup.solVault;
     usdcVault = setup.usdcVault;
+    solAsset = setup.solAsset;
     adminSolAccount = setup.adminSolAccount;
     adminUsdcAccount = setup.adminUsdcAccount;
     user1UsdcAccount = setup.user1UsdcAccount;
@@ -128,25 +131,6 @@ describe("perp-margin-accounts", () => {
 
     console.log("Margin vault:", marginVault.toString());
 
-    // Create LP token accounts for users
-    user1LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user1.publicKey
-      )
-    ).address;
-
-    user2LpTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        admin,
-        lpTokenMint,
-        user2.publicKey
-      )
-    ).address;
-
     // Derive user states
     [user1State] = PublicKey.findProgramAddressSync(
       [Buffer.from("user_state"), user1.publicKey.toBuffer()],
@@ -248,7 +232,7 @@ describe("perp-margin-accounts", () => {
 
         // Deposit SOL to user1's margin account
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -257,6 +241,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -265,7 +250,7 @@ describe("perp-margin-accounts", () => {
 
         // Deposit USDC to user2's margin account
         await marginProgram.methods
-          .depositMargin(usdcDepositAmount)
+          .depositMargin(usdcDepositAmount, [])
           .accountsStrict({
             marginAccount: user2MarginAccount,
             marginVault: marginVault,
@@ -274,6 +259,7 @@ describe("perp-margin-accounts", () => {
             owner: user2.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user2])
           .rpc();
@@ -429,7 +415,7 @@ describe("perp-margin-accounts", () => {
           );
 
           await marginProgram.methods
-            .depositMargin(solDepositAmount)
+            .depositMargin(solDepositAmount, [])
             .accountsStrict({
               marginAccount: user1MarginAccount,
               marginVault: marginVault,
@@ -438,6 +424,7 @@ describe("perp-margin-accounts", () => {
               owner: user1.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user1])
             .rpc();
@@ -454,7 +441,7 @@ describe("perp-margin-accounts", () => {
         );
 
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -463,6 +450,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -533,7 +521,7 @@ describe("perp-margin-accounts", () => {
           );
 
           await marginProgram.methods
-            .depositMargin(solDepositAmount)
+            .depositMargin(solDepositAmount, [])
             .accountsStrict({
               marginAccount: user1MarginAccount,
               marginVault: marginVault,
@@ -542,6 +530,7 @@ describe("perp-margin-accounts", () => {
               owner: user1.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user1])
             .rpc();
@@ -558,7 +547,7 @@ describe("perp-margin-accounts", () => {
         );
 
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -567,6 +556,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -652,7 +642,7 @@ describe("perp-margin-accounts", () => {
           );
 
           await marginProgram.methods
-            .depositMargin(solDepositAmount)
+            .depositMargin(solDepositAmount, [])
             .accountsStrict({
               marginAccount: user1MarginAccount,
               marginVault: marginVault,
@@ -661,6 +651,7 @@ describe("perp-margin-accounts", () => {
               owner: user1.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user1])
             .rpc();
@@ -693,7 +684,7 @@ describe("perp-margin-accounts", () => {
         );
 
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -702,6 +693,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -761,7 +753,7 @@ describe("perp-margin-accounts", () => {
           );
 
           await marginProgram.methods
-            .depositMargin(solDepositAmount)
+            .depositMargin(solDepositAmount, [])
             .accountsStrict({
               marginAccount: user1MarginAccount,
               marginVault: marginVault,
@@ -770,6 +762,7 @@ describe("perp-margin-accounts", () => {
               owner: user1.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user1])
             .rpc();
@@ -786,7 +779,7 @@ describe("perp-margin-accounts", () => {
         );
 
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -795,6 +788,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -859,8 +853,16 @@ describe("perp-margin-accounts", () => {
             authorityTokenAccount: adminSolAccount,
             poolState: poolState,
             poolVaultAccount: solVault,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            poolAsset: solAsset,
+            poolOutflowLimits: outflowLimits,
+            oracleProgram: chainlinkProgram,
+            oracleFeed: chainlinkFeed,
+            owner: user1.publicKey,
+            solUnwrapAccount: getSolUnwrapPda(
+              marginProgram.programId,
+              user1MarginAccount
+            ),
+            nativeMint: NATIVE_MINT,
             authority: admin.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             liquidityPoolProgram: ammProgram.programId,
@@ -918,7 +920,7 @@ describe("perp-margin-accounts", () => {
         ) {
           // Add more funds if needed
           await marginProgram.methods
-            .depositMargin(usdcDepositAmount)
+            .depositMargin(usdcDepositAmount, [])
             .accountsStrict({
               marginAccount: user2MarginAccount,
               marginVault: marginVault,
@@ -927,6 +929,7 @@ describe("perp-margin-accounts", () => {
               owner: user2.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user2])
             .rpc();
@@ -935,7 +938,7 @@ describe("perp-margin-accounts", () => {
         console.log("Need to initialize account first");
         // Initialize account by depositing
         await marginProgram.methods
-          .depositMargin(usdcDepositAmount)
+          .depositMargin(usdcDepositAmount, [])
           .accountsStrict({
             marginAccount: user2MarginAccount,
             marginVault: marginVault,
@@ -944,6 +947,7 @@ describe("perp-margin-accounts", () => {
             owner: user2.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user2])
           .rpc();
@@ -1003,8 +1007,16 @@ describe("perp-margin-accounts", () => {
             authorityTokenAccount: adminSolAccount,
             poolState: poolState,
             poolVaultAccount: solVault,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            poolAsset: solAsset,
+            poolOutflowLimits: outflowLimits,
+            oracleProgram: chainlinkProgram,
+            oracleFeed: chainlinkFeed,
+            owner: user2.publicKey,
+            solUnwrapAccount: getSolUnwrapPda(
+              marginProgram.programId,
+              user2MarginAccount
+            ),
+            nativeMint: NATIVE_MINT,
             authority: admin.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             liquidityPoolProgram: ammProgram.programId,
@@ -1066,7 +1078,7 @@ describe("perp-margin-accounts", () => {
           );
 
           await marginProgram.methods
-            .depositMargin(solDepositAmount)
+            .depositMargin(solDepositAmount, [])
             .accountsStrict({
               marginAccount: user1MarginAccount,
               marginVault: marginVault,
@@ -1075,6 +1087,7 @@ describe("perp-margin-accounts", () => {
               owner: user1.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user1])
             .rpc();
@@ -1091,7 +1104,7 @@ describe("perp-margin-accounts", () => {
         );
 
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -1100,6 +1113,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -1155,8 +1169,16 @@ describe("perp-margin-accounts", () => {
             authorityTokenAccount: adminSolAccount,
             poolState: poolState,
             poolVaultAccount: solVault,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            poolAsset: solAsset,
+            poolOutflowLimits: outflowLimits,
+            oracleProgram: chainlinkProgram,
+            oracleFeed: chainlinkFeed,
+            owner: user1.publicKey,
+            solUnwrapAccount: getSolUnwrapPda(
+              marginProgram.programId,
+              user1MarginAccount
+            ),
+            nativeMint: NATIVE_MINT,
             authority: admin.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             liquidityPoolProgram: ammProgram.programId,
@@ -1204,7 +1226,7 @@ describe("perp-margin-accounts", () => {
           );
 
           await marginProgram.methods
-            .depositMargin(solDepositAmount)
+            .depositMargin(solDepositAmount, [])
             .accountsStrict({
               marginAccount: user1MarginAccount,
               marginVault: marginVault,
@@ -1213,6 +1235,7 @@ describe("perp-margin-accounts", () => {
               owner: user1.publicKey,
               tokenProgram: TOKEN_PROGRAM_ID,
               systemProgram: SystemProgram.programId,
+              allowlistEntry: null,
             })
             .signers([user1])
             .rpc();
@@ -1229,7 +1252,7 @@ describe("perp-margin-accounts", () => {
         );
 
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -1238,6 +1261,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -1283,8 +1307,16 @@ describe("perp-margin-accounts", () => {
             authorityTokenAccount: adminSolAccount,
             poolState: poolState,
             poolVaultAccount: solVault,
-            chainlinkProgram: chainlinkProgram,
-            chainlinkFeed: chainlinkFeed,
+            poolAsset: solAsset,
+            poolOutflowLimits: outflowLimits,
+            oracleProgram: chainlinkProgram,
+            oracleFeed: chainlinkFeed,
+            owner: user1.publicKey,
+            solUnwrapAccount: getSolUnwrapPda(
+              marginProgram.programId,
+              user1MarginAccount
+            ),
+            nativeMint: NATIVE_MINT,
             authority: admin.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             liquidityPoolProgram: ammProgram.programId,
@@ -1351,20 +1383,21 @@ describe("perp-margin-accounts", () => {
 
       // Deposit WSOL
       await ammProgram.methods
-        .deposit(initialSolDeposit)
+        .deposit(initialSolDeposit, new BN(0), [])
         .accountsStrict({
           user: user1.publicKey,
           poolState,
           userTokenAccount: user1SolAccount,
           vaultAccount: solVault,
+          asset: solAsset,
           userState: user1State,
           lpTokenMint,
-          userLpTokenAccount: user1LpTokenAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          lpStakeVault,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          allowlistEntry: null,
         })
+        .remainingAccounts(await getLiquidityAccounts(ammProgram, poolState))
         .signers([user1])
         .rpc();
 
@@ -1385,7 +1418,7 @@ describe("perp-margin-accounts", () => {
           user1
         );
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -1394,6 +1427,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -1468,8 +1502,16 @@ describe("perp-margin-accounts", () => {
           authorityTokenAccount: adminSolAccount,
           poolState: poolState,
           poolVaultAccount: solVault, // using the SOL vault for SOL withdrawal
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          poolAsset: solAsset,
+          poolOutflowLimits: outflowLimits,
+          oracleProgram: chainlinkProgram,
+          oracleFeed: chainlinkFeed,
+          owner: user1.publicKey,
+          solUnwrapAccount: getSolUnwrapPda(
+            marginProgram.programId,
+            user1MarginAccount
+          ),
+          nativeMint: NATIVE_MINT,
           authority: admin.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           liquidityPoolProgram: ammProgram.programId,
@@ -1529,20 +1571,21 @@ describe("perp-margin-accounts", () => {
 
       // Deposit WSOL
       await ammProgram.methods
-        .deposit(initialSolDeposit)
+        .deposit(initialSolDeposit, new BN(0), [])
         .accountsStrict({
           user: user1.publicKey,
           poolState,
           userTokenAccount: user1SolAccount,
           vaultAccount: solVault,
+          asset: solAsset,
           userState: user1State,
           lpTokenMint,
-          userLpTokenAccount: user1LpTokenAccount,
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          lpStakeVault,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          allowlistEntry: null,
         })
+        .remainingAccounts(await getLiquidityAccounts(ammProgram, poolState))
         .signers([user1])
         .rpc();
 
@@ -1563,7 +1606,7 @@ describe("perp-margin-accounts", () => {
           user1
         );
         await marginProgram.methods
-          .depositMargin(solDepositAmount)
+          .depositMargin(solDepositAmount, [])
           .accountsStrict({
             marginAccount: user1MarginAccount,
             marginVault: marginVault,
@@ -1572,6 +1615,7 @@ describe("perp-margin-accounts", () => {
             owner: user1.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            allowlistEntry: null,
           })
           .signers([user1])
           .rpc();
@@ -1651,8 +1695,16 @@ describe("perp-margin-accounts", () => {
           authorityTokenAccount: adminSolAccount,
           poolState: poolState,
           poolVaultAccount: solVault, // using the SOL vault for SOL withdrawal
-          chainlinkProgram: chainlinkProgram,
-          chainlinkFeed: chainlinkFeed,
+          poolAsset: solAsset,
+          poolOutflowLimits: outflowLimits,
+          oracleProgram: chainlinkProgram,
+          oracleFeed: chainlinkFeed,
+          owner: user1.publicKey,
+          solUnwrapAccount: getSolUnwrapPda(
+            marginProgram.programId,
+            user1MarginAccount
+          ),
+          nativeMint: NATIVE_MINT,
           authority: admin.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           liquidityPoolProgram: ammProgram.programId,
@@ -1692,5 +1744,130 @@ describe("perp-margin-accounts", () => {
         "Margin account should decrease by > withdrawal amount"
       );
     });
+
+    it("should pay a native SOL withdrawal to the owner's wallet", async () => {
+      // Fund the margin account straight from the wallet
+      await marginProgram.methods
+        .depositMarginSol(solDepositAmount, [])
+        .accountsStrict({
+          marginAccount: user1MarginAccount,
+          marginVault: marginVault,
+          marginSolVault: marginSolVault,
+          owner: user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          allowlistEntry: null,
+        })
+        .signers([user1])
+        .rpc();
+
+      // Requests are rate limited by the timelock too
+      await new Promise((resolve) =>
+        setTimeout(resolve, (withdrawalTimelock + 1) * 1000)
+      );
+
+      await marginProgram.methods
+        .requestWithdrawalSol(solWithdrawAmount)
+        .accountsStrict({
+          marginAccount: user1MarginAccount,
+          marginVault: marginVault,
+          owner: user1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc();
+
+      const requested = await marginProgram.account.marginAccount.fetch(
+        user1MarginAccount
+      );
+      assert.isTrue(
+        requested.unwrapSolWithdrawal,
+        "Withdrawal should be flagged for unwrapping"
+      );
+
+      await new Promise((resolve) =>
+        setTimeout(resolve, (withdrawalTimelock + 1) * 1000)
+      );
+
+      const adminSolAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          admin,
+          solMint,
+          admin.publicKey
+        )
+      ).address;
+      const solUnwrapAccount = getSolUnwrapPda(
+        marginProgram.programId,
+        user1MarginAccount
+      );
+      const lamportsBefore = await provider.connection.getBalance(
+        user1.publicKey
+      );
+      const userWsolBefore = await getAccount(
+        provider.connection,
+        user1SolAccount
+      );
+
+      await marginProgram.methods
+        .executeWithdrawal(
+          new BN(0), // pnl_update
+          new BN(0), // locked_sol
+          new BN(0), // locked_usdc
+          new BN(0), // sol_fees_owed
+          new BN(0) // usdc_fees_owed
+        )
+        .accountsStrict({
+          marginAccount: user1MarginAccount,
+          marginVault: marginVault,
+          marginSolVault: marginSolVault,
+          marginUsdcVault: marginUsdcVault,
+          userSolAccount: user1SolAccount,
+          userUsdcAccount: user1UsdcAccount,
+          authorityTokenAccount: adminSolAccount,
+          poolState: poolState,
+          poolVaultAccount: solVault,
+          poolAsset: solAsset,
+          poolOutflowLimits: outflowLimits,
+          oracleProgram: chainlinkProgram,
+          oracleFeed: chainlinkFeed,
+          owner: user1.publicKey,
+          solUnwrapAccount,
+          nativeMint: NATIVE_MINT,
+          authority: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          liquidityPoolProgram: ammProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      const lamportsAfter = await provider.connection.getBalance(
+        user1.publicKey
+      );
+      const userWsolAfter = await getAccount(
+        provider.connection,
+        user1SolAccount
+      );
+      const executed = await marginProgram.account.marginAccount.fetch(
+        user1MarginAccount
+      );
+
+      assert.equal(
+        lamportsAfter - lamportsBefore,
+        solWithdrawAmount.toNumber(),
+        "Owner's wallet should receive the withdrawal as native SOL"
+      );
+      assert.equal(
+        userWsolAfter.amount.toString(),
+        userWsolBefore.amount.toString(),
+        "Owner's WSOL account should be untouched"
+      );
+      assert.isFalse(executed.unwrapSolWithdrawal);
+      assert.isNull(
+        await provider.connection.getAccountInfo(solUnwrapAccount),
+        "Temporary unwrap account should be closed"
+      );
+    });
   });
 });